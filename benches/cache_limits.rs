@@ -0,0 +1,54 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use serde_json::{Map, Value};
+use serde_toon::{CacheLimits, EncodeOptions};
+
+const ROWS: usize = 4000;
+const FIELDS: usize = 8;
+// Cells cycle through this many distinct strings, so each repeats ~8 times.
+// Limits below it evict-by-refusal and miss on most repeats; limits at or
+// above it hit on every repeat after the first pass.
+const DISTINCT: usize = 4096;
+
+fn build_document() -> Value {
+    let rows: Vec<Value> = (0..ROWS)
+        .map(|row| {
+            let record: Map<String, Value> = (0..FIELDS)
+                .map(|field| {
+                    let id = (row * FIELDS + field) % DISTINCT;
+                    (format!("f{field}"), Value::String(format!("v-{id:04}")))
+                })
+                .collect();
+            Value::Object(record)
+        })
+        .collect();
+    Value::Array(rows)
+}
+
+fn bench_cache_limits(c: &mut Criterion) {
+    let document = build_document();
+    let encoded_len = serde_toon::to_string(&document)
+        .expect("encode failed")
+        .len();
+
+    let mut group = c.benchmark_group("cache_limits");
+    group.throughput(Throughput::Bytes(encoded_len as u64));
+    for (name, limits) in [
+        ("off", CacheLimits::uniform(0)),
+        ("small", CacheLimits::uniform(64)),
+        ("default", CacheLimits::default()),
+        ("above_cardinality", CacheLimits::uniform(DISTINCT * 2)),
+    ] {
+        let options = EncodeOptions::new().with_cache_limits(limits);
+        group.bench_with_input(BenchmarkId::from_parameter(name), &options, |b, options| {
+            b.iter(|| {
+                let encoded = serde_toon::to_string_with_options(black_box(&document), options)
+                    .expect("encode failed");
+                black_box(encoded);
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_cache_limits);
+criterion_main!(benches);
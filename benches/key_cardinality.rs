@@ -0,0 +1,54 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use serde_json::{json, Map, Value};
+use serde_toon::DecodeOptions;
+
+const ROWS: usize = 5000;
+
+// Every key is distinct, so one object holds 5000 pairs.
+fn unique_keys_document() -> String {
+    let mut map = Map::new();
+    for idx in 0..ROWS {
+        map.insert(format!("key_{idx}"), json!(idx));
+    }
+    serde_toon::to_string(&Value::Object(map)).expect("encode failed")
+}
+
+// A handful of keys repeated in every list item, in many small objects.
+fn repeated_keys_document() -> String {
+    let items: Vec<Value> = (0..ROWS)
+        .map(|idx| json!({"id": idx, "name": "item", "tags": [idx % 7]}))
+        .collect();
+    serde_toon::to_string(&json!({ "items": items })).expect("encode failed")
+}
+
+// `None` stores every key occurrence; thresholds intern keys seen more often.
+const THRESHOLDS: [(&str, Option<usize>); 3] = [
+    ("inline", None),
+    ("intern_0", Some(0)),
+    ("intern_16", Some(16)),
+];
+
+fn bench_key_cardinality(c: &mut Criterion) {
+    let mut group = c.benchmark_group("key_cardinality");
+    for (name, document) in [
+        ("unique_keys", unique_keys_document()),
+        ("repeated_keys", repeated_keys_document()),
+    ] {
+        group.throughput(Throughput::Bytes(document.len() as u64));
+        for (label, threshold) in THRESHOLDS {
+            let options = DecodeOptions::new().with_intern_threshold(threshold);
+            group.bench_function(format!("{name}/{label}"), |b| {
+                b.iter(|| {
+                    let value: Value =
+                        serde_toon::from_str_with_options(black_box(&document), &options)
+                            .expect("decode failed");
+                    black_box(value);
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_key_cardinality);
+criterion_main!(benches);
@@ -0,0 +1,51 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use serde_json::{json, Map, Value};
+
+const ROWS: usize = 2000;
+const FIELDS: usize = 12;
+
+fn build_document() -> Value {
+    let words = [
+        "alpha", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel",
+    ];
+    let mut records = Vec::with_capacity(ROWS);
+    for row in 0..ROWS {
+        let mut record = Map::new();
+        for field in 0..FIELDS {
+            let word = words[(row + field) % words.len()];
+            record.insert(
+                format!("field_{field}"),
+                Value::String(format!("{word} {}", row % 97)),
+            );
+        }
+        records.push(Value::Object(record));
+    }
+    let mut notes = Map::new();
+    for idx in 0..ROWS {
+        notes.insert(
+            format!("note_{idx}"),
+            Value::String(format!("{}: {idx}", words[idx % words.len()])),
+        );
+    }
+    json!({ "records": records, "notes": notes })
+}
+
+fn bench_string_heavy(c: &mut Criterion) {
+    let document = build_document();
+    let encoded_len = serde_toon::to_string(&document)
+        .expect("encode failed")
+        .len();
+
+    let mut group = c.benchmark_group("string_heavy");
+    group.throughput(Throughput::Bytes(encoded_len as u64));
+    group.bench_function("encode_toon", |b| {
+        b.iter(|| {
+            let encoded = serde_toon::to_string(black_box(&document)).expect("encode failed");
+            black_box(encoded);
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_string_heavy);
+criterion_main!(benches);
@@ -0,0 +1,156 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use serde_json::{json, Map, Value};
+
+const FLAT_FIELDS: usize = 64;
+const TABULAR_ROWS: usize = 10_000;
+const NESTED_DEPTH: usize = 64;
+const NESTED_WIDTH: usize = 4;
+const STRING_ROWS: usize = 2_000;
+const NUMBER_ROWS: usize = 5_000;
+
+const WORDS: [&str; 8] = [
+    "alpha", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel",
+];
+
+fn small_object() -> Value {
+    json!({
+        "id": 42,
+        "name": "Ada Lovelace",
+        "email": "ada@example.com",
+        "active": true,
+        "role": "admin",
+    })
+}
+
+fn flat_object() -> Value {
+    let mut map = Map::new();
+    for idx in 0..FLAT_FIELDS {
+        let value = match idx % 4 {
+            0 => json!(idx),
+            1 => json!(idx % 3 == 0),
+            2 => json!(WORDS[idx % WORDS.len()]),
+            _ => json!(idx as f64 * 0.25),
+        };
+        map.insert(format!("field_{idx}"), value);
+    }
+    Value::Object(map)
+}
+
+fn large_tabular() -> Value {
+    let rows: Vec<Value> = (0..TABULAR_ROWS)
+        .map(|idx| {
+            json!({
+                "id": idx,
+                "name": format!("user_{idx}"),
+                "active": idx % 2 == 0,
+                "score": (idx % 1000) as f64 / 10.0,
+                "team": WORDS[idx % WORDS.len()],
+            })
+        })
+        .collect();
+    Value::Array(rows)
+}
+
+fn nested_tabular() -> Value {
+    json!({"data": {"rows": large_tabular()}})
+}
+
+fn deeply_nested() -> Value {
+    let mut value = json!({"leaf": true, "tags": ["a", "b", "c"]});
+    for depth in (0..NESTED_DEPTH).rev() {
+        let mut map = Map::new();
+        for sibling in 0..NESTED_WIDTH {
+            map.insert(format!("k{depth}_{sibling}"), json!(sibling));
+        }
+        map.insert("child".to_string(), value);
+        value = Value::Object(map);
+    }
+    value
+}
+
+fn string_heavy() -> Value {
+    let rows: Vec<Value> = (0..STRING_ROWS)
+        .map(|idx| {
+            let word = WORDS[idx % WORDS.len()];
+            json!({
+                "title": format!("{word} {idx}"),
+                "quoted": format!("{word}, needs quotes: {idx}"),
+                "escaped": format!("{word}\n\"{idx}\""),
+                "unicode": format!("{word} – ünïcödé {idx}"),
+            })
+        })
+        .collect();
+    json!({ "records": rows })
+}
+
+fn number_heavy() -> Value {
+    let rows: Vec<Value> = (0..NUMBER_ROWS)
+        .map(|idx| {
+            json!({
+                "int": idx as i64 * 7919 - 1_000_000,
+                "big": u64::MAX - idx as u64,
+                "float": idx as f64 / 7.0,
+                "exp": 1.5e-9 * idx as f64,
+            })
+        })
+        .collect();
+    json!({ "samples": rows, "series": (0..NUMBER_ROWS).collect::<Vec<_>>() })
+}
+
+fn bench_throughput(c: &mut Criterion) {
+    let documents = [
+        ("small_object", small_object()),
+        ("flat_object", flat_object()),
+        ("large_tabular", large_tabular()),
+        ("deeply_nested", deeply_nested()),
+        ("string_heavy", string_heavy()),
+        ("number_heavy", number_heavy()),
+        ("nested_tabular", nested_tabular()),
+    ];
+
+    let mut group = c.benchmark_group("throughput");
+    for (name, document) in &documents {
+        let toon = serde_toon::to_string(document).expect("encode failed");
+        group.throughput(Throughput::Bytes(toon.len() as u64));
+        group.bench_with_input(BenchmarkId::new("encode", name), document, |b, document| {
+            b.iter(|| {
+                let encoded = serde_toon::to_string(black_box(document)).expect("encode failed");
+                black_box(encoded);
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("decode", name), &toon, |b, toon| {
+            b.iter(|| {
+                let decoded: Value = serde_toon::from_str(black_box(toon)).expect("decode failed");
+                black_box(decoded);
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("decode_slice", name), &toon, |b, toon| {
+            b.iter(|| {
+                let decoded: Value =
+                    serde_toon::from_slice(black_box(toon.as_bytes())).expect("decode failed");
+                black_box(decoded);
+            });
+        });
+    }
+
+    #[cfg(feature = "parallel")]
+    {
+        let toon = serde_toon::to_string(&documents[2].1).expect("encode failed");
+        group.throughput(Throughput::Bytes(toon.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::new("decode_parallel", "large_tabular"),
+            &toon,
+            |b, toon| {
+                b.iter(|| {
+                    let decoded: Vec<Value> =
+                        serde_toon::from_str_parallel(black_box(toon)).expect("decode failed");
+                    black_box(decoded);
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_throughput);
+criterion_main!(benches);
@@ -0,0 +1,147 @@
+//! CSV support for `--from-csv` and `--to-csv`.
+
+use ::csv::{ReaderBuilder, StringRecord, Writer};
+use serde_json::{Map, Number, Value};
+
+/// A CSV field and whether it was quoted in the source.
+pub struct Cell {
+    text: String,
+    quoted: bool,
+}
+
+/// Splits `input` into records. Ragged records are kept so that
+/// [`records_to_value`] can report them against the header.
+pub fn parse_records(input: &str) -> Result<Vec<Vec<Cell>>, String> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let mut reader = ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(input.as_bytes());
+    let mut records = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|err| err.to_string())?;
+        // After a CRLF the reported position can sit on the `\n`.
+        let start = record.position().map_or(0, |pos| pos.byte() as usize);
+        let raw = input[start..].trim_start_matches(['\r', '\n']);
+        records.push(record_cells(raw, &record));
+    }
+    Ok(records)
+}
+
+/// Pairs each parsed field with the raw text starting at `raw`. A quoted
+/// field spans its text, one extra byte per doubled quote, and both quotes.
+fn record_cells(raw: &str, record: &StringRecord) -> Vec<Cell> {
+    let mut offset = 0;
+    record
+        .iter()
+        .map(|field| {
+            let quoted = raw.get(offset..).is_some_and(|rest| rest.starts_with('"'));
+            offset += field.len() + 1;
+            if quoted {
+                offset += field.matches('"').count() + 2;
+            }
+            Cell {
+                text: field.to_string(),
+                quoted,
+            }
+        })
+        .collect()
+}
+
+/// Builds a root array of objects keyed by the header row. Cells that parse as
+/// a JSON number, `true`, `false`, or `null` keep that type; empty cells become
+/// `null` and everything else, including any quoted cell, stays a string.
+pub fn records_to_value(records: Vec<Vec<Cell>>) -> Result<Value, String> {
+    let mut records = records.into_iter();
+    let header: Vec<String> = records
+        .next()
+        .ok_or("CSV input has no header row")?
+        .into_iter()
+        .map(|cell| cell.text)
+        .collect();
+    for (idx, name) in header.iter().enumerate() {
+        if header[..idx].contains(name) {
+            return Err(format!("duplicate CSV column \"{name}\""));
+        }
+    }
+
+    let mut rows = Vec::new();
+    for (idx, record) in records.enumerate() {
+        if record.len() != header.len() {
+            return Err(format!(
+                "CSV record {} has {} fields, expected {}",
+                idx + 1,
+                record.len(),
+                header.len()
+            ));
+        }
+        let row: Map<String, Value> = header
+            .iter()
+            .cloned()
+            .zip(record.into_iter().map(infer_cell))
+            .collect();
+        rows.push(Value::Object(row));
+    }
+    Ok(Value::Array(rows))
+}
+
+fn infer_cell(cell: Cell) -> Value {
+    let Cell { text, quoted } = cell;
+    if quoted {
+        return Value::String(text);
+    }
+    match text.as_str() {
+        "" | "null" => Value::Null,
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => match text.parse::<Number>() {
+            Ok(number) => Value::Number(number),
+            Err(_) => Value::String(text),
+        },
+    }
+}
+
+/// Renders a root array of uniform objects as CSV, taking the header from the
+/// first object's keys. `null` becomes an empty cell; arrays and objects are an
+/// error unless `json_cells` writes them as JSON text. An empty array writes
+/// nothing.
+pub fn value_to_csv(value: &Value, json_cells: bool) -> Result<String, String> {
+    const SHAPE: &str = "--to-csv needs a root array of objects with the same fields";
+    let rows = value.as_array().ok_or(SHAPE)?;
+    let header: Vec<&String> = match rows.first() {
+        Some(Value::Object(first)) => first.keys().collect(),
+        Some(_) => return Err(SHAPE.to_string()),
+        None => return Ok(String::new()),
+    };
+
+    let mut writer = Writer::from_writer(Vec::new());
+    writer
+        .write_record(&header)
+        .map_err(|err| err.to_string())?;
+    for (idx, row) in rows.iter().enumerate() {
+        let row = row.as_object().ok_or(SHAPE)?;
+        if row.len() != header.len() || !header.iter().all(|name| row.contains_key(*name)) {
+            return Err(format!("{SHAPE}; row {} differs", idx + 1));
+        }
+        let cells = header
+            .iter()
+            .map(|name| cell_text(name, &row[name.as_str()], json_cells))
+            .collect::<Result<Vec<_>, _>>()?;
+        writer.write_record(&cells).map_err(|err| err.to_string())?;
+    }
+    let bytes = writer.into_inner().map_err(|err| err.to_string())?;
+    String::from_utf8(bytes).map_err(|err| err.to_string())
+}
+
+fn cell_text(name: &str, value: &Value, json_cells: bool) -> Result<String, String> {
+    match value {
+        Value::Null => Ok(String::new()),
+        Value::Bool(flag) => Ok(flag.to_string()),
+        Value::Number(number) => Ok(number.to_string()),
+        Value::String(text) => Ok(text.clone()),
+        Value::Array(_) | Value::Object(_) if json_cells => Ok(value.to_string()),
+        Value::Array(_) | Value::Object(_) => Err(format!(
+            "field \"{name}\" is not a scalar; pass --csv-json-cells to write it as JSON"
+        )),
+    }
+}
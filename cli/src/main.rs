@@ -4,16 +4,17 @@ use std::fs;
 use std::io::{self, Read, Write};
 use std::path::Path;
 
-use clap::{ArgAction, Parser, ValueEnum};
+use clap::{Parser, ValueEnum};
 use serde::Serialize;
 use serde_json::Value;
 use serde_toon::{DecodeOptions, Delimiter, EncodeOptions, ExpandPaths, Indent, KeyFolding};
-use tiktoken_rs::cl100k_base;
+
+mod csv;
 
 #[derive(Parser, Debug)]
 #[command(name = "toon", version, about = "TOON encoder/decoder")]
 struct Args {
-    /// Input file path (.json or .toon). Omit or use '-' to read from stdin.
+    /// Input file path (.json, .toon, or .csv). Omit or use '-' to read from stdin.
     input: Option<String>,
 
     /// Output file path (prints to stdout if omitted).
@@ -28,7 +29,20 @@ struct Args {
     #[arg(short = 'd', long)]
     decode: bool,
 
-    /// Array delimiter: , (comma), \\t (tab), | (pipe).
+    /// Read CSV (header row + records) and encode it as a root tabular array.
+    #[arg(long = "from-csv", conflicts_with = "decode")]
+    from_csv: bool,
+
+    /// Decode TOON whose root is an array of uniform objects and write it as CSV.
+    #[arg(long = "to-csv", conflicts_with_all = ["encode", "from_csv"])]
+    to_csv: bool,
+
+    /// With --to-csv, write array and object fields as JSON text instead of failing.
+    #[arg(long = "csv-json-cells", requires = "to_csv")]
+    csv_json_cells: bool,
+
+    /// Array delimiter: , (comma), \\t (tab), | (pipe), ; (semicolon). When decoding, ';' also
+    /// allows semicolon-delimited arrays.
     #[arg(long, value_name = "char", value_parser = parse_delimiter)]
     delimiter: Option<Delimiter>,
 
@@ -40,7 +54,11 @@ struct Args {
     #[arg(long)]
     stats: bool,
 
-    /// Key folding mode: off, safe (default: off).
+    /// Show token statistics on stderr, keeping stdout pure TOON.
+    #[arg(long = "stats-stderr")]
+    stats_stderr: bool,
+
+    /// Key folding mode: off, safe, aggressive (default: off).
     #[arg(long = "keyFolding", alias = "key-folding", value_enum, value_name = "mode", default_value_t = KeyFoldingArg::Off)]
     key_folding: KeyFoldingArg,
 
@@ -52,15 +70,26 @@ struct Args {
     #[arg(long = "expandPaths", alias = "expand-paths", value_enum, value_name = "mode", default_value_t = ExpandPathsArg::Off)]
     expand_paths: ExpandPathsArg,
 
-    /// Disable strict validation when decoding.
-    #[arg(long = "no-strict", action = ArgAction::SetFalse, default_value_t = true)]
+    /// Enforce strict validation when decoding (default).
+    #[arg(long, conflicts_with = "no_strict")]
     strict: bool,
+
+    /// Disable strict validation when decoding.
+    #[arg(long = "no-strict")]
+    no_strict: bool,
+}
+
+impl Args {
+    fn strict(&self) -> bool {
+        self.strict || !self.no_strict
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
 enum KeyFoldingArg {
     Off,
     Safe,
+    Aggressive,
 }
 
 impl From<KeyFoldingArg> for KeyFolding {
@@ -68,6 +97,7 @@ impl From<KeyFoldingArg> for KeyFolding {
         match value {
             KeyFoldingArg::Off => KeyFolding::Off,
             KeyFoldingArg::Safe => KeyFolding::Safe,
+            KeyFoldingArg::Aggressive => KeyFolding::Aggressive,
         }
     }
 }
@@ -112,13 +142,27 @@ fn run() -> Result<(), Box<dyn Error>> {
     let mode = resolve_mode(&args, &input_source)?;
 
     match mode {
-        Mode::Encode => run_encode(&args, &input_text, &input_source),
+        Mode::Encode => {
+            let value: Value = if is_csv_input(&args, &input_source) {
+                csv::records_to_value(csv::parse_records(&input_text)?)?
+            } else {
+                serde_json::from_str(&input_text)?
+            };
+            run_encode(&args, &value, &input_source)
+        }
         Mode::Decode => run_decode(&args, &input_text, &input_source),
     }
 }
 
-fn run_encode(args: &Args, input: &str, input_source: &InputSource) -> Result<(), Box<dyn Error>> {
-    let value: Value = serde_json::from_str(input)?;
+fn is_csv_input(args: &Args, input_source: &InputSource) -> bool {
+    args.from_csv || (!args.encode && has_extension(input_source, "csv"))
+}
+
+fn run_encode(
+    args: &Args,
+    value: &Value,
+    input_source: &InputSource,
+) -> Result<(), Box<dyn Error>> {
     let mut options = EncodeOptions::new().with_indent(Indent::Spaces(args.indent));
 
     if let Some(delimiter) = args.delimiter {
@@ -133,21 +177,26 @@ fn run_encode(args: &Args, input: &str, input_source: &InputSource) -> Result<()
 
     let output_target = OutputTarget::from_arg(args.output.as_deref());
 
-    if args.stats {
-        let toon = serde_toon::to_string_with_options(&value, &options)?;
+    if args.stats || args.stats_stderr {
+        let toon = serde_toon::to_string_with_options(value, &options)?;
         write_output(output_target.path(), toon.as_bytes())?;
-        let leading_newlines = if let OutputTarget::File(path) = &output_target {
+        if let OutputTarget::File(path) = &output_target {
             report_status(Mode::Encode, input_source, path);
-            1
+        }
+        if args.stats_stderr {
+            print_stats(&mut io::stderr().lock(), value, &toon, 0)?;
         } else {
-            2
-        };
-        print_stats(&value, &toon, leading_newlines)?;
+            let leading_newlines = match output_target {
+                OutputTarget::File(_) => 1,
+                _ => 2,
+            };
+            print_stats(&mut io::stdout().lock(), value, &toon, leading_newlines)?;
+        }
         return Ok(());
     }
 
     with_output_writer(output_target.path(), |writer| {
-        serde_toon::to_writer_with_options(writer, &value, &options).map_err(|err| err.into())
+        serde_toon::to_writer_with_options(writer, value, &options).map_err(|err| err.into())
     })?;
     if let OutputTarget::File(path) = &output_target {
         report_status(Mode::Encode, input_source, path);
@@ -158,10 +207,11 @@ fn run_encode(args: &Args, input: &str, input_source: &InputSource) -> Result<()
 fn run_decode(args: &Args, input: &str, input_source: &InputSource) -> Result<(), Box<dyn Error>> {
     let options = DecodeOptions::new()
         .with_indent(Indent::Spaces(args.indent))
-        .with_strict(args.strict)
-        .with_expand_paths(args.expand_paths.into());
+        .with_strict(args.strict())
+        .with_expand_paths(args.expand_paths.into())
+        .with_semicolon_delimiter(args.delimiter == Some(Delimiter::Semicolon));
 
-    let normalized = if args.strict || !input.contains('\t') {
+    let normalized = if args.strict() || !input.contains('\t') {
         Cow::Borrowed(input)
     } else {
         Cow::Owned(normalize_non_strict_tabs(input))
@@ -170,9 +220,14 @@ fn run_decode(args: &Args, input: &str, input_source: &InputSource) -> Result<()
     let value: Value = serde_toon::from_str_with_options(&normalized, &options)?;
     let output_target = OutputTarget::from_arg(args.output.as_deref());
 
-    with_output_writer(output_target.path(), |writer| {
-        write_json(writer, &value, args.indent)
-    })?;
+    if args.to_csv {
+        let csv = csv::value_to_csv(&value, args.csv_json_cells)?;
+        write_output(output_target.path(), csv.as_bytes())?;
+    } else {
+        with_output_writer(output_target.path(), |writer| {
+            write_json(writer, &value, args.indent)
+        })?;
+    }
     if let OutputTarget::File(path) = &output_target {
         report_status(Mode::Decode, input_source, path);
     }
@@ -180,11 +235,11 @@ fn run_decode(args: &Args, input: &str, input_source: &InputSource) -> Result<()
 }
 
 fn resolve_mode(args: &Args, input_source: &InputSource) -> Result<Mode, Box<dyn Error>> {
-    if args.encode {
+    if args.encode || args.from_csv {
         return Ok(Mode::Encode);
     }
 
-    if args.decode {
+    if args.decode || args.to_csv {
         return Ok(Mode::Decode);
     }
 
@@ -196,13 +251,23 @@ fn resolve_mode(args: &Args, input_source: &InputSource) -> Result<Mode, Box<dyn
             .map(|ext| ext.to_ascii_lowercase())
             .as_deref()
         {
-            Some("json") => Ok(Mode::Encode),
+            Some("json" | "csv") => Ok(Mode::Encode),
             Some("toon") => Ok(Mode::Decode),
             _ => Err("unable to auto-detect mode; use --encode or --decode".into()),
         },
     }
 }
 
+fn has_extension(input_source: &InputSource, expected: &str) -> bool {
+    match input_source {
+        InputSource::Stdin => false,
+        InputSource::File(path) => Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case(expected)),
+    }
+}
+
 fn read_input(input: Option<&str>) -> Result<(String, InputSource), Box<dyn Error>> {
     match input {
         None | Some("-") => {
@@ -222,8 +287,10 @@ fn parse_delimiter(raw: &str) -> Result<Delimiter, String> {
         "," => Ok(Delimiter::Comma),
         "|" => Ok(Delimiter::Pipe),
         "\t" => Ok(Delimiter::Tab),
+        ";" => Ok(Delimiter::Semicolon),
         _ => Err(format!(
-            "Invalid delimiter \"{raw}\". Valid delimiters are: comma (,), tab (\\t), pipe (|)"
+            "Invalid delimiter \"{raw}\". Valid delimiters are: comma (,), tab (\\t), pipe (|), \
+             semicolon (;)"
         )),
     }
 }
@@ -300,12 +367,15 @@ fn report_status(mode: Mode, input_source: &InputSource, output_path: &str) {
     println!("✔ {verb} {input_label} → {output_label}");
 }
 
-fn print_stats(value: &Value, toon: &str, leading_newlines: usize) -> Result<(), Box<dyn Error>> {
-    let json = serde_json::to_string(value)?;
-    let bpe = cl100k_base()?;
-    let json_tokens = count_tokens(&bpe, &json);
-    let toon_tokens = count_tokens(&bpe, toon);
-    let saved = json_tokens as isize - toon_tokens as isize;
+fn print_stats(
+    writer: &mut dyn Write,
+    value: &Value,
+    toon: &str,
+    leading_newlines: usize,
+) -> Result<(), Box<dyn Error>> {
+    let stats = serde_toon::encode_stats(value, toon)?;
+    let (json_tokens, toon_tokens) = (stats.json_tokens, stats.toon_tokens);
+    let saved = stats.saved_tokens();
     let pct = if json_tokens > 0 {
         ((toon_tokens as f64 - json_tokens as f64) / json_tokens as f64) * 100.0
     } else {
@@ -313,17 +383,16 @@ fn print_stats(value: &Value, toon: &str, leading_newlines: usize) -> Result<(),
     };
 
     for _ in 0..leading_newlines {
-        println!();
+        writeln!(writer)?;
     }
-    println!("ℹ Token estimates: ~{json_tokens} (JSON) → ~{toon_tokens} (TOON)");
-    println!("✔ Saved ~{saved} tokens ({pct:.1}%)");
+    writeln!(
+        writer,
+        "ℹ Token estimates: ~{json_tokens} (JSON) → ~{toon_tokens} (TOON)"
+    )?;
+    writeln!(writer, "✔ Saved ~{saved} tokens ({pct:.1}%)")?;
     Ok(())
 }
 
-fn count_tokens(bpe: &tiktoken_rs::CoreBPE, text: &str) -> usize {
-    bpe.encode_with_special_tokens(text).len()
-}
-
 fn display_path(path: &str) -> String {
     let path = Path::new(path);
     let Ok(cwd) = std::env::current_dir() else {
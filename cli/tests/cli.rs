@@ -52,6 +52,37 @@ fn encode_with_custom_delimiter() {
         .stdout("items[3|]: 1|2|3");
 }
 
+#[test]
+fn encode_with_semicolon_delimiter() {
+    let dir = TempDir::new().expect("tempdir");
+    let input = dir.path().join("input.json");
+    write_file(&input, r#"{"notes":["a, b","c;d"]}"#);
+
+    cargo_bin_cmd!("toon")
+        .arg(&input)
+        .args(["--delimiter", ";"])
+        .assert()
+        .success()
+        .stdout("notes[2;]: a, b;\"c;d\"");
+}
+
+#[test]
+fn decode_semicolon_delimiter_needs_flag() {
+    cargo_bin_cmd!("toon")
+        .arg("--decode")
+        .write_stdin("notes[2;]: a, b;c")
+        .assert()
+        .failure()
+        .stderr(contains("invalid array delimiter"));
+
+    cargo_bin_cmd!("toon")
+        .args(["--decode", "--delimiter", ";"])
+        .write_stdin("notes[2;]: a, b;c")
+        .assert()
+        .success()
+        .stdout(contains("\"a, b\""));
+}
+
 #[test]
 fn encode_with_stats_writes_output_and_stdout() {
     let dir = TempDir::new().expect("tempdir");
@@ -71,6 +102,21 @@ fn encode_with_stats_writes_output_and_stdout() {
         .stderr("");
 }
 
+#[test]
+fn encode_with_stats_stderr_keeps_stdout_pure() {
+    let dir = TempDir::new().expect("tempdir");
+    let input = dir.path().join("input.json");
+    write_file(&input, r#"{"items":[1,2]}"#);
+
+    cargo_bin_cmd!("toon")
+        .arg(&input)
+        .arg("--stats-stderr")
+        .assert()
+        .success()
+        .stdout("items[2]: 1,2")
+        .stderr(contains("Token estimates:").and(contains("Saved")));
+}
+
 #[test]
 fn key_folding_and_flatten_depth() {
     let dir = TempDir::new().expect("tempdir");
@@ -158,3 +204,133 @@ fn flatten_depth_without_key_folding_is_ignored() {
         .success()
         .stdout("a:\n  b: 1");
 }
+
+#[test]
+fn strict_flag_is_explicit_default() {
+    let dir = TempDir::new().expect("tempdir");
+    let input = dir.path().join("input.toon");
+    write_file(&input, "a:\n\tb: 1");
+
+    cargo_bin_cmd!("toon")
+        .arg(&input)
+        .arg("--strict")
+        .assert()
+        .failure()
+        .stderr(contains("tabs not allowed in indentation"));
+
+    cargo_bin_cmd!("toon")
+        .arg(&input)
+        .args(["--strict", "--no-strict"])
+        .assert()
+        .failure()
+        .stderr(contains("cannot be used with"));
+}
+
+#[test]
+fn forced_decode_of_json_suggests_encoding() {
+    cargo_bin_cmd!("toon")
+        .arg("--decode")
+        .write_stdin("{\"a\": 1}")
+        .assert()
+        .failure()
+        .stderr(contains("did you mean to encode?"));
+}
+
+#[test]
+fn from_csv_emits_root_tabular_array() {
+    let dir = TempDir::new().expect("tempdir");
+    let input = dir.path().join("input.csv");
+    write_file(
+        &input,
+        "id,name,note,active\r\n1,Ada,\"hello, world\",true\r\n2,\"Bo \"\"B\"\"\",,false\r\n",
+    );
+
+    cargo_bin_cmd!("toon")
+        .arg("--from-csv")
+        .arg(&input)
+        .assert()
+        .success()
+        .stdout(
+            "[2]{id,name,note,active}:\n  1,Ada,\"hello, world\",true\n  2,\"Bo \\\"B\\\"\",null,false",
+        );
+}
+
+#[test]
+fn from_csv_auto_detects_extension_and_keeps_strings() {
+    let dir = TempDir::new().expect("tempdir");
+    let input = dir.path().join("input.csv");
+    write_file(&input, "zip,score\n007,1.5\n\"multi\nline\",-2");
+
+    cargo_bin_cmd!("toon")
+        .arg(&input)
+        .args(["--delimiter", "|"])
+        .assert()
+        .success()
+        .stdout("[2|]{zip|score}:\n  \"007\"|1.5\n  \"multi\\nline\"|-2");
+}
+
+#[test]
+fn from_csv_keeps_quoted_cells_as_strings() {
+    let dir = TempDir::new().expect("tempdir");
+    let input = dir.path().join("input.csv");
+    write_file(
+        &input,
+        "id,flag,note\n\"42\",\"true\",\"say \"\"hi\"\"\"\n7,false,\n",
+    );
+
+    cargo_bin_cmd!("toon")
+        .arg("--from-csv")
+        .arg(&input)
+        .assert()
+        .success()
+        .stdout("[2]{id,flag,note}:\n  \"42\",\"true\",\"say \\\"hi\\\"\"\n  7,false,null");
+}
+
+#[test]
+fn from_csv_rejects_ragged_records() {
+    let dir = TempDir::new().expect("tempdir");
+    let input = dir.path().join("input.csv");
+    write_file(&input, "a,b\n1,2\n3\n");
+
+    cargo_bin_cmd!("toon")
+        .arg("--from-csv")
+        .arg(&input)
+        .assert()
+        .failure()
+        .stderr(contains("CSV record 2 has 1 fields, expected 2"));
+}
+
+#[test]
+fn to_csv_rejects_non_tabular_roots() {
+    let dir = TempDir::new().expect("tempdir");
+    let input = dir.path().join("input.toon");
+    write_file(&input, "name: Ada");
+
+    cargo_bin_cmd!("toon")
+        .arg("--to-csv")
+        .arg(&input)
+        .assert()
+        .failure()
+        .stderr(contains("root array of objects"));
+}
+
+#[test]
+fn to_csv_json_cells_flag_stringifies_nested_fields() {
+    let dir = TempDir::new().expect("tempdir");
+    let input = dir.path().join("input.toon");
+    write_file(&input, "[1]:\n  - id: 1\n    tags[2]: a,b");
+
+    cargo_bin_cmd!("toon")
+        .arg("--to-csv")
+        .arg(&input)
+        .assert()
+        .failure()
+        .stderr(contains("field \"tags\" is not a scalar"));
+
+    cargo_bin_cmd!("toon")
+        .args(["--to-csv", "--csv-json-cells"])
+        .arg(&input)
+        .assert()
+        .success()
+        .stdout("id,tags\n1,\"[\"\"a\"\",\"\"b\"\"]\"\n");
+}
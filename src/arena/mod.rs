@@ -24,6 +24,16 @@ pub struct Span {
     pub end: usize,
 }
 
+/// Source text of a block container, for [`crate::RawToon`]. Lines after the
+/// first lose up to `dedent` leading spaces or tabs so the text parses on its
+/// own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawSpan {
+    pub node: usize,
+    pub span: Span,
+    pub dedent: usize,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum StringRef {
     Span(Span),
@@ -68,6 +78,7 @@ pub struct ArenaParts {
     pub children: Vec<usize>,
     pub pairs: Vec<Pair>,
     pub keys: Vec<SmolStr>,
+    pub raw_spans: Vec<RawSpan>,
 }
 
 impl ArenaParts {
@@ -82,30 +93,36 @@ impl ArenaParts {
         self.children.clear();
         self.pairs.clear();
         self.keys.clear();
+        self.raw_spans.clear();
     }
 }
 
 #[derive(Debug)]
 pub struct ArenaView<'a> {
     pub input: &'a str,
+    /// Index of the document's root node once parsed; `0` before that.
+    pub root: usize,
     pub nodes: Vec<Node>,
     pub strings: Vec<StringRef>,
     pub numbers: Vec<Span>,
     pub children: Vec<usize>,
     pub pairs: Vec<Pair>,
     pub keys: Vec<SmolStr>,
+    pub raw_spans: Vec<RawSpan>,
 }
 
 impl<'a> ArenaView<'a> {
     pub fn new(input: &'a str) -> Self {
         Self {
             input,
+            root: 0,
             nodes: Vec::new(),
             strings: Vec::new(),
             numbers: Vec::new(),
             children: Vec::new(),
             pairs: Vec::new(),
             keys: Vec::new(),
+            raw_spans: Vec::new(),
         }
     }
 
@@ -113,12 +130,14 @@ impl<'a> ArenaView<'a> {
         parts.clear();
         Self {
             input,
+            root: 0,
             nodes: parts.nodes,
             strings: parts.strings,
             numbers: parts.numbers,
             children: parts.children,
             pairs: parts.pairs,
             keys: parts.keys,
+            raw_spans: parts.raw_spans,
         }
     }
 
@@ -130,6 +149,7 @@ impl<'a> ArenaView<'a> {
             children: self.children,
             pairs: self.pairs,
             keys: self.keys,
+            raw_spans: self.raw_spans,
         }
     }
 
@@ -145,6 +165,75 @@ impl<'a> ArenaView<'a> {
         self.input.get(span.start..span.end)
     }
 
+    /// The string at `index` as a slice of the input, or `None` when it was
+    /// unescaped into an owned copy.
+    pub fn get_str_span(&self, index: usize) -> Option<&'a str> {
+        match self.strings.get(index)? {
+            StringRef::Span(span) => self.input.get(span.start..span.end),
+            StringRef::Owned(_) => None,
+        }
+    }
+
+    /// Follows a `users[1].name` style path from `root`. For duplicate keys the
+    /// last one wins, matching deserialization.
+    pub fn find_path(&self, root: usize, path: &str) -> Option<usize> {
+        let mut current = root;
+        if path.is_empty() {
+            return Some(current);
+        }
+        for segment in path.split('.') {
+            let (key, mut indices) = match segment.find('[') {
+                Some(idx) => (&segment[..idx], &segment[idx..]),
+                None => (segment, ""),
+            };
+            if !key.is_empty() {
+                let node = self.nodes.get(current)?;
+                if node.kind != NodeKind::Object {
+                    return None;
+                }
+                current = self
+                    .pairs(node)
+                    .iter()
+                    .rev()
+                    .find(|pair| self.get_key(pair.key) == Some(key))?
+                    .value;
+            }
+            while !indices.is_empty() {
+                let rest = indices.strip_prefix('[')?;
+                let end = rest.find(']')?;
+                let idx: usize = rest[..end].parse().ok()?;
+                let node = self.nodes.get(current)?;
+                if node.kind != NodeKind::Array {
+                    return None;
+                }
+                current = *self.children(node).get(idx)?;
+                indices = &rest[end + 1..];
+            }
+        }
+        Some(current)
+    }
+
+    /// Returns the standalone TOON source of a block container (an indented
+    /// object, a list-item object, or an array with its `[N]...:` header but
+    /// without its key). Scalars and single-line containers have none.
+    pub fn raw_text(&self, node: usize) -> Option<String> {
+        let idx = self
+            .raw_spans
+            .binary_search_by_key(&node, |raw| raw.node)
+            .ok()?;
+        let RawSpan { span, dedent, .. } = self.raw_spans[idx];
+        let mut lines = self.input.get(span.start..span.end)?.split('\n');
+        let mut text = String::with_capacity(span.end - span.start);
+        text.push_str(lines.next()?.trim_end_matches('\r'));
+        for line in lines {
+            let line = line.trim_end_matches('\r');
+            let indent = line.len() - line.trim_start_matches([' ', '\t']).len();
+            text.push('\n');
+            text.push_str(&line[indent.min(dedent)..]);
+        }
+        Some(text)
+    }
+
     pub fn get_key(&self, index: usize) -> Option<&str> {
         self.keys.get(index).map(|key| key.as_str())
     }
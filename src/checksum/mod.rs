@@ -0,0 +1,94 @@
+use crate::{Error, Result};
+
+pub const CHECKSUM_PREFIX: &str = "#checksum: ";
+
+const CRC32_TABLE: [u32; 256] = build_crc32_table();
+
+const fn build_crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut idx = 0;
+    while idx < 256 {
+        let mut crc = idx as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[idx] = crc;
+        idx += 1;
+    }
+    table
+}
+
+/// CRC-32 (IEEE 802.3), as used by zlib and `cksum -o 3`.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(bytes);
+    crc.finish()
+}
+
+/// Incremental form of [`crc32`] for input that arrives in pieces.
+pub(crate) struct Crc32(u32);
+
+impl Crc32 {
+    pub(crate) fn new() -> Self {
+        Self(!0u32)
+    }
+
+    pub(crate) fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = CRC32_TABLE[((self.0 ^ byte as u32) & 0xFF) as usize] ^ (self.0 >> 8);
+        }
+    }
+
+    pub(crate) fn finish(&self) -> u32 {
+        !self.0
+    }
+}
+
+pub(crate) fn append_checksum_line(out: &mut Vec<u8>) {
+    let checksum = crc32(out);
+    if !out.is_empty() {
+        out.push(b'\n');
+    }
+    out.extend_from_slice(CHECKSUM_PREFIX.as_bytes());
+    out.extend_from_slice(format!("{checksum:08x}").as_bytes());
+}
+
+/// Splits a trailing `#checksum: <crc32>` line off `input`.
+///
+/// The trailer is always removed so checksummed documents decode normally; when
+/// `verify` is set it must be present and match the body.
+pub(crate) fn strip_checksum_line(input: &str, verify: bool) -> Result<&str> {
+    let trimmed = input.strip_suffix('\n').unwrap_or(input);
+    let (body, last) = match trimmed.rfind('\n') {
+        Some(idx) => (&trimmed[..idx], &trimmed[idx + 1..]),
+        None => ("", trimmed),
+    };
+    let Some(hex) = last.strip_prefix(CHECKSUM_PREFIX) else {
+        if verify {
+            return Err(Error::decode("missing checksum line"));
+        }
+        return Ok(input);
+    };
+    if verify {
+        verify_checksum(hex, crc32(body.as_bytes()))?;
+    }
+    Ok(body)
+}
+
+/// Compares the hex digits of a checksum line with the body's CRC-32.
+pub(crate) fn verify_checksum(hex: &str, actual: u32) -> Result<()> {
+    let expected = u32::from_str_radix(hex.trim_end_matches('\r'), 16)
+        .map_err(|err| Error::decode_with_source("invalid checksum line", err))?;
+    if expected != actual {
+        return Err(Error::decode(format!(
+            "checksum mismatch: expected {expected:08x}, found {actual:08x}"
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use crate::decode::CommentScope;
+
+/// A full-line `# ...` comment captured by [`crate::from_str_with_comments`].
+///
+/// `path` is the dotted key path of the field line that follows the comment
+/// (`server.port`); comments inside an array block attach to the array's field,
+/// `Some("")` is a root array, and `None` means the comment trails the document.
+/// `text` excludes the `#` and one following space.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Comment {
+    pub path: Option<String>,
+    pub text: String,
+}
+
+struct Block {
+    indent: usize,
+    path_len: usize,
+    is_array: bool,
+}
+
+/// Removes comment lines from `input` and returns the remaining document with
+/// the captured comments in order. Rows of tabular and matrix blocks stay in
+/// the document even when they start with `#`.
+pub(crate) fn extract_comments(input: &str) -> (String, Vec<Comment>) {
+    let mut body = String::with_capacity(input.len());
+    let mut comments = Vec::new();
+    let mut pending: Vec<String> = Vec::new();
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut path = String::new();
+    let mut scope = CommentScope::default();
+
+    for line in input.split('\n') {
+        let trimmed = line.trim_start_matches([' ', '\t']);
+        let comment = scope.is_comment(line);
+        if let Some(text) = trimmed.strip_prefix('#').filter(|_| comment) {
+            let text = text.trim_end_matches('\r');
+            pending.push(text.strip_prefix(' ').unwrap_or(text).to_string());
+            continue;
+        }
+        if !body.is_empty() {
+            body.push('\n');
+        }
+        body.push_str(line);
+        if trimmed.trim_end().is_empty() {
+            continue;
+        }
+
+        let indent = line.len() - trimmed.len();
+        while blocks.last().is_some_and(|block| block.indent >= indent) {
+            blocks.pop();
+        }
+        if let Some(block) = blocks.last() {
+            path.truncate(block.path_len);
+        } else {
+            path.clear();
+        }
+
+        let field = if blocks.last().is_some_and(|block| block.is_array) {
+            None
+        } else {
+            parse_field(trimmed)
+        };
+        let Some((key, is_array)) = field else {
+            attach(&mut comments, &mut pending, Some(&path));
+            continue;
+        };
+        if !path.is_empty() && !key.is_empty() {
+            path.push('.');
+        }
+        path.push_str(&key);
+        attach(&mut comments, &mut pending, Some(&path));
+        blocks.push(Block {
+            indent,
+            path_len: path.len(),
+            is_array,
+        });
+    }
+    attach(&mut comments, &mut pending, None);
+    (body, comments)
+}
+
+fn attach(comments: &mut Vec<Comment>, pending: &mut Vec<String>, path: Option<&str>) {
+    comments.extend(pending.drain(..).map(|text| Comment {
+        path: path.map(str::to_string),
+        text,
+    }));
+}
+
+/// Key and array-ness of a `key: ...`, `key:` or `key[N]...:` line. List items,
+/// tabular rows and root primitives are not fields.
+fn parse_field(content: &str) -> Option<(String, bool)> {
+    if content.starts_with('-') {
+        return None;
+    }
+    let (key, rest) = if content.starts_with('"') {
+        let bytes = content.as_bytes();
+        let mut idx = 1;
+        while idx < bytes.len() && bytes[idx] != b'"' {
+            idx += if bytes[idx] == b'\\' { 2 } else { 1 };
+        }
+        let quoted = content.get(..idx + 1)?;
+        let key = serde_json::from_str::<String>(quoted).ok()?;
+        (key, &content[idx + 1..])
+    } else {
+        let end = content.find([':', '['])?;
+        (content[..end].trim_end().to_string(), &content[end..])
+    };
+    match rest.as_bytes().first() {
+        Some(b':') => Some((key, false)),
+        Some(b'[') => Some((key, true)),
+        _ => None,
+    }
+}
@@ -0,0 +1,79 @@
+use std::fmt;
+
+use crate::{Error, Location};
+
+/// A non-conformity the decoder tolerated instead of rejecting.
+///
+/// Collected by [`crate::from_str_with_diagnostics`] when
+/// [`DecodeOptions::with_indent_warnings`](crate::DecodeOptions::with_indent_warnings)
+/// is enabled; `line` is one-based and counts lines after the checksum and
+/// comment lines are removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeDiagnostic {
+    IndentMismatch {
+        line: usize,
+        indent: usize,
+        level: usize,
+    },
+}
+
+impl fmt::Display for DecodeDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeDiagnostic::IndentMismatch {
+                line,
+                indent,
+                level,
+            } => write!(
+                f,
+                "line {line}: indent of {indent} spaces is not a multiple of the indent size; \
+                 read as level {level}"
+            ),
+        }
+    }
+}
+
+/// How serious a [`ToonDiagnostic`] is: `Error` marks what
+/// [`crate::validate_str_with_options`] would reject, `Warning` what it
+/// tolerates under the given options.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// One problem reported by [`crate::validate_collect`]. `line` and `column`
+/// are one-based, or zero when the problem has no position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToonDiagnostic {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+    pub severity: Severity,
+}
+
+impl ToonDiagnostic {
+    pub(crate) fn from_error(err: &Error, fallback: Location) -> Self {
+        let at = err.location.unwrap_or(fallback);
+        Self {
+            line: at.line,
+            column: at.column,
+            message: err.message.clone(),
+            severity: Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for ToonDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(
+            f,
+            "{severity} at line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
@@ -1,33 +1,104 @@
+pub mod diagnostic;
 mod parser;
 mod pool;
 mod scan;
 mod serde;
+mod validate;
 
+use std::borrow::Cow;
+use std::cell::Cell;
 use std::io::Read;
 
 use ::serde::de::DeserializeOwned;
+use ::serde::Deserialize;
 use memchr::{memchr, memchr2, memchr3, memchr_iter};
 use serde_json::{Map, Value};
 use smallvec::SmallVec;
 use smol_str::SmolStr;
 
-use crate::arena::ArenaView;
+use crate::arena::{ArenaView, NodeData};
+use crate::checksum::strip_checksum_line;
+use crate::comments::{extract_comments, Comment};
 use crate::num::number::format_json_number;
-use crate::text::string::{is_canonical_unquoted_key, is_identifier_segment};
-use crate::{DecodeOptions, Error, ExpandPaths, Indent, Result};
+use crate::text::string::{
+    is_canonical_unquoted_key, is_identifier_segment, is_non_finite_literal,
+    is_separated_identifier_path, is_valid_path_separator, split_quoted_path,
+};
+use crate::{
+    DecodeOptions, Error, EscapePolicy, ExpandPaths, Indent, KeyCase, Location, NegZeroPolicy,
+    NonFinitePolicy, Result,
+};
+
+pub use diagnostic::{DecodeDiagnostic, Severity, ToonDiagnostic};
+pub use pool::shared_str;
 
 #[cfg(feature = "parallel")]
 use crate::arena::NodeKind;
 #[cfg(feature = "parallel")]
-use ::serde::Deserialize;
-#[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
 #[cfg(feature = "parallel")]
 const PARALLEL_ARRAY_MIN_ITEMS: usize = 64;
 
 pub fn from_str<T: DeserializeOwned>(input: &str, options: &DecodeOptions) -> Result<T> {
-    if options.expand_paths != ExpandPaths::Off {
+    let input = strip_checksum_line(input, options.verify_checksum)?;
+    reject_json_input(input)?;
+    let body = strip_comments(input, options);
+    from_str_body(&body, options)
+}
+
+pub fn from_str_with_comments<T: DeserializeOwned>(
+    input: &str,
+    options: &DecodeOptions,
+) -> Result<(T, Vec<Comment>)> {
+    let input = strip_checksum_line(input, options.verify_checksum)?;
+    reject_json_input(input)?;
+    if !options.capture_comments {
+        return Ok((from_str_body(input, options)?, Vec::new()));
+    }
+    let (body, comments) = extract_comments(input);
+    Ok((from_str_body(&body, options)?, comments))
+}
+
+fn strip_comments<'a>(input: &'a str, options: &DecodeOptions) -> Cow<'a, str> {
+    if options.capture_comments {
+        Cow::Owned(extract_comments(input).0)
+    } else {
+        Cow::Borrowed(input)
+    }
+}
+
+pub fn from_str_with_diagnostics<T: DeserializeOwned>(
+    input: &str,
+    options: &DecodeOptions,
+) -> Result<(T, Vec<DecodeDiagnostic>)> {
+    let input = strip_checksum_line(input, options.verify_checksum)?;
+    reject_json_input(input)?;
+    let body = strip_comments(input, options);
+    let mut decoder = Decoder::new(options);
+    let value = decoder.decode_document(&body)?;
+    let strings = options.string_dedup.then(pool::StringPool::default);
+    let _shared = pool::install_string_pool(strings.as_ref());
+    let value = serde_json::from_value(value)
+        .map_err(|err| Error::deserialize_with_source(format!("deserialize failed: {err}"), err))?;
+    Ok((value, decoder.diagnostics))
+}
+
+/// Options only the tree decoder implements; the arena parser rejects or
+/// ignores what they would accept.
+fn needs_tree_decoder(options: &DecodeOptions) -> bool {
+    options.expand_paths != ExpandPaths::Off
+        || options.inline_objects
+        || options.key_normalize != KeyCase::Preserve
+        || options.numeric_separators
+        || options.indent_warnings
+        || options.empty_containers_in_tabular
+}
+
+fn from_str_body<T: DeserializeOwned>(input: &str, options: &DecodeOptions) -> Result<T> {
+    let strings = options.string_dedup.then(pool::StringPool::default);
+    let _shared = pool::install_string_pool(strings.as_ref());
+    if needs_tree_decoder(options) {
         let mut decoder = Decoder::new(options);
         let value = decoder.decode_document(input)?;
         return serde_json::from_value(value).map_err(|err| {
@@ -37,6 +108,23 @@ pub fn from_str<T: DeserializeOwned>(input: &str, options: &DecodeOptions) -> Re
     let mut arena = ArenaView::with_parts(input, pool::take_arena_parts());
     let result = (|| {
         let root = parser::parse_into(&mut arena, options)?;
+        let mut de = self::serde::ArenaDeserializer::new(&arena, root)
+            .with_bytes_from_base64(options.bytes_from_base64);
+        T::deserialize(&mut de).map_err(|err| {
+            Error::deserialize_with_source(format!("deserialize failed: {err}"), err)
+        })
+    })();
+    pool::put_arena_parts(arena.into_parts());
+    result
+}
+
+pub fn from_str_borrowed<'de, T: Deserialize<'de>>(input: &'de str) -> Result<T> {
+    let options = DecodeOptions::default();
+    let input = strip_checksum_line(input, options.verify_checksum)?;
+    reject_json_input(input)?;
+    let mut arena = ArenaView::with_parts(input, pool::take_arena_parts());
+    let result = (|| {
+        let root = parser::parse_into(&mut arena, &options)?;
         let mut de = self::serde::ArenaDeserializer::new(&arena, root);
         T::deserialize(&mut de).map_err(|err| {
             Error::deserialize_with_source(format!("deserialize failed: {err}"), err)
@@ -46,9 +134,35 @@ pub fn from_str<T: DeserializeOwned>(input: &str, options: &DecodeOptions) -> Re
     result
 }
 
+pub fn raw_str_span<'a>(input: &'a str, path: &str, options: &DecodeOptions) -> Option<&'a str> {
+    let input = strip_checksum_line(input, options.verify_checksum).ok()?;
+    reject_json_input(input).ok()?;
+    let mut arena = ArenaView::with_parts(input, pool::take_arena_parts());
+    let span = parser::parse_into(&mut arena, options)
+        .ok()
+        .and_then(|root| arena.find_path(root, path))
+        .and_then(|node| match arena.nodes[node].data {
+            NodeData::String(index) => arena.get_str_span(index),
+            _ => None,
+        });
+    pool::put_arena_parts(arena.into_parts());
+    span
+}
+
+pub fn parse_to_arena<'a>(input: &'a str, options: &DecodeOptions) -> Result<ArenaView<'a>> {
+    let input = strip_checksum_line(input, options.verify_checksum)?;
+    reject_json_input(input)?;
+    let mut arena = ArenaView::new(input);
+    parser::parse_into(&mut arena, options)?;
+    Ok(arena)
+}
+
 pub fn from_str_value(input: &str, options: &DecodeOptions) -> Result<Value> {
+    let input = strip_checksum_line(input, options.verify_checksum)?;
+    reject_json_input(input)?;
+    let body = strip_comments(input, options);
     let mut decoder = Decoder::new(options);
-    decoder.decode_document(input)
+    decoder.decode_document(&body)
 }
 
 #[cfg(feature = "parallel")]
@@ -56,9 +170,15 @@ pub fn from_str_parallel<T: DeserializeOwned + Send>(
     input: &str,
     options: &DecodeOptions,
 ) -> Result<Vec<T>> {
-    if options.expand_paths != ExpandPaths::Off {
-        return from_str::<Vec<T>>(input, options);
+    let input = strip_checksum_line(input, options.verify_checksum)?;
+    reject_json_input(input)?;
+    let body = strip_comments(input, options);
+    let input = body.as_ref();
+    if needs_tree_decoder(options) {
+        return from_str_body::<Vec<T>>(input, options);
     }
+    let strings = options.string_dedup.then(pool::StringPool::default);
+    let _shared = pool::install_string_pool(strings.as_ref());
     let mut arena = ArenaView::with_parts(input, pool::take_arena_parts());
     let result = (|| {
         let root = parser::parse_into(&mut arena, options)?;
@@ -69,7 +189,9 @@ pub fn from_str_parallel<T: DeserializeOwned + Send>(
                 let results: Vec<Result<T>> = children
                     .par_iter()
                     .map(|child| {
-                        let mut de = self::serde::ArenaDeserializer::new(&arena, *child);
+                        let _shared = pool::install_string_pool(strings.as_ref());
+                        let mut de = self::serde::ArenaDeserializer::new(&arena, *child)
+                            .with_bytes_from_base64(options.bytes_from_base64);
                         T::deserialize(&mut de).map_err(|err| {
                             Error::deserialize_with_source(
                                 format!("deserialize failed: {err}"),
@@ -81,7 +203,8 @@ pub fn from_str_parallel<T: DeserializeOwned + Send>(
                 return results.into_iter().collect();
             }
         }
-        let mut de = self::serde::ArenaDeserializer::new(&arena, root);
+        let mut de = self::serde::ArenaDeserializer::new(&arena, root)
+            .with_bytes_from_base64(options.bytes_from_base64);
         Vec::<T>::deserialize(&mut de).map_err(|err| {
             Error::deserialize_with_source(format!("deserialize failed: {err}"), err)
         })
@@ -91,9 +214,18 @@ pub fn from_str_parallel<T: DeserializeOwned + Send>(
 }
 
 pub fn from_slice<T: DeserializeOwned>(input: &[u8], options: &DecodeOptions) -> Result<T> {
-    let text = std::str::from_utf8(input)
-        .map_err(|err| Error::decode_with_source(format!("invalid utf-8: {err}"), err))?;
-    from_str(text, options)
+    from_str(slice_to_str(input)?, options)
+}
+
+// Most documents are pure ASCII, and `is_ascii` is a cheaper scan than full
+// UTF-8 validation; anything else takes the validating path.
+fn slice_to_str(input: &[u8]) -> Result<&str> {
+    if input.is_ascii() {
+        // SAFETY: ASCII bytes are valid UTF-8.
+        return Ok(unsafe { std::str::from_utf8_unchecked(input) });
+    }
+    std::str::from_utf8(input)
+        .map_err(|err| Error::decode_with_source(format!("invalid utf-8: {err}"), err))
 }
 
 pub fn from_reader<T: DeserializeOwned, R: Read>(
@@ -108,31 +240,122 @@ pub fn from_reader<T: DeserializeOwned, R: Read>(
 }
 
 pub fn validate_str(input: &str, options: &DecodeOptions) -> Result<()> {
+    let body = strip_checksum_line(input, options.verify_checksum)?;
+    // The body check below cannot see a newline after the checksum line.
+    if body.len() < input.len() && input.ends_with('\n') {
+        let at = Location {
+            offset: input.len(),
+            line: memchr_iter(b'\n', input.as_bytes()).count() + 1,
+            column: 1,
+        };
+        return Err(Error::decode("trailing newline not allowed").with_location(at));
+    }
+    let input = body;
+    reject_json_input(input)?;
+    let body = strip_comments(input, options);
     let mut validator = Decoder::new_validator(options);
-    validator.validate_document(input)
+    validator.validate_document(&body)
+}
+
+pub fn validate_collect(input: &str, options: &DecodeOptions) -> Vec<ToonDiagnostic> {
+    if let Err(err) = reject_json_input(input) {
+        return vec![ToonDiagnostic::from_error(&err, Location::default())];
+    }
+    validate::validate_collect(input.as_bytes(), options)
+}
+
+pub fn validate_reader<R: Read>(reader: R, options: &DecodeOptions) -> Result<()> {
+    validate::validate_reader(std::io::BufReader::new(reader), options)
+}
+
+/// Cheap prefix check for JSON piped into the decoder by mistake.
+///
+/// Only a first line opening with `{"` or `["` after leading spaces is
+/// rejected; anything else, such as the root string `{{a}`, is left to the
+/// parser.
+fn reject_json_input(input: &str) -> Result<()> {
+    let mut bytes = input.trim_start_matches(' ').bytes();
+    reject_json_prefix(bytes.next(), bytes.next())
+}
+
+/// [`reject_json_input`] on the first two bytes after the leading spaces.
+fn reject_json_prefix(first: Option<u8>, second: Option<u8>) -> Result<()> {
+    if matches!((first, second), (Some(b'{' | b'['), Some(b'"'))) {
+        return Err(Error::decode(
+            "input looks like JSON, not TOON; did you mean to encode?",
+        ));
+    }
+    Ok(())
 }
 
 struct Decoder {
     indent_size: usize,
+    /// `Indent::Tabs`: a tab is one level and strict mode rejects spaces.
+    indent_tabs: bool,
     strict: bool,
+    allow_comments: bool,
     expand_paths: ExpandPaths,
+    path_separator: String,
+    unknown_escape: EscapePolicy,
+    canonical_numbers: bool,
+    inline_objects: bool,
+    key_case: KeyCase,
+    delimiter_escape: bool,
+    semicolon_delimiter: bool,
+    numeric_separators: bool,
+    coerce_numbers: bool,
+    non_finite: NonFinitePolicy,
+    negative_zero: NegZeroPolicy,
+    trim_quoted: bool,
+    max_array_len: usize,
+    max_depth: usize,
+    /// Open object and list blocks. Not unwound on error, which ends the decode.
+    depth: usize,
+    indent_warnings: bool,
+    empty_containers_in_tabular: bool,
+    matrix_tabular: bool,
+    diagnostics: Vec<DecodeDiagnostic>,
     validate: bool,
     active_delimiter: char,
     delimiter_stack: Vec<char>,
+    /// Line the block parsers are on, attached to errors raised without a
+    /// location. `None` for single-line documents.
+    current_line: Cell<Option<Location>>,
 }
 
 type TokenBuf<'a> = SmallVec<[&'a str; 16]>;
 
 impl Decoder {
     fn new(options: &DecodeOptions) -> Self {
-        let Indent::Spaces(indent_size) = options.indent;
         Self {
-            indent_size,
+            indent_size: options.indent.width(),
+            indent_tabs: options.indent == Indent::Tabs,
             strict: options.strict,
+            allow_comments: options.allow_comments,
             expand_paths: options.expand_paths,
+            path_separator: options.expand_separator.clone(),
+            unknown_escape: options.unknown_escape_policy(),
+            canonical_numbers: options.strict_number_canonicalization,
+            inline_objects: options.inline_objects,
+            key_case: options.key_normalize,
+            delimiter_escape: options.delimiter_escape,
+            semicolon_delimiter: options.semicolon_delimiter,
+            numeric_separators: options.numeric_separators,
+            coerce_numbers: options.coerce_numbers,
+            non_finite: options.non_finite,
+            negative_zero: options.negative_zero,
+            trim_quoted: options.trim_quoted,
+            max_array_len: options.max_array_len,
+            max_depth: options.max_depth,
+            depth: 0,
+            indent_warnings: options.indent_warnings,
+            empty_containers_in_tabular: options.empty_containers_in_tabular,
+            matrix_tabular: options.matrix_tabular,
+            diagnostics: Vec::new(),
             validate: false,
             active_delimiter: ',',
             delimiter_stack: Vec::new(),
+            current_line: Cell::new(None),
         }
     }
 
@@ -157,8 +380,40 @@ impl Decoder {
         }
     }
 
+    fn check_array_len(&self, header: &HeaderLine, found: usize) -> Result<()> {
+        if found > self.max_array_len {
+            let key = header.key.as_ref().map(|key| key.value.as_str());
+            return Err(array_too_long(key, found, self.max_array_len));
+        }
+        if self.strict && found != header.len {
+            return Err(array_length_mismatch(header.len, found));
+        }
+        Ok(())
+    }
+
     fn decode_document(&mut self, input: &str) -> Result<Value> {
-        let lines = self.collect_lines(input)?;
+        self.current_line.set(None);
+        self.decode_lines(input)
+            .map_err(|err| match (err.location, self.current_line.get()) {
+                (None, Some(at)) => err.with_location(at),
+                _ => err,
+            })
+    }
+
+    fn mark_line(&self, line: &Line) {
+        self.current_line.set(Some(line.at));
+    }
+
+    fn decode_lines(&mut self, input: &str) -> Result<Value> {
+        if self.expand_paths == ExpandPaths::Safe && !is_valid_path_separator(&self.path_separator)
+        {
+            return Err(Error::decode("invalid expand separator"));
+        }
+        let mut lines = self.collect_lines(input)?;
+        self.current_line.set(None);
+        if self.indent_warnings {
+            self.infer_indent_levels(&mut lines);
+        }
 
         let non_blank: Vec<&Line> = lines.iter().filter(|line| !line.is_blank).collect();
 
@@ -210,8 +465,10 @@ impl Decoder {
         if let Some(array) = self.parse_array_line(line)? {
             return Ok(array);
         }
-        if let (Some(bracket_idx), Some(colon_idx)) = (line.find('['), line.find(':')) {
-            if bracket_idx < colon_idx {
+        if let (Some(bracket_idx), Some((key_part, _))) =
+            (line.find('['), self.split_key_value(line)?)
+        {
+            if bracket_idx < key_part.len() {
                 if let Some(header) = self.parse_array_header(line)? {
                     if let Some(key) = header.key.as_ref() {
                         let value = self.build_array_value(&header)?;
@@ -228,7 +485,7 @@ impl Decoder {
             let value = if trim_ascii(value).is_empty() {
                 Value::Object(Map::new())
             } else {
-                self.parse_value_token(value)?
+                self.parse_field_value(value)?
             };
             self.insert_key_value(&mut map, key, value)?;
             return Ok(Value::Object(map));
@@ -265,9 +522,7 @@ impl Decoder {
         if header.inline.is_none() && header.len > 0 {
             return Err(Error::decode("array payload not implemented"));
         }
-        if self.strict && header.len != items.len() {
-            return Err(Error::decode("array length mismatch"));
-        }
+        self.check_array_len(header, items.len())?;
         Ok(Value::Array(items))
     }
 
@@ -283,7 +538,7 @@ impl Decoder {
             if token.is_empty() {
                 values.push(Value::String(String::new()));
             } else {
-                values.push(self.parse_value_token(token)?);
+                values.push(self.parse_cell_token(token, delimiter)?);
             }
         }
         Ok(values)
@@ -373,6 +628,10 @@ impl Decoder {
                         idx = pos + 1;
                         continue;
                     }
+                    if self.delimiter_escape && is_escaped_delimiter(bytes, pos) {
+                        idx = pos + 1;
+                        continue;
+                    }
                     let token = trim_ascii(&input[start..pos]);
                     tokens.push(token);
                     start = pos + 1;
@@ -385,7 +644,7 @@ impl Decoder {
         }
 
         if in_quotes {
-            return Err(Error::decode("unterminated string"));
+            return Err(Error::decode_incomplete("unterminated string"));
         }
 
         if start < bytes.len() || input.ends_with(delimiter) {
@@ -404,20 +663,104 @@ impl Decoder {
             return Err(Error::decode("empty value"));
         }
         if token.starts_with('"') {
-            return Ok(Value::String(self.parse_quoted(token)?));
+            let value = self.parse_quoted(token)?;
+            if self.trim_quoted {
+                return Ok(Value::String(value.trim().to_string()));
+            }
+            return Ok(Value::String(value));
         }
         match token {
             "null" => return Ok(Value::Null),
             "true" => return Ok(Value::Bool(true)),
             "false" => return Ok(Value::Bool(false)),
+            _ if is_non_finite_literal(token) => {
+                return match self.non_finite {
+                    NonFinitePolicy::Error => Err(non_finite_error()),
+                    NonFinitePolicy::Null => Ok(Value::Null),
+                    NonFinitePolicy::Keep => Ok(Value::String(token.to_string())),
+                };
+            }
             _ => {}
         }
+        if !self.coerce_numbers {
+            return Ok(Value::String(token.to_string()));
+        }
+        if self.canonical_numbers {
+            ensure_canonical_number(token)?;
+        }
+        if self.negative_zero != NegZeroPolicy::PreserveFloat && is_negative_zero(token) {
+            if self.negative_zero == NegZeroPolicy::Error {
+                return Err(negative_zero_error());
+            }
+            if let Some(number) = parse_number_token(&token[1..]) {
+                return Ok(Value::Number(number));
+            }
+        }
         if let Some(number) = self.parse_number(token) {
             return Ok(Value::Number(number));
         }
+        if self.numeric_separators {
+            if let Some(number) = strip_digit_separators(token)
+                .as_deref()
+                .and_then(parse_number_token)
+            {
+                return Ok(Value::Number(number));
+            }
+        }
         Ok(Value::String(token.to_string()))
     }
 
+    fn parse_cell_token(&self, token: &str, delimiter: char) -> Result<Value> {
+        if self.delimiter_escape {
+            if let Some(unescaped) = unescape_delimiters(token, delimiter) {
+                return Ok(Value::String(unescaped));
+            }
+        }
+        self.parse_value_token(token)
+    }
+
+    fn parse_tabular_cell(&self, token: &str, delimiter: char) -> Result<Value> {
+        if self.empty_containers_in_tabular {
+            match trim_ascii(token) {
+                "[]" => return Ok(Value::Array(Vec::new())),
+                "{}" => return Ok(Value::Object(Map::new())),
+                _ => {}
+            }
+        }
+        self.parse_cell_token(token, delimiter)
+    }
+
+    fn parse_field_value(&self, token: &str) -> Result<Value> {
+        let trimmed = trim_ascii(token);
+        if self.inline_objects && trimmed.starts_with('{') {
+            return Ok(Value::Object(self.parse_inline_object(trimmed)?));
+        }
+        self.parse_value_token(token)
+    }
+
+    fn parse_inline_object(&self, token: &str) -> Result<Map<String, Value>> {
+        let inner = token
+            .strip_prefix('{')
+            .and_then(|rest| rest.strip_suffix('}'))
+            .ok_or_else(|| Error::decode_incomplete("unterminated inline object"))?;
+        let mut map = Map::new();
+        if trim_ascii(inner).is_empty() {
+            return Ok(map);
+        }
+        for entry in split_inline_entries(inner)? {
+            let (key, value) = self
+                .split_key_value(entry)?
+                .ok_or_else(|| Error::decode("missing colon in inline object"))?;
+            if trim_ascii(value).is_empty() {
+                return Err(Error::decode("empty value"));
+            }
+            let key = self.parse_key_token(trim_ascii(key))?;
+            let value = self.parse_field_value(value)?;
+            self.insert_key_value(&mut map, key, value)?;
+        }
+        Ok(map)
+    }
+
     fn validate_value_token(&self, token: &str) -> Result<()> {
         let token = trim_ascii(token);
         if token.is_empty() {
@@ -429,11 +772,20 @@ impl Decoder {
         }
         match token {
             "true" | "false" | "null" => return Ok(()),
-            "NaN" | "Infinity" | "-Infinity" | "+Infinity" => {
-                return Err(Error::decode("non-finite numbers must be null"))
+            _ if is_non_finite_literal(token) => {
+                return match self.non_finite {
+                    NonFinitePolicy::Error => Err(non_finite_error()),
+                    NonFinitePolicy::Null | NonFinitePolicy::Keep => Ok(()),
+                };
             }
             _ => {}
         }
+        if !self.coerce_numbers {
+            return Ok(());
+        }
+        if self.negative_zero == NegZeroPolicy::Error && is_negative_zero(token) {
+            return Err(negative_zero_error());
+        }
         if is_numeric_like(token) {
             let number = self
                 .parse_number(token)
@@ -465,24 +817,43 @@ impl Decoder {
 
     fn parse_key_token(&self, token: &str) -> Result<KeyToken> {
         let token = trim_ascii(token);
+        if let Some(segments) = split_quoted_path(token, &self.path_separator) {
+            let path = segments
+                .into_iter()
+                .map(|segment| match segment.starts_with('"') {
+                    true => self.parse_quoted(segment),
+                    false => Ok(segment.to_string()),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            return Ok(KeyToken {
+                value: SmolStr::new(path.join(&self.path_separator)),
+                quoted: true,
+                path: Some(path),
+            });
+        }
         if token.starts_with('"') {
             let value = self.parse_quoted(token)?;
             Ok(KeyToken {
                 value: SmolStr::new(value.as_str()),
                 quoted: true,
+                path: None,
             })
         } else {
             if self.strict {
                 if contains_whitespace(token) {
                     return Err(Error::decode("invalid unquoted key"));
                 }
-                if token.is_ascii() && !is_canonical_unquoted_key(token) {
+                if token.is_ascii()
+                    && !is_canonical_unquoted_key(token)
+                    && !self.is_separated_path_key(token)
+                {
                     return Err(Error::decode("invalid unquoted key"));
                 }
             }
             Ok(KeyToken {
-                value: SmolStr::new(token),
+                value: normalize_key_case(token, self.key_case),
                 quoted: false,
+                path: None,
             })
         }
     }
@@ -490,7 +861,7 @@ impl Decoder {
     fn parse_quoted(&self, token: &str) -> Result<String> {
         let token = trim_ascii(token);
         if token.len() < 2 || !token.starts_with('"') || !token.ends_with('"') {
-            return Err(Error::decode("unterminated string"));
+            return Err(Error::decode_incomplete("unterminated string"));
         }
         let inner = &token[1..token.len() - 1];
         let bytes = inner.as_bytes();
@@ -505,16 +876,16 @@ impl Decoder {
             let next_idx = esc_pos + 1;
             let next = bytes
                 .get(next_idx)
-                .ok_or_else(|| Error::decode("unterminated escape"))?;
+                .ok_or_else(|| Error::decode_incomplete("unterminated escape"))?;
             match next {
                 b'n' => out.push('\n'),
                 b'r' => out.push('\r'),
                 b't' => out.push('\t'),
                 b'"' => out.push('"'),
                 b'\\' => out.push('\\'),
-                _ => return Err(Error::decode("invalid escape")),
+                _ => push_unknown_escape(&mut out, inner, next_idx, self.unknown_escape)?,
             }
-            idx = esc_pos + 2;
+            idx = next_idx + utf8_char_len(*next);
         }
         out.push_str(&inner[idx..]);
         Ok(out)
@@ -547,7 +918,7 @@ impl Decoder {
             }
         }
         if in_quotes {
-            return Err(Error::decode("unterminated string"));
+            return Err(Error::decode_incomplete("unterminated string"));
         }
         Ok(None)
     }
@@ -581,7 +952,7 @@ impl Decoder {
             }
         }
         if in_quotes {
-            return Err(Error::decode("unterminated string"));
+            return Err(Error::decode_incomplete("unterminated string"));
         }
         let bracket_start = match bracket_start {
             Some(idx) => idx,
@@ -589,7 +960,7 @@ impl Decoder {
         };
         let bracket_end = match line[bracket_start + 1..].find(']') {
             Some(idx) => bracket_start + 1 + idx,
-            None => return Err(Error::decode("unterminated array header")),
+            None => return Err(Error::decode_incomplete("unterminated array header")),
         };
 
         let key_part = trim_ascii(&line[..bracket_start]);
@@ -616,8 +987,22 @@ impl Decoder {
         }
         let len: usize = inner[..digits_end]
             .parse()
-            .map_err(|_| Error::decode("invalid array length"))?;
-        let remainder = &inner[digits_end..];
+            .map_err(|err| Error::decode_with_source("invalid array length", err))?;
+        if len > self.max_array_len {
+            return Err(array_too_long(
+                key.as_ref().map(|key| key.value.as_str()),
+                len,
+                self.max_array_len,
+            ));
+        }
+        let (columns, remainder) = split_matrix_columns(&inner[digits_end..], self.matrix_tabular)?;
+        if let Some(columns) = columns.filter(|&columns| columns > self.max_array_len) {
+            return Err(array_too_long(
+                key.as_ref().map(|key| key.value.as_str()),
+                columns,
+                self.max_array_len,
+            ));
+        }
         let mut chars = remainder.chars().peekable();
         while matches!(chars.peek(), Some(' ')) {
             chars.next();
@@ -628,7 +1013,12 @@ impl Decoder {
                 if chars.any(|ch| ch != ' ') {
                     return Err(Error::decode("invalid array delimiter"));
                 }
-                if !matches!(delimiter, ',' | '\t' | '|') {
+                let allowed = match delimiter {
+                    ',' | '\t' | '|' => true,
+                    ';' => self.semicolon_delimiter,
+                    _ => false,
+                };
+                if !allowed {
                     return Err(Error::decode("invalid array delimiter"));
                 }
                 delimiter
@@ -640,14 +1030,22 @@ impl Decoder {
         if rest.starts_with('{') {
             let end = rest
                 .find('}')
-                .ok_or_else(|| Error::decode("unterminated field list"))?;
+                .ok_or_else(|| Error::decode_incomplete("unterminated field list"))?;
             let field_segment = &rest[1..end];
             let mut parsed_fields = Vec::new();
             for token in self.split_delimited(field_segment, delimiter)? {
                 if token.is_empty() {
                     return Err(Error::decode("empty field name"));
                 }
-                parsed_fields.push(self.parse_key_token(token)?);
+                let field = self.parse_key_token(token)?;
+                if self.strict
+                    && parsed_fields
+                        .iter()
+                        .any(|f: &KeyToken| f.value == field.value)
+                {
+                    return Err(duplicate_key(&field.value));
+                }
+                parsed_fields.push(field);
             }
             fields = Some(parsed_fields);
             rest = rest[end + 1..].trim_start();
@@ -665,10 +1063,16 @@ impl Decoder {
         } else {
             Some(inline.to_string())
         };
+        if columns.is_some() && (fields.is_some() || inline.is_some()) {
+            return Err(Error::decode(
+                "matrix header cannot have a field list or inline values",
+            ));
+        }
 
         Ok(Some(HeaderLine {
             key,
             len,
+            columns,
             delimiter,
             fields,
             inline,
@@ -685,20 +1089,16 @@ impl Decoder {
         }
 
         let mut lines = Vec::new();
+        let mut comments = CommentScope::default();
         let mut start = 0;
         for idx in memchr_iter(b'\n', bytes) {
             let mut end = idx;
             if end > start && bytes[end - 1] == b'\r' {
                 end -= 1;
             }
-            if self.validate && end > start {
-                let last = bytes[end - 1];
-                if last == b' ' || last == b'\t' {
-                    return Err(Error::decode("trailing whitespace not allowed"));
-                }
-            }
             let line = &input[start..end];
-            lines.push(self.build_line(line)?);
+            let comment = self.allow_comments && comments.is_comment(line);
+            lines.push(self.build_located_line(line, comment, start, lines.len() + 1)?);
             start = idx + 1;
         }
 
@@ -706,25 +1106,97 @@ impl Decoder {
         if end > start && bytes[end - 1] == b'\r' {
             end -= 1;
         }
-        if self.validate && end > start {
-            let last = bytes[end - 1];
-            if last == b' ' || last == b'\t' {
-                return Err(Error::decode("trailing whitespace not allowed"));
-            }
-        }
         let line = &input[start..end];
-        lines.push(self.build_line(line)?);
+        let comment = self.allow_comments && comments.is_comment(line);
+        lines.push(self.build_located_line(line, comment, start, lines.len() + 1)?);
 
         Ok(lines)
     }
 
-    fn build_line(&self, line: &str) -> Result<Line> {
-        if is_blank_line(line) {
+    fn build_located_line(
+        &self,
+        text: &str,
+        comment: bool,
+        offset: usize,
+        number: usize,
+    ) -> Result<Line> {
+        let at = Location {
+            offset,
+            line: number,
+            column: 1,
+        };
+        self.current_line.set(Some(at));
+        if self.validate && matches!(text.as_bytes().last(), Some(b' ' | b'\t')) {
+            let column = text.trim_end_matches([' ', '\t']).len() + 1;
+            return Err(Error::decode_at(
+                "trailing whitespace not allowed",
+                Location {
+                    offset: offset + column - 1,
+                    column,
+                    ..at
+                },
+            ));
+        }
+        let mut line = self.build_line(text, comment).map_err(|err| {
+            let indent = indent_width(text);
+            err.with_location(Location {
+                offset: offset + indent,
+                column: indent + 1,
+                ..at
+            })
+        })?;
+        let indent = text.len() - line.content.len();
+        line.at = Location {
+            offset: offset + indent,
+            column: indent + 1,
+            ..at
+        };
+        Ok(line)
+    }
+
+    /// Gives each line whose indent is not a multiple of the indent size the
+    /// neighbouring level that fits the previous line: a deeper indent nests one
+    /// level below it when possible, an equal indent is its sibling, and a
+    /// shallower one rounds down. Each such line is reported as a diagnostic.
+    fn infer_indent_levels(&mut self, lines: &mut [Line]) {
+        let mut previous: Option<(usize, usize)> = None;
+        for (idx, line) in lines.iter_mut().enumerate() {
+            if line.is_blank {
+                continue;
+            }
+            if !line.indent.is_multiple_of(self.indent_size) {
+                line.level = self.inferred_level(line.indent, previous);
+                self.diagnostics.push(DecodeDiagnostic::IndentMismatch {
+                    line: idx + 1,
+                    indent: line.indent,
+                    level: line.level,
+                });
+            }
+            previous = Some((line.indent, line.level));
+        }
+    }
+
+    /// Level of a misaligned `indent` given the previous non-blank line's
+    /// indent and level; see [`Decoder::infer_indent_levels`].
+    fn inferred_level(&self, indent: usize, previous: Option<(usize, usize)>) -> usize {
+        let floor = indent / self.indent_size;
+        match previous {
+            Some((prev_indent, level)) if indent == prev_indent => level,
+            Some((prev_indent, level)) if indent > prev_indent && floor <= level => floor + 1,
+            _ => floor,
+        }
+    }
+
+    /// Splits `line` into indentation and content. `comment` marks a line that
+    /// [`CommentScope`] classified as a comment; it is read as blank.
+    fn build_line(&self, line: &str, comment: bool) -> Result<Line> {
+        if comment || is_blank_line(line) {
             return Ok(Line {
                 indent: 0,
                 level: 0,
                 content: String::new(),
                 is_blank: true,
+                at: Location::default(),
             });
         }
         let mut indent_columns: usize = 0;
@@ -732,11 +1204,14 @@ impl Decoder {
         for &byte in line.as_bytes() {
             match byte {
                 b' ' => {
+                    if self.strict && self.indent_tabs {
+                        return Err(Error::decode("spaces not allowed in tab indentation"));
+                    }
                     indent_columns += 1;
                     indent_chars += 1;
                 }
                 b'\t' => {
-                    if self.strict {
+                    if self.strict && !self.indent_tabs {
                         return Err(Error::decode("tabs not allowed in indentation"));
                     }
                     indent_columns = indent_columns.saturating_add(self.indent_size);
@@ -745,7 +1220,8 @@ impl Decoder {
                 _ => break,
             }
         }
-        if self.strict && !indent_columns.is_multiple_of(self.indent_size) {
+        if self.strict && !self.indent_warnings && !indent_columns.is_multiple_of(self.indent_size)
+        {
             return Err(Error::decode("invalid indentation"));
         }
         let level = indent_columns / self.indent_size;
@@ -755,6 +1231,7 @@ impl Decoder {
             level,
             content,
             is_blank: false,
+            at: Location::default(),
         })
     }
 
@@ -772,10 +1249,12 @@ impl Decoder {
         mut idx: usize,
         base_level: usize,
     ) -> Result<(Map<String, Value>, usize)> {
+        self.depth = descend(self.depth, self.max_depth)?;
         let mut map = Map::new();
         let mut override_level: Option<usize> = None;
         while idx < lines.len() {
             let line = &lines[idx];
+            self.mark_line(line);
             if line.is_blank {
                 idx += 1;
                 continue;
@@ -796,6 +1275,7 @@ impl Decoder {
                     .as_ref()
                     .ok_or_else(|| Error::decode("array header missing key in object context"))?;
                 let parsed = self.parse_array_from_header(&header, lines, idx + 1, base_level)?;
+                self.mark_line(line);
                 self.insert_key_value(&mut map, key.clone(), parsed.value)?;
                 if parsed.deindent_next {
                     override_level = Some(base_level);
@@ -809,10 +1289,11 @@ impl Decoder {
                 if trim_ascii(value).is_empty() {
                     let (nested, next_idx) =
                         self.parse_object_block(lines, idx + 1, base_level + 1)?;
+                    self.mark_line(line);
                     self.insert_key_value(&mut map, key, Value::Object(nested))?;
                     idx = next_idx;
                 } else {
-                    let value = self.parse_value_token(value)?;
+                    let value = self.parse_field_value(value)?;
                     self.insert_key_value(&mut map, key, value)?;
                     idx += 1;
                 }
@@ -826,6 +1307,7 @@ impl Decoder {
             self.insert_key_value(&mut map, key, Value::Null)?;
             idx += 1;
         }
+        self.depth -= 1;
         Ok((map, idx))
     }
 
@@ -837,12 +1319,12 @@ impl Decoder {
         base_level: usize,
     ) -> Result<ParsedArray> {
         self.push_delimiter(header.delimiter);
+        // Length errors point at the header, not the last row read.
+        let header_at = self.current_line.get();
         let result = (|| {
             if let Some(inline) = header.inline.as_deref() {
                 let items = self.parse_inline_array(inline, header.delimiter, header.len)?;
-                if self.strict && items.len() != header.len {
-                    return Err(Error::decode("array length mismatch"));
-                }
+                self.check_array_len(header, items.len())?;
                 return Ok(ParsedArray {
                     value: Value::Array(items),
                     next_idx: idx,
@@ -859,9 +1341,8 @@ impl Decoder {
                     header.delimiter,
                     header.len,
                 )?;
-                if self.strict && rows.len() != header.len {
-                    return Err(Error::decode("array length mismatch"));
-                }
+                self.current_line.set(header_at);
+                self.check_array_len(header, rows.len())?;
                 return Ok(ParsedArray {
                     value: Value::Array(rows),
                     next_idx,
@@ -869,6 +1350,24 @@ impl Decoder {
                 });
             }
 
+            if let Some(columns) = header.columns {
+                let (rows, next_idx) = self.parse_matrix_block(
+                    lines,
+                    idx,
+                    base_level,
+                    columns,
+                    header.delimiter,
+                    header.len,
+                )?;
+                self.current_line.set(header_at);
+                self.check_array_len(header, rows.len())?;
+                return Ok(ParsedArray {
+                    value: Value::Array(rows),
+                    next_idx,
+                    deindent_next: false,
+                });
+            }
+
             if header.len == 0 {
                 return Ok(ParsedArray {
                     value: Value::Array(Vec::new()),
@@ -879,9 +1378,8 @@ impl Decoder {
 
             let (items, next_idx) =
                 self.parse_list_block(lines, idx, base_level + 1, header.len)?;
-            if self.strict && items.len() != header.len {
-                return Err(Error::decode("array length mismatch"));
-            }
+            self.current_line.set(header_at);
+            self.check_array_len(header, items.len())?;
             Ok(ParsedArray {
                 value: Value::Array(items),
                 next_idx,
@@ -892,6 +1390,49 @@ impl Decoder {
         result
     }
 
+    /// Rows of a `[RxC]` matrix: one line per row, one level below the header,
+    /// each holding exactly `columns` delimited scalars.
+    fn parse_matrix_block(
+        &self,
+        lines: &[Line],
+        mut idx: usize,
+        base_level: usize,
+        columns: usize,
+        delimiter: char,
+        expected_len: usize,
+    ) -> Result<(Vec<Value>, usize)> {
+        let mut rows = Vec::with_capacity(expected_len);
+        while idx < lines.len() {
+            let line = &lines[idx];
+            self.mark_line(line);
+            if line.is_blank {
+                if !self.strict {
+                    idx += 1;
+                    continue;
+                }
+                let mut peek = idx + 1;
+                while peek < lines.len() && lines[peek].is_blank {
+                    peek += 1;
+                }
+                if peek >= lines.len() || lines[peek].level <= base_level {
+                    break;
+                }
+                return Err(Error::decode("blank line not allowed in array"));
+            }
+            if line.level <= base_level {
+                break;
+            }
+            if line.level > base_level + 1 {
+                return Err(Error::decode("unexpected indentation"));
+            }
+            let row = self.parse_inline_array(trim_ascii(&line.content), delimiter, columns)?;
+            check_matrix_row(rows.len() + 1, row.len(), columns)?;
+            rows.push(Value::Array(row));
+            idx += 1;
+        }
+        Ok((rows, idx))
+    }
+
     fn parse_tabular_block(
         &self,
         lines: &[Line],
@@ -916,6 +1457,7 @@ impl Decoder {
         let mut row_level = None;
         while idx < lines.len() {
             let line = &lines[idx];
+            self.mark_line(line);
             if line.is_blank {
                 if !self.strict {
                     idx += 1;
@@ -950,7 +1492,9 @@ impl Decoder {
                     row_content = stripped.trim_start();
                 }
             }
-            if !self.split_tabular_row_into(row_content, delimiter, &mut tokens)? {
+            let is_row =
+                self.split_tabular_row_into(row_content, delimiter, rows.len() + 1, &mut tokens)?;
+            if !is_row {
                 return Ok((rows, idx, true));
             }
             if tokens.len() != fields.len() {
@@ -969,7 +1513,7 @@ impl Decoder {
                     let value = if token.is_empty() {
                         Value::String(String::new())
                     } else {
-                        self.parse_value_token(token)?
+                        self.parse_tabular_cell(token, delimiter)?
                     };
                     obj.insert(field_names[idx].clone(), value);
                 }
@@ -978,7 +1522,7 @@ impl Decoder {
                     let value = if token.is_empty() {
                         Value::String(String::new())
                     } else {
-                        self.parse_value_token(token)?
+                        self.parse_tabular_cell(token, delimiter)?
                     };
                     if let Some(parts) = field_paths[idx].as_deref() {
                         self.insert_path(&mut obj, parts, value)?;
@@ -993,10 +1537,12 @@ impl Decoder {
         Ok((rows, idx, false))
     }
 
+    /// `row` is the 1-based row number reported for an unterminated quoted cell.
     fn split_tabular_row_into<'c>(
         &self,
         input: &'c str,
         delimiter: char,
+        row: usize,
         tokens: &mut TokenBuf<'c>,
     ) -> Result<bool> {
         tokens.clear();
@@ -1074,6 +1620,9 @@ impl Decoder {
                             }
                             idx = pos + 1;
                         }
+                        _ if self.delimiter_escape && is_escaped_delimiter(bytes, pos) => {
+                            idx = pos + 1;
+                        }
                         _ => {
                             let token = trim_ascii(&input[start..pos]);
                             tokens.push(token);
@@ -1088,7 +1637,7 @@ impl Decoder {
         }
 
         if in_quotes {
-            return Err(Error::decode("unterminated string"));
+            return Err(unterminated_tabular_cell(row, tokens.len() + 1));
         }
         if colon_before_delim {
             return Ok(false);
@@ -1109,6 +1658,9 @@ impl Decoder {
         if let Some(parts) = self.expandable_path_parts(&key) {
             return self.insert_path(map, &parts, value);
         }
+        if self.strict && map.contains_key(key.value.as_str()) {
+            return Err(duplicate_key(&key.value));
+        }
         if self.expand_paths == ExpandPaths::Safe {
             if let Some(existing) = map.get(key.value.as_str()) {
                 if self.strict && existing.is_object() != value.is_object() {
@@ -1120,14 +1672,23 @@ impl Decoder {
         Ok(())
     }
 
+    fn is_separated_path_key(&self, token: &str) -> bool {
+        self.expand_paths == ExpandPaths::Safe
+            && is_separated_identifier_path(token, &self.path_separator)
+    }
+
     fn expandable_path_parts<'a>(&self, key: &'a KeyToken) -> Option<Vec<&'a str>> {
         if self.expand_paths != ExpandPaths::Safe {
             return None;
         }
-        if key.quoted || !key.value.as_str().contains('.') {
+        if let Some(path) = &key.path {
+            return Some(path.iter().map(String::as_str).collect());
+        }
+        let separator = self.path_separator.as_str();
+        if key.quoted || !key.value.as_str().contains(separator) {
             return None;
         }
-        let parts: Vec<&str> = key.value.as_str().split('.').collect();
+        let parts: Vec<&str> = key.value.as_str().split(separator).collect();
         if parts.iter().all(|part| is_identifier_segment(part)) {
             Some(parts)
         } else {
@@ -1170,6 +1731,7 @@ impl Decoder {
         if parts.is_empty() {
             return Err(Error::decode("invalid path"));
         }
+        descend(self.depth + parts.len() - 1, self.max_depth)?;
         let key = parts[0];
         if parts.len() == 1 {
             if let Some(existing) = map.get_mut(key) {
@@ -1215,9 +1777,11 @@ impl Decoder {
         item_level: usize,
         expected_len: usize,
     ) -> Result<(Vec<Value>, usize)> {
+        self.depth = descend(self.depth, self.max_depth)?;
         let mut items = Vec::with_capacity(expected_len);
         while idx < lines.len() {
             let line = &lines[idx];
+            self.mark_line(line);
             if line.is_blank {
                 if !self.strict {
                     idx += 1;
@@ -1249,6 +1813,7 @@ impl Decoder {
             items.push(item);
             idx = next_idx;
         }
+        self.depth -= 1;
         Ok((items, idx))
     }
 
@@ -1263,6 +1828,11 @@ impl Decoder {
             return Ok((Value::Object(Map::new()), idx));
         }
 
+        if self.inline_objects && item_content.starts_with('{') {
+            let map = self.parse_inline_object(trim_ascii(item_content))?;
+            return Ok((Value::Object(map), idx));
+        }
+
         if let Some(header) = self.parse_array_header(item_content)? {
             if header.key.is_none() {
                 let parsed = self.parse_array_from_header(&header, lines, idx, item_level)?;
@@ -1294,9 +1864,7 @@ impl Decoder {
                     header.delimiter,
                     header.len,
                 )?;
-                if self.strict && rows.len() != header.len {
-                    return Err(Error::decode("array length mismatch"));
-                }
+                self.check_array_len(&header, rows.len())?;
                 ParsedArray {
                     value: Value::Array(rows),
                     next_idx,
@@ -1335,6 +1903,9 @@ impl Decoder {
             level: base_level,
             content: first_content.to_string(),
             is_blank: false,
+            at: idx
+                .checked_sub(1)
+                .map_or_else(Location::default, |item| lines[item].at),
         });
         combined.extend_from_slice(&lines[idx..]);
         let (map, consumed) = self.parse_object_block(&combined, 0, base_level)?;
@@ -1347,11 +1918,16 @@ impl Decoder {
 struct KeyToken {
     value: SmolStr,
     quoted: bool,
+    /// Segments of a key with quoted parts such as `"a b".c`; `value` holds
+    /// them joined by the path separator.
+    path: Option<Vec<String>>,
 }
 
 struct HeaderLine {
     key: Option<KeyToken>,
     len: usize,
+    /// Row width of a `[RxC]` matrix header.
+    columns: Option<usize>,
     delimiter: char,
     fields: Option<Vec<KeyToken>>,
     inline: Option<String>,
@@ -1369,6 +1945,9 @@ struct Line {
     level: usize,
     content: String,
     is_blank: bool,
+    /// Where `content` starts in the input; unset for lines built by the
+    /// streaming validator, which tracks locations itself.
+    at: Location,
 }
 
 pub(super) fn parse_number_token(token: &str) -> Option<serde_json::Number> {
@@ -1376,7 +1955,7 @@ pub(super) fn parse_number_token(token: &str) -> Option<serde_json::Number> {
         return None;
     }
     if token == "-0" {
-        return serde_json::Number::from_f64(0.0);
+        return serde_json::Number::from_f64(-0.0);
     }
     let has_float = token
         .as_bytes()
@@ -1397,6 +1976,259 @@ pub(super) fn parse_number_token(token: &str) -> Option<serde_json::Number> {
     serde_json::Number::from_f64(float)
 }
 
+fn normalize_key_case(key: &str, key_case: KeyCase) -> SmolStr {
+    match key_case {
+        KeyCase::Preserve => SmolStr::new(key),
+        KeyCase::Lower => SmolStr::new(key.to_lowercase()),
+        KeyCase::Snake => {
+            let chars: Vec<char> = key.chars().collect();
+            let mut out = String::with_capacity(key.len() + 4);
+            for (idx, &ch) in chars.iter().enumerate() {
+                if ch == '-' || ch == ' ' {
+                    out.push('_');
+                    continue;
+                }
+                if ch.is_uppercase() {
+                    let prev = idx.checked_sub(1).map(|prev| chars[prev]);
+                    let next = chars.get(idx + 1);
+                    let boundary = match prev {
+                        Some(prev) if prev.is_lowercase() || prev.is_ascii_digit() => true,
+                        Some(prev) if prev.is_uppercase() => next.is_some_and(|c| c.is_lowercase()),
+                        _ => false,
+                    };
+                    if boundary {
+                        out.push('_');
+                    }
+                    out.extend(ch.to_lowercase());
+                } else {
+                    out.push(ch);
+                }
+            }
+            SmolStr::new(out)
+        }
+    }
+}
+
+pub(super) fn is_escaped_delimiter(bytes: &[u8], pos: usize) -> bool {
+    pos > 0 && bytes[pos - 1] == b'\\'
+}
+
+/// Replaces `\<delimiter>` with the delimiter in an unquoted cell, or returns
+/// `None` when the cell is quoted or has no escaped delimiter.
+pub(super) fn unescape_delimiters(token: &str, delimiter: char) -> Option<String> {
+    if token.starts_with('"') {
+        return None;
+    }
+    let mut escaped = [0u8; 5];
+    escaped[0] = b'\\';
+    let len = 1 + delimiter.encode_utf8(&mut escaped[1..]).len();
+    let escaped = std::str::from_utf8(&escaped[..len]).ok()?;
+    if !token.contains(escaped) {
+        return None;
+    }
+    Some(token.replace(escaped, delimiter.encode_utf8(&mut [0u8; 4])))
+}
+
+fn split_inline_entries(inner: &str) -> Result<Vec<&str>> {
+    let mut entries = Vec::new();
+    let mut depth = 0usize;
+    let mut in_quotes = false;
+    let mut escape = false;
+    let mut start = 0;
+    for (idx, byte) in inner.as_bytes().iter().enumerate() {
+        if escape {
+            escape = false;
+            continue;
+        }
+        if in_quotes {
+            match byte {
+                b'\\' => escape = true,
+                b'"' => in_quotes = false,
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_quotes = true,
+            b'{' => depth += 1,
+            b'}' => {
+                depth = depth
+                    .checked_sub(1)
+                    .ok_or_else(|| Error::decode("unbalanced inline object"))?;
+            }
+            b',' if depth == 0 => {
+                entries.push(&inner[start..idx]);
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+    if in_quotes {
+        return Err(Error::decode_incomplete("unterminated string"));
+    }
+    if depth != 0 {
+        return Err(Error::decode_incomplete("unterminated inline object"));
+    }
+    entries.push(&inner[start..]);
+    Ok(entries)
+}
+
+/// Removes `_` separators that sit between two ASCII digits, or returns `None`
+/// when the token has none or any `_` is misplaced.
+fn strip_digit_separators(token: &str) -> Option<String> {
+    let bytes = token.as_bytes();
+    if !token.is_ascii() || !bytes.contains(&b'_') {
+        return None;
+    }
+    let mut out = String::with_capacity(token.len());
+    for (idx, &byte) in bytes.iter().enumerate() {
+        if byte != b'_' {
+            out.push(byte as char);
+            continue;
+        }
+        let between_digits = idx > 0
+            && bytes[idx - 1].is_ascii_digit()
+            && bytes.get(idx + 1).is_some_and(u8::is_ascii_digit);
+        if !between_digits {
+            return None;
+        }
+    }
+    Some(out)
+}
+
+pub(super) fn ensure_canonical_number(token: &str) -> Result<()> {
+    if !is_numeric_like(token) {
+        return Ok(());
+    }
+    if is_int_with_leading_zero(token) {
+        return Err(Error::decode("non-canonical number"));
+    }
+    if let Some(number) = parse_number_token(token) {
+        if format_json_number(&number) != token {
+            return Err(Error::decode("non-canonical number"));
+        }
+    }
+    Ok(())
+}
+
+/// Fewer items than declared reads as truncated input; more is malformed.
+pub(super) fn array_length_mismatch(expected: usize, found: usize) -> Error {
+    if found < expected {
+        Error::decode_incomplete("array length mismatch")
+    } else {
+        Error::decode("array length mismatch")
+    }
+}
+
+/// Splits the `xC` column count of a `[RxC]` matrix header off the bracket
+/// contents that follow the row count. Without `matrix_tabular` the `x` is
+/// left in place and later rejected as a delimiter, as the spec requires.
+/// Rows cannot be zero values wide, so `[Rx0]` is an error; `[0xC]` is an
+/// empty array.
+pub(super) fn split_matrix_columns(
+    remainder: &str,
+    matrix_tabular: bool,
+) -> Result<(Option<usize>, &str)> {
+    let Some(rest) = remainder.strip_prefix('x').filter(|_| matrix_tabular) else {
+        return Ok((None, remainder));
+    };
+    let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+    if digits == 0 {
+        return Err(Error::decode("matrix column count missing"));
+    }
+    let columns = rest[..digits]
+        .parse()
+        .map_err(|err| Error::decode_with_source("invalid matrix column count", err))?;
+    if columns == 0 {
+        return Err(Error::decode("matrix column count must be at least 1"));
+    }
+    Ok((Some(columns), &rest[digits..]))
+}
+
+/// Bytes of leading spaces and tabs; indentation errors point just past them.
+pub(super) fn indent_width(text: &str) -> usize {
+    text.len() - text.trim_start_matches([' ', '\t']).len()
+}
+
+pub(super) fn check_matrix_row(row: usize, found: usize, columns: usize) -> Result<()> {
+    if found == columns {
+        return Ok(());
+    }
+    Err(Error::decode(format!(
+        "matrix row {row} has {found} values, expected {columns}"
+    )))
+}
+
+/// The nesting depth after opening one more block, or an error past `max`.
+pub(super) fn descend(depth: usize, max: usize) -> Result<usize> {
+    if depth >= max {
+        return Err(Error::decode("maximum nesting depth exceeded"));
+    }
+    Ok(depth + 1)
+}
+
+pub(super) fn array_too_long(key: Option<&str>, len: usize, max: usize) -> Error {
+    let array = match key {
+        Some(key) => format!("array \"{key}\""),
+        None => "root array".to_string(),
+    };
+    Error::decode(format!(
+        "{array} has {len} items, exceeding max_array_len {max}"
+    ))
+}
+
+pub(super) fn non_finite_error() -> Error {
+    Error::decode("non-finite numbers must be null")
+}
+
+pub(super) fn is_negative_zero(token: &str) -> bool {
+    token.starts_with('-')
+        && parse_number_token(token).and_then(|number| number.as_f64()) == Some(0.0)
+}
+
+pub(super) fn negative_zero_error() -> Error {
+    Error::decode("negative zero is not allowed")
+}
+
+pub(super) fn duplicate_key(key: &str) -> Error {
+    Error::decode(format!("duplicate key '{key}'"))
+}
+
+pub(super) fn unterminated_tabular_cell(row: usize, column: usize) -> Error {
+    Error::decode_incomplete(format!(
+        "unterminated string in tabular row {row}, column {column} (quoted values cannot span \
+         lines; escape newlines as \\n)"
+    ))
+}
+
+pub(super) fn push_unknown_escape(
+    out: &mut String,
+    inner: &str,
+    escaped_idx: usize,
+    policy: EscapePolicy,
+) -> Result<()> {
+    let escaped_end = escaped_idx + utf8_char_len(inner.as_bytes()[escaped_idx]);
+    let escaped = &inner[escaped_idx..escaped_end];
+    match policy {
+        EscapePolicy::Error => return Err(Error::decode("invalid escape")),
+        EscapePolicy::Keep => {
+            out.push('\\');
+            out.push_str(escaped);
+        }
+        EscapePolicy::Strip => out.push_str(escaped),
+    }
+    Ok(())
+}
+
+pub(super) fn utf8_char_len(first_byte: u8) -> usize {
+    match first_byte {
+        0x00..=0x7F => 1,
+        0xC0..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        _ => 4,
+    }
+}
+
 pub(super) fn is_int_with_leading_zero(token: &str) -> bool {
     let mut chars = token.chars();
     let first = chars.next();
@@ -1466,6 +2298,61 @@ pub(super) fn trim_ascii(input: &str) -> &str {
     &input[start..end]
 }
 
+/// Decides which lines are `#` comments. Inside a tabular or matrix block, a
+/// line deeper than the header is a row even when its first cell starts with
+/// `#`; everywhere else a leading `#` marks a comment. Feed it every line of
+/// the document in order.
+#[derive(Default)]
+pub(crate) struct CommentScope {
+    /// Indent of the open tabular or matrix header.
+    row_block: Option<usize>,
+}
+
+impl CommentScope {
+    pub(crate) fn is_comment(&mut self, line: &str) -> bool {
+        let content = line.trim_start_matches([' ', '\t']);
+        if is_blank_line(content) {
+            return false;
+        }
+        let indent = line.len() - content.len();
+        if let Some(header) = self.row_block {
+            if indent > header {
+                return false;
+            }
+            self.row_block = None;
+        }
+        if content.starts_with('#') {
+            return true;
+        }
+        if opens_row_block(content) {
+            self.row_block = Some(indent);
+        }
+        false
+    }
+}
+
+/// Whether `content` is a `key[N]{a,b}:` or `key[RxC]:` header whose rows
+/// follow on deeper lines.
+fn opens_row_block(content: &str) -> bool {
+    let Some(head) = content.trim_end().strip_suffix(':') else {
+        return false;
+    };
+    if head.ends_with('}') {
+        return head.contains("]{");
+    }
+    let Some((_, bracket)) = head
+        .strip_suffix(']')
+        .and_then(|head| head.rsplit_once('['))
+    else {
+        return false;
+    };
+    let rows = bracket.bytes().take_while(u8::is_ascii_digit).count();
+    rows > 0
+        && bracket[rows..]
+            .strip_prefix('x')
+            .is_some_and(|columns| columns.starts_with(|ch: char| ch.is_ascii_digit()))
+}
+
 pub(super) fn is_blank_line(line: &str) -> bool {
     if line.is_ascii() {
         return line
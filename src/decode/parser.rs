@@ -4,47 +4,110 @@ use memchr::{memchr, memchr2, memchr3, memchr_iter};
 use smallvec::SmallVec;
 use smol_str::SmolStr;
 
-use crate::arena::{ArenaView, Node, NodeData, NodeKind, Pair, Span, StringRef};
-use crate::text::string::is_canonical_unquoted_key;
-use crate::{DecodeOptions, Error, Indent, Result};
+use crate::arena::{ArenaView, Node, NodeData, NodeKind, Pair, RawSpan, Span, StringRef};
+use crate::text::string::{is_canonical_unquoted_key, is_non_finite_literal, split_quoted_path};
+use crate::{DecodeOptions, Error, EscapePolicy, Indent, NegZeroPolicy, NonFinitePolicy, Result};
 
 use super::scan::{scan_lines, ScanLine, ScanResult};
-use super::{contains_whitespace, parse_number_token, trim_ascii};
+use super::{
+    array_length_mismatch, array_too_long, check_matrix_row, contains_whitespace, descend,
+    duplicate_key, ensure_canonical_number, is_escaped_delimiter, is_negative_zero,
+    negative_zero_error, non_finite_error, parse_number_token, push_unknown_escape,
+    split_matrix_columns, trim_ascii, unescape_delimiters, unterminated_tabular_cell,
+    utf8_char_len,
+};
 
 type TokenBuf<'a> = SmallVec<[&'a str; 16]>;
+
+/// Objects with fewer pairs than this find repeated keys by scanning. Larger
+/// ones use a std `HashMap`, not `CacheMap`: its keys come from the input, so
+/// it keeps the randomly seeded hasher even with `fast-hash`.
+const PAIR_SCAN_LEN: usize = 8;
+
 pub fn parse_into<'a>(arena: &mut ArenaView<'a>, options: &DecodeOptions) -> Result<usize> {
     let mut parser = ArenaParser::new(arena, options);
-    parser.parse_document()
+    let root = parser.parse_document()?;
+    arena.root = root;
+    Ok(root)
 }
 
 struct ArenaParser<'a, 'b> {
     arena: &'b mut ArenaView<'a>,
     indent_size: usize,
+    indent_tabs: bool,
+    /// Joins the segments of keys such as `"a b".c`, which stay literal here.
+    path_separator: String,
     strict: bool,
+    allow_comments: bool,
+    unknown_escape: EscapePolicy,
+    canonical_numbers: bool,
+    coerce_numbers: bool,
+    delimiter_escape: bool,
+    semicolon_delimiter: bool,
+    matrix_tabular: bool,
+    non_finite: NonFinitePolicy,
+    negative_zero: NegZeroPolicy,
+    trim_quoted: bool,
+    max_array_len: usize,
+    max_depth: usize,
+    /// Open object and list blocks. Not unwound on error, which ends the parse.
+    depth: usize,
     active_delimiter: char,
     delimiter_stack: Vec<char>,
-    key_lookup: HashMap<SmolStr, usize>,
     null_node: Option<usize>,
     empty_string_node: Option<usize>,
+    intern_threshold: Option<usize>,
+    /// Keys counted for `intern_threshold`. Like the pair index, it keeps the
+    /// randomly seeded std hasher since its keys come from the input.
+    key_counts: HashMap<SmolStr, KeyCount>,
+}
+
+/// Occurrences of a key so far and, once past the intern threshold, the
+/// `keys` entry they share.
+#[derive(Default)]
+struct KeyCount {
+    seen: usize,
+    id: Option<usize>,
 }
 
 impl<'a, 'b> ArenaParser<'a, 'b> {
     fn new(arena: &'b mut ArenaView<'a>, options: &DecodeOptions) -> Self {
-        let Indent::Spaces(indent_size) = options.indent;
         Self {
             arena,
-            indent_size,
+            indent_size: options.indent.width(),
+            indent_tabs: options.indent == Indent::Tabs,
+            path_separator: options.expand_separator.clone(),
             strict: options.strict,
+            allow_comments: options.allow_comments,
+            unknown_escape: options.unknown_escape_policy(),
+            canonical_numbers: options.strict_number_canonicalization,
+            coerce_numbers: options.coerce_numbers,
+            delimiter_escape: options.delimiter_escape,
+            semicolon_delimiter: options.semicolon_delimiter,
+            matrix_tabular: options.matrix_tabular,
+            non_finite: options.non_finite,
+            negative_zero: options.negative_zero,
+            trim_quoted: options.trim_quoted,
+            max_array_len: options.max_array_len,
+            max_depth: options.max_depth,
+            depth: 0,
             active_delimiter: ',',
             delimiter_stack: Vec::new(),
-            key_lookup: HashMap::new(),
             null_node: None,
             empty_string_node: None,
+            intern_threshold: options.intern_threshold,
+            key_counts: HashMap::new(),
         }
     }
 
     fn parse_document(&mut self) -> Result<usize> {
-        let scan = scan_lines(self.arena.input, self.indent_size, self.strict)?;
+        let scan = scan_lines(
+            self.arena.input,
+            self.indent_size,
+            self.indent_tabs,
+            self.strict,
+            self.allow_comments,
+        )?;
         self.reserve_from_scan(&scan);
         if scan.non_blank == 0 {
             return Ok(self.push_object(&[]));
@@ -97,12 +160,14 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
         if let Some(array) = self.parse_array_line(line)? {
             return Ok(array);
         }
-        if let (Some(bracket_idx), Some(colon_idx)) = (line.find('['), line.find(':')) {
-            if bracket_idx < colon_idx {
+        if let (Some(bracket_idx), Some((key_part, _))) =
+            (line.find('['), self.split_key_value(line)?)
+        {
+            if bracket_idx < key_part.len() {
                 if let Some(header) = self.parse_array_header(line)? {
                     if let Some(key) = header.key.as_ref() {
                         let value = self.build_array_value(&header)?;
-                        let key_id = self.intern_key(&key.value);
+                        let key_id = self.push_key(&key.value);
                         let pairs = vec![Pair { key: key_id, value }];
                         return Ok(self.push_object(&pairs));
                     }
@@ -117,7 +182,7 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
             } else {
                 self.parse_value_token(value)?
             };
-            let key_id = self.intern_key(&key.value);
+            let key_id = self.push_key(&key.value);
             let pairs = vec![Pair {
                 key: key_id,
                 value: value_id,
@@ -157,9 +222,7 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
         if header.inline.is_none() && header.len > 0 {
             return Err(Error::decode("array payload not implemented"));
         }
-        if self.strict && header.len != items.len() {
-            return Err(Error::decode("array length mismatch"));
-        }
+        self.check_array_len(header, items.len())?;
         Ok(self.push_array(&items))
     }
 
@@ -175,7 +238,7 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
             if token.is_empty() {
                 values.push(self.empty_string_node());
             } else {
-                values.push(self.parse_value_token_trimmed(token)?);
+                values.push(self.parse_cell_token(token, delimiter)?);
             }
         }
         Ok(values)
@@ -195,9 +258,7 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
         let result = (|| {
             if let Some(inline) = header.inline {
                 let items = self.parse_inline_array(inline, header.delimiter, header.len)?;
-                if self.strict && items.len() != header.len {
-                    return Err(Error::decode("array length mismatch"));
-                }
+                self.check_array_len(header, items.len())?;
                 return Ok(ParsedArray {
                     node_id: self.push_array(&items),
                     next_idx: idx,
@@ -214,9 +275,7 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
                     header.delimiter,
                     header.len,
                 )?;
-                if self.strict && rows.len() != header.len {
-                    return Err(Error::decode("array length mismatch"));
-                }
+                self.check_array_len(header, rows.len())?;
                 return Ok(ParsedArray {
                     node_id: self.push_array(&rows),
                     next_idx,
@@ -224,6 +283,23 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
                 });
             }
 
+            if let Some(columns) = header.columns {
+                let (rows, next_idx) = self.parse_matrix_block(
+                    scan,
+                    idx,
+                    base_level,
+                    columns,
+                    header.delimiter,
+                    header.len,
+                )?;
+                self.check_array_len(header, rows.len())?;
+                return Ok(ParsedArray {
+                    node_id: self.push_array(&rows),
+                    next_idx,
+                    deindent_next: false,
+                });
+            }
+
             if header.len == 0 {
                 return Ok(ParsedArray {
                     node_id: self.push_array(&[]),
@@ -233,9 +309,7 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
             }
 
             let (items, next_idx) = self.parse_list_block(scan, idx, base_level + 1, header.len)?;
-            if self.strict && items.len() != header.len {
-                return Err(Error::decode("array length mismatch"));
-            }
+            self.check_array_len(header, items.len())?;
             Ok(ParsedArray {
                 node_id: self.push_array(&items),
                 next_idx,
@@ -243,9 +317,60 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
             })
         })();
         self.pop_delimiter();
+        if let Ok(parsed) = &result {
+            let dedent = base_level * self.indent_size;
+            self.record_raw(
+                parsed.node_id,
+                header.raw,
+                scan,
+                idx..parsed.next_idx,
+                dedent,
+            );
+        }
         result
     }
 
+    fn parse_matrix_block(
+        &mut self,
+        scan: &ScanResult,
+        mut idx: usize,
+        base_level: usize,
+        columns: usize,
+        delimiter: char,
+        expected_len: usize,
+    ) -> Result<(Vec<usize>, usize)> {
+        let mut rows = Vec::with_capacity(expected_len);
+        while idx < scan.lines.len() {
+            let line = &scan.lines[idx];
+            if line.is_blank {
+                if !self.strict {
+                    idx += 1;
+                    continue;
+                }
+                let mut peek = idx + 1;
+                while peek < scan.lines.len() && scan.lines[peek].is_blank {
+                    peek += 1;
+                }
+                if peek >= scan.lines.len() || scan.lines[peek].level <= base_level {
+                    break;
+                }
+                return Err(Error::decode("blank line not allowed in array"));
+            }
+            if line.level <= base_level {
+                break;
+            }
+            if line.level > base_level + 1 {
+                return Err(Error::decode("unexpected indentation"));
+            }
+            let row =
+                self.parse_inline_array(trim_ascii(self.line_content(line)), delimiter, columns)?;
+            check_matrix_row(rows.len() + 1, row.len(), columns)?;
+            rows.push(self.push_array(&row));
+            idx += 1;
+        }
+        Ok((rows, idx))
+    }
+
     fn parse_tabular_block(
         &mut self,
         scan: &ScanResult,
@@ -258,11 +383,11 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
         let mut rows = Vec::with_capacity(expected_len);
         let mut tokens = TokenBuf::with_capacity(fields.len());
         let mut value_ids: SmallVec<[usize; 16]> = SmallVec::with_capacity(fields.len());
-        let mut field_key_ids = Vec::with_capacity(fields.len());
-        for field in fields {
-            field_key_ids.push(self.intern_key(&field.value));
-        }
-        let (unique_keys, field_slots) = build_field_slots(&field_key_ids);
+        let (unique_fields, field_slots) = build_field_slots(fields);
+        let unique_keys: Vec<usize> = unique_fields
+            .into_iter()
+            .map(|field| self.push_key(&field.value))
+            .collect();
         let null_id = self.null_node();
         let row_template: Vec<Pair> = unique_keys
             .iter()
@@ -313,7 +438,9 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
                     row_content = stripped.trim_start();
                 }
             }
-            if !self.split_tabular_row_into(row_content, delimiter, &mut tokens)? {
+            let is_row =
+                self.split_tabular_row_into(row_content, delimiter, rows.len() + 1, &mut tokens)?;
+            if !is_row {
                 return Ok((rows, idx, true));
             }
             if tokens.len() != fields.len() {
@@ -331,7 +458,7 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
                 let value_id = if token.is_empty() {
                     self.empty_string_node()
                 } else {
-                    self.parse_value_token_trimmed(token)?
+                    self.parse_cell_token(token, delimiter)?
                 };
                 value_ids.push(value_id);
             }
@@ -369,6 +496,7 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
         item_level: usize,
         expected_len: usize,
     ) -> Result<(Vec<usize>, usize)> {
+        self.depth = descend(self.depth, self.max_depth)?;
         let mut items = Vec::with_capacity(expected_len);
         while idx < scan.lines.len() {
             let line = &scan.lines[idx];
@@ -402,6 +530,7 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
             items.push(item);
             idx = next_idx;
         }
+        self.depth -= 1;
         Ok((items, idx))
     }
 
@@ -437,8 +566,7 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
             let parsed = self.parse_array_from_header(&header, scan, idx, array_base_level)?;
             let mut pairs = Vec::new();
             let mut pair_index = HashMap::new();
-            let key_id = self.intern_key(&key.value);
-            insert_pair(&mut pairs, &mut pair_index, key_id, parsed.node_id);
+            self.insert_pair(&mut pairs, &mut pair_index, &key.value, parsed.node_id)?;
             let next_idx = self.parse_object_block_into(
                 scan,
                 parsed.next_idx,
@@ -447,6 +575,8 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
                 &mut pair_index,
             )?;
             let obj_node = self.push_object(&pairs);
+            let dedent = (item_level + 1) * self.indent_size;
+            self.record_raw(obj_node, item_content, scan, idx..next_idx, dedent);
             return Ok((obj_node, next_idx));
         }
 
@@ -470,19 +600,21 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
         let mut pair_index = HashMap::new();
         if let Some((key, value)) = self.split_key_value(first_content)? {
             let key = self.parse_key_token(trim_ascii(key))?;
-            let key_id = self.intern_key(&key.value);
             if trim_ascii(value).is_empty() {
                 let (nested, next_idx) = self.parse_object_block(scan, idx, base_level + 1)?;
-                insert_pair(&mut pairs, &mut pair_index, key_id, nested);
+                self.insert_pair(&mut pairs, &mut pair_index, &key.value, nested)?;
                 idx = next_idx;
             } else {
                 let value_id = self.parse_value_token(value)?;
-                insert_pair(&mut pairs, &mut pair_index, key_id, value_id);
+                self.insert_pair(&mut pairs, &mut pair_index, &key.value, value_id)?;
             }
         }
+        let first_idx = idx;
         let next_idx =
             self.parse_object_block_into(scan, idx, base_level, &mut pairs, &mut pair_index)?;
         let obj_node = self.push_object(&pairs);
+        let dedent = base_level * self.indent_size;
+        self.record_raw(obj_node, first_content, scan, first_idx..next_idx, dedent);
         Ok((obj_node, next_idx))
     }
 
@@ -497,6 +629,11 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
         let next_idx =
             self.parse_object_block_into(scan, idx, base_level, &mut pairs, &mut pair_index)?;
         let obj_node = self.push_object(&pairs);
+        if let Some(first) = (idx..next_idx).find(|&line| !scan.lines[line].is_blank) {
+            let head = trim_ascii(self.line_content(&scan.lines[first]));
+            let dedent = base_level * self.indent_size;
+            self.record_raw(obj_node, head, scan, first + 1..next_idx, dedent);
+        }
         Ok((obj_node, next_idx))
     }
 
@@ -506,8 +643,9 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
         mut idx: usize,
         base_level: usize,
         pairs: &mut Vec<Pair>,
-        pair_index: &mut HashMap<usize, usize>,
+        pair_index: &mut HashMap<SmolStr, usize>,
     ) -> Result<usize> {
+        self.depth = descend(self.depth, self.max_depth)?;
         let mut override_level: Option<usize> = None;
         while idx < scan.lines.len() {
             let line = &scan.lines[idx];
@@ -531,8 +669,7 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
                     .as_ref()
                     .ok_or_else(|| Error::decode("array header missing key in object context"))?;
                 let parsed = self.parse_array_from_header(&header, scan, idx + 1, base_level)?;
-                let key_id = self.intern_key(&key.value);
-                insert_pair(pairs, pair_index, key_id, parsed.node_id);
+                self.insert_pair(pairs, pair_index, &key.value, parsed.node_id)?;
                 if parsed.deindent_next {
                     override_level = Some(base_level);
                 }
@@ -542,15 +679,14 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
 
             if let Some((key, value)) = self.split_key_value(content)? {
                 let key = self.parse_key_token(trim_ascii(key))?;
-                let key_id = self.intern_key(&key.value);
                 if trim_ascii(value).is_empty() {
                     let (nested, next_idx) =
                         self.parse_object_block(scan, idx + 1, base_level + 1)?;
-                    insert_pair(pairs, pair_index, key_id, nested);
+                    self.insert_pair(pairs, pair_index, &key.value, nested)?;
                     idx = next_idx;
                 } else {
                     let value_id = self.parse_value_token(value)?;
-                    insert_pair(pairs, pair_index, key_id, value_id);
+                    self.insert_pair(pairs, pair_index, &key.value, value_id)?;
                     idx += 1;
                 }
                 continue;
@@ -560,14 +696,23 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
                 return Err(Error::decode("bare key not allowed in strict mode"));
             }
             let key = self.parse_key_token(content)?;
-            let key_id = self.intern_key(&key.value);
             let null_id = self.null_node();
-            insert_pair(pairs, pair_index, key_id, null_id);
+            self.insert_pair(pairs, pair_index, &key.value, null_id)?;
             idx += 1;
         }
+        self.depth -= 1;
         Ok(idx)
     }
 
+    fn parse_cell_token(&mut self, token: &str, delimiter: char) -> Result<usize> {
+        if self.delimiter_escape {
+            if let Some(unescaped) = unescape_delimiters(token, delimiter) {
+                return Ok(self.push_string(StringRef::Owned(unescaped)));
+            }
+        }
+        self.parse_value_token_trimmed(token)
+    }
+
     fn parse_value_token(&mut self, token: &str) -> Result<usize> {
         let token = trim_ascii(token);
         self.parse_value_token_trimmed(token)
@@ -578,15 +723,42 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
             return Err(Error::decode("empty value"));
         }
         if token.starts_with('"') {
-            let string_ref = self.parse_quoted_ref(token)?;
+            let mut string_ref = self.parse_quoted_ref(token)?;
+            if self.trim_quoted {
+                string_ref = self.trim_string_ref(string_ref);
+            }
             return Ok(self.push_string(string_ref));
         }
         match token {
             "null" => return Ok(self.null_node()),
             "true" => return Ok(self.push_bool(true)),
             "false" => return Ok(self.push_bool(false)),
+            _ if is_non_finite_literal(token) => {
+                return match self.non_finite {
+                    NonFinitePolicy::Error => Err(non_finite_error()),
+                    NonFinitePolicy::Null => Ok(self.null_node()),
+                    NonFinitePolicy::Keep => {
+                        let span = self.span_for(token);
+                        Ok(self.push_string(StringRef::Span(span)))
+                    }
+                };
+            }
             _ => {}
         }
+        if !self.coerce_numbers {
+            let span = self.span_for(token);
+            return Ok(self.push_string(StringRef::Span(span)));
+        }
+        if self.canonical_numbers {
+            ensure_canonical_number(token)?;
+        }
+        if self.negative_zero != NegZeroPolicy::PreserveFloat && is_negative_zero(token) {
+            if self.negative_zero == NegZeroPolicy::Error {
+                return Err(negative_zero_error());
+            }
+            let span = self.span_for(&token[1..]);
+            return Ok(self.push_number(span));
+        }
         if parse_number_token(token).is_some() {
             let span = self.span_for(token);
             return Ok(self.push_number(span));
@@ -595,8 +767,41 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
         Ok(self.push_string(StringRef::Span(span)))
     }
 
+    fn trim_string_ref(&self, string_ref: StringRef) -> StringRef {
+        match string_ref {
+            StringRef::Span(span) => {
+                StringRef::Span(self.span_for(self.arena.input[span.start..span.end].trim()))
+            }
+            StringRef::Owned(value) => StringRef::Owned(value.trim().to_string()),
+        }
+    }
+
     fn parse_key_token(&self, token: &str) -> Result<KeyToken> {
         let token = trim_ascii(token);
+        if let Some(segments) = split_quoted_path(token, &self.path_separator) {
+            let mut value = String::with_capacity(token.len());
+            for (idx, segment) in segments.into_iter().enumerate() {
+                if idx > 0 {
+                    value.push_str(&self.path_separator);
+                }
+                if segment.starts_with('"') {
+                    match self.parse_quoted_ref(segment)? {
+                        StringRef::Span(span) => value.push_str(
+                            self.arena
+                                .input
+                                .get(span.start..span.end)
+                                .ok_or_else(|| Error::decode("invalid string span"))?,
+                        ),
+                        StringRef::Owned(segment) => value.push_str(&segment),
+                    }
+                } else {
+                    value.push_str(segment);
+                }
+            }
+            return Ok(KeyToken {
+                value: SmolStr::new(value),
+            });
+        }
         if token.starts_with('"') {
             let value_ref = self.parse_quoted_ref(token)?;
             let value = match value_ref {
@@ -629,7 +834,7 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
     fn parse_quoted_ref(&self, token: &str) -> Result<StringRef> {
         let token = trim_ascii(token);
         if token.len() < 2 || !token.starts_with('"') || !token.ends_with('"') {
-            return Err(Error::decode("unterminated string"));
+            return Err(Error::decode_incomplete("unterminated string"));
         }
         let inner = &token[1..token.len() - 1];
         let bytes = inner.as_bytes();
@@ -651,18 +856,18 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
                     let next_idx = pos + 1;
                     let next = bytes
                         .get(next_idx)
-                        .ok_or_else(|| Error::decode("unterminated escape"))?;
+                        .ok_or_else(|| Error::decode_incomplete("unterminated escape"))?;
                     match next {
                         b'n' => out.push('\n'),
                         b'r' => out.push('\r'),
                         b't' => out.push('\t'),
                         b'"' => out.push('"'),
                         b'\\' => out.push('\\'),
-                        _ => return Err(Error::decode("invalid escape")),
+                        _ => push_unknown_escape(&mut out, inner, next_idx, self.unknown_escape)?,
                     }
-                    idx = pos + 2;
+                    idx = next_idx + utf8_char_len(*next);
                 }
-                _ => return Err(Error::decode("unterminated string")),
+                _ => return Err(Error::decode_incomplete("unterminated string")),
             }
         }
         Ok(StringRef::Owned(out))
@@ -704,7 +909,7 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
             }
         }
         if in_quotes {
-            return Err(Error::decode("unterminated string"));
+            return Err(Error::decode_incomplete("unterminated string"));
         }
         Ok(None)
     }
@@ -738,7 +943,7 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
             }
         }
         if in_quotes {
-            return Err(Error::decode("unterminated string"));
+            return Err(Error::decode_incomplete("unterminated string"));
         }
         let bracket_start = match bracket_start {
             Some(idx) => idx,
@@ -746,7 +951,7 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
         };
         let bracket_end = match line[bracket_start + 1..].find(']') {
             Some(idx) => bracket_start + 1 + idx,
-            None => return Err(Error::decode("unterminated array header")),
+            None => return Err(Error::decode_incomplete("unterminated array header")),
         };
 
         let key_part = trim_ascii(&line[..bracket_start]);
@@ -773,8 +978,22 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
         }
         let len: usize = inner[..digits_end]
             .parse()
-            .map_err(|_| Error::decode("invalid array length"))?;
-        let remainder = &inner[digits_end..];
+            .map_err(|err| Error::decode_with_source("invalid array length", err))?;
+        if len > self.max_array_len {
+            return Err(array_too_long(
+                key.as_ref().map(|key| key.value.as_str()),
+                len,
+                self.max_array_len,
+            ));
+        }
+        let (columns, remainder) = split_matrix_columns(&inner[digits_end..], self.matrix_tabular)?;
+        if let Some(columns) = columns.filter(|&columns| columns > self.max_array_len) {
+            return Err(array_too_long(
+                key.as_ref().map(|key| key.value.as_str()),
+                columns,
+                self.max_array_len,
+            ));
+        }
         let mut chars = remainder.chars().peekable();
         while matches!(chars.peek(), Some(' ')) {
             chars.next();
@@ -785,7 +1004,12 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
                 if chars.any(|ch| ch != ' ') {
                     return Err(Error::decode("invalid array delimiter"));
                 }
-                if !matches!(delimiter, ',' | '\t' | '|') {
+                let allowed = match delimiter {
+                    ',' | '\t' | '|' => true,
+                    ';' => self.semicolon_delimiter,
+                    _ => false,
+                };
+                if !allowed {
                     return Err(Error::decode("invalid array delimiter"));
                 }
                 delimiter
@@ -797,14 +1021,22 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
         if rest.starts_with('{') {
             let end = rest
                 .find('}')
-                .ok_or_else(|| Error::decode("unterminated field list"))?;
+                .ok_or_else(|| Error::decode_incomplete("unterminated field list"))?;
             let field_segment = &rest[1..end];
             let mut parsed_fields = Vec::new();
             for token in self.split_delimited(field_segment, delimiter)? {
                 if token.is_empty() {
                     return Err(Error::decode("empty field name"));
                 }
-                parsed_fields.push(self.parse_key_token(token)?);
+                let field = self.parse_key_token(token)?;
+                if self.strict
+                    && parsed_fields
+                        .iter()
+                        .any(|f: &KeyToken| f.value == field.value)
+                {
+                    return Err(duplicate_key(&field.value));
+                }
+                parsed_fields.push(field);
             }
             fields = Some(parsed_fields);
             rest = rest[end + 1..].trim_start();
@@ -822,10 +1054,17 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
         } else {
             Some(inline)
         };
+        if columns.is_some() && (fields.is_some() || inline.is_some()) {
+            return Err(Error::decode(
+                "matrix header cannot have a field list or inline values",
+            ));
+        }
 
         Ok(Some(HeaderLine {
+            raw: &line[bracket_start..],
             key,
             len,
+            columns,
             delimiter,
             fields,
             inline,
@@ -916,6 +1155,10 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
                         idx = pos + 1;
                         continue;
                     }
+                    if self.delimiter_escape && is_escaped_delimiter(bytes, pos) {
+                        idx = pos + 1;
+                        continue;
+                    }
                     let token = trim_ascii(&input[start..pos]);
                     tokens.push(token);
                     start = pos + 1;
@@ -928,7 +1171,7 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
         }
 
         if in_quotes {
-            return Err(Error::decode("unterminated string"));
+            return Err(Error::decode_incomplete("unterminated string"));
         }
 
         if start < bytes.len() || input.ends_with(delimiter) {
@@ -938,10 +1181,12 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
         Ok(())
     }
 
+    /// `row` is the 1-based row number reported for an unterminated quoted cell.
     fn split_tabular_row_into<'c>(
         &self,
         input: &'c str,
         delimiter: char,
+        row: usize,
         tokens: &mut TokenBuf<'c>,
     ) -> Result<bool> {
         tokens.clear();
@@ -1019,6 +1264,9 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
                             }
                             idx = pos + 1;
                         }
+                        _ if self.delimiter_escape && is_escaped_delimiter(bytes, pos) => {
+                            idx = pos + 1;
+                        }
                         _ => {
                             let token = trim_ascii(&input[start..pos]);
                             tokens.push(token);
@@ -1033,7 +1281,7 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
         }
 
         if in_quotes {
-            return Err(Error::decode("unterminated string"));
+            return Err(unterminated_tabular_cell(row, tokens.len() + 1));
         }
         if colon_before_delim {
             return Ok(false);
@@ -1067,6 +1315,17 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
         }
     }
 
+    fn check_array_len(&self, header: &HeaderLine<'a>, found: usize) -> Result<()> {
+        if found > self.max_array_len {
+            let key = header.key.as_ref().map(|key| key.value.as_str());
+            return Err(array_too_long(key, found, self.max_array_len));
+        }
+        if self.strict && found != header.len {
+            return Err(array_length_mismatch(header.len, found));
+        }
+        Ok(())
+    }
+
     fn span_for(&self, slice: &str) -> Span {
         let base = self.arena.input.as_ptr() as usize;
         let start = slice.as_ptr() as usize - base;
@@ -1076,13 +1335,49 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
         }
     }
 
-    fn intern_key(&mut self, key: &SmolStr) -> usize {
-        if let Some(&id) = self.key_lookup.get(key.as_str()) {
+    /// Remembers `head` through the last non-blank line in `lines` as the raw
+    /// source of `node`. Nodes are recorded in creation order, so the table
+    /// stays sorted for [`ArenaView::raw_text`].
+    fn record_raw(
+        &mut self,
+        node: usize,
+        head: &str,
+        scan: &ScanResult,
+        lines: std::ops::Range<usize>,
+        dedent: usize,
+    ) {
+        let head = self.span_for(head);
+        let end = scan.lines[lines]
+            .iter()
+            .rev()
+            .find(|line| !line.is_blank)
+            .map_or(head.end, |line| line.end.max(head.end));
+        self.arena.raw_spans.push(RawSpan {
+            node,
+            span: Span {
+                start: head.start,
+                end,
+            },
+            dedent,
+        });
+    }
+
+    fn push_key(&mut self, key: &SmolStr) -> usize {
+        let Some(threshold) = self.intern_threshold else {
+            let id = self.arena.keys.len();
+            self.arena.keys.push(key.clone());
+            return id;
+        };
+        let count = self.key_counts.entry(key.clone()).or_default();
+        count.seen += 1;
+        if let Some(id) = count.id {
             return id;
         }
         let id = self.arena.keys.len();
         self.arena.keys.push(key.clone());
-        self.key_lookup.insert(key.clone(), id);
+        if count.seen > threshold {
+            count.id = Some(id);
+        }
         id
     }
 
@@ -1106,6 +1401,45 @@ impl<'a, 'b> ArenaParser<'a, 'b> {
         node_index
     }
 
+    // A repeated key replaces the earlier value in place, or is an error in
+    // strict mode. Keys are matched by text since each occurrence gets its own
+    // entry in `keys`; `pair_index` is only built once an object outgrows a
+    // linear scan.
+    fn insert_pair(
+        &mut self,
+        pairs: &mut Vec<Pair>,
+        pair_index: &mut HashMap<SmolStr, usize>,
+        key: &SmolStr,
+        value: usize,
+    ) -> Result<()> {
+        let existing = if pairs.len() < PAIR_SCAN_LEN {
+            pairs
+                .iter()
+                .position(|pair| self.arena.keys[pair.key] == *key)
+        } else {
+            if pair_index.is_empty() {
+                for (idx, pair) in pairs.iter().enumerate() {
+                    pair_index.insert(self.arena.keys[pair.key].clone(), idx);
+                }
+            }
+            pair_index.get(key).copied()
+        };
+        if let Some(idx) = existing {
+            if self.strict {
+                return Err(duplicate_key(key));
+            }
+            pairs[idx].value = value;
+            return Ok(());
+        }
+        let idx = pairs.len();
+        let key_id = self.push_key(key);
+        pairs.push(Pair { key: key_id, value });
+        if !pair_index.is_empty() {
+            pair_index.insert(key.clone(), idx);
+        }
+        Ok(())
+    }
+
     fn push_object(&mut self, pairs: &[Pair]) -> usize {
         let node_index = self.push_node(NodeKind::Object, NodeData::None);
         let start = self.arena.pairs.len();
@@ -1156,8 +1490,12 @@ struct KeyToken {
 }
 
 struct HeaderLine<'a> {
+    /// The header from its `[` to the end of the line.
+    raw: &'a str,
     key: Option<KeyToken>,
     len: usize,
+    /// Row width of a `[RxC]` matrix header.
+    columns: Option<usize>,
     delimiter: char,
     fields: Option<Vec<KeyToken>>,
     inline: Option<&'a str>,
@@ -1169,36 +1507,21 @@ struct ParsedArray {
     deindent_next: bool,
 }
 
-fn insert_pair(
-    pairs: &mut Vec<Pair>,
-    pair_index: &mut HashMap<usize, usize>,
-    key: usize,
-    value: usize,
-) {
-    if let Some(&idx) = pair_index.get(&key) {
-        pairs[idx].value = value;
-        return;
-    }
-    let idx = pairs.len();
-    pairs.push(Pair { key, value });
-    pair_index.insert(key, idx);
-}
-
-fn build_field_slots(field_key_ids: &[usize]) -> (Vec<usize>, Vec<usize>) {
-    let mut unique_keys = Vec::new();
-    let mut field_slots = Vec::with_capacity(field_key_ids.len());
-    let mut slot_index: HashMap<usize, usize> = HashMap::new();
-    for &key_id in field_key_ids {
-        let slot = match slot_index.get(&key_id) {
+fn build_field_slots(fields: &[KeyToken]) -> (Vec<&KeyToken>, Vec<usize>) {
+    let mut unique_fields = Vec::new();
+    let mut field_slots = Vec::with_capacity(fields.len());
+    let mut slot_index: HashMap<&str, usize> = HashMap::new();
+    for field in fields {
+        let slot = match slot_index.get(field.value.as_str()) {
             Some(&slot) => slot,
             None => {
-                let slot = unique_keys.len();
-                unique_keys.push(key_id);
-                slot_index.insert(key_id, slot);
+                let slot = unique_fields.len();
+                unique_fields.push(field);
+                slot_index.insert(field.value.as_str(), slot);
                 slot
             }
         };
         field_slots.push(slot);
     }
-    (unique_keys, field_slots)
+    (unique_fields, field_slots)
 }
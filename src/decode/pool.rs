@@ -1,4 +1,9 @@
 use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use serde::de::{self, Deserializer, Visitor};
 
 use crate::arena::ArenaParts;
 
@@ -28,3 +33,71 @@ pub fn take_arena_parts() -> ArenaParts {
 pub fn put_arena_parts(parts: ArenaParts) {
     ARENA_POOL.with(|pool| pool.borrow_mut().put(parts));
 }
+
+/// Strings handed out by [`shared_str`] during a decode with
+/// [`crate::DecodeOptions::with_string_dedup`]. The values come from the
+/// input, so the set keeps std's randomly seeded hasher.
+pub type StringPool = Arc<Mutex<HashSet<Arc<str>>>>;
+
+thread_local! {
+    static STRING_POOL: RefCell<Option<StringPool>> = const { RefCell::new(None) };
+}
+
+/// Restores the previous pool of this thread when dropped.
+pub struct StringPoolGuard {
+    previous: Option<StringPool>,
+}
+
+impl Drop for StringPoolGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        STRING_POOL.with(|slot| *slot.borrow_mut() = previous);
+    }
+}
+
+/// Makes [`shared_str`] on this thread draw from `pool` until the guard drops.
+pub fn install_string_pool(pool: Option<&StringPool>) -> Option<StringPoolGuard> {
+    let pool = pool?.clone();
+    let previous = STRING_POOL.with(|slot| slot.replace(Some(pool)));
+    Some(StringPoolGuard { previous })
+}
+
+/// Deserializes a string as `Arc<str>`, for use as
+/// `#[serde(deserialize_with = "serde_toon::shared_str")]`. Inside a decode
+/// with [`crate::DecodeOptions::with_string_dedup`], equal strings come back
+/// as clones of one `Arc`; otherwise each value gets its own allocation.
+pub fn shared_str<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Arc<str>, D::Error> {
+    deserializer.deserialize_str(SharedStrVisitor)
+}
+
+struct SharedStrVisitor;
+
+impl Visitor<'_> for SharedStrVisitor {
+    type Value = Arc<str>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> std::result::Result<Arc<str>, E> {
+        Ok(share(value))
+    }
+}
+
+fn share(value: &str) -> Arc<str> {
+    STRING_POOL.with(|slot| {
+        let slot = slot.borrow();
+        let Some(pool) = slot.as_ref() else {
+            return Arc::from(value);
+        };
+        let mut pool = pool.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(shared) = pool.get(value) {
+            return Arc::clone(shared);
+        }
+        let shared: Arc<str> = Arc::from(value);
+        pool.insert(Arc::clone(&shared));
+        shared
+    })
+}
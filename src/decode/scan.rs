@@ -2,6 +2,8 @@ use memchr::memchr_iter;
 
 use crate::{Error, Result};
 
+use super::CommentScope;
+
 #[derive(Clone, Copy, Debug)]
 pub struct ScanLine {
     pub indent: usize,
@@ -17,20 +19,35 @@ pub struct ScanResult {
     pub non_blank: usize,
 }
 
-pub fn scan_lines(input: &str, indent_size: usize, strict: bool) -> Result<ScanResult> {
+pub fn scan_lines(
+    input: &str,
+    indent_size: usize,
+    indent_tabs: bool,
+    strict: bool,
+    allow_comments: bool,
+) -> Result<ScanResult> {
     if indent_size == 0 {
         return Err(Error::decode("indent size must be greater than zero"));
     }
     let bytes = input.as_bytes();
     let mut lines = Vec::new();
     let mut non_blank = 0;
+    let mut comments = CommentScope::default();
     let mut start = 0;
     for idx in memchr_iter(b'\n', bytes) {
         let mut end = idx;
         if end > start && bytes[end - 1] == b'\r' {
             end -= 1;
         }
-        let line = build_line(bytes, start, end, indent_size, strict)?;
+        let line = build_line(
+            bytes,
+            start,
+            end,
+            indent_size,
+            indent_tabs,
+            strict,
+            allow_comments && comments.is_comment(&input[start..end]),
+        )?;
         if !line.is_blank {
             non_blank += 1;
         }
@@ -42,7 +59,15 @@ pub fn scan_lines(input: &str, indent_size: usize, strict: bool) -> Result<ScanR
     if end > start && bytes[end - 1] == b'\r' {
         end -= 1;
     }
-    let line = build_line(bytes, start, end, indent_size, strict)?;
+    let line = build_line(
+        bytes,
+        start,
+        end,
+        indent_size,
+        indent_tabs,
+        strict,
+        allow_comments && comments.is_comment(&input[start..end]),
+    )?;
     if !line.is_blank {
         non_blank += 1;
     }
@@ -56,7 +81,9 @@ fn build_line(
     start: usize,
     end: usize,
     indent_size: usize,
+    indent_tabs: bool,
     strict: bool,
+    is_comment: bool,
 ) -> Result<ScanLine> {
     if start >= end {
         return Ok(ScanLine {
@@ -67,14 +94,9 @@ fn build_line(
             is_blank: true,
         });
     }
-    let mut only_whitespace = true;
-    for &byte in &bytes[start..end] {
-        if !byte.is_ascii_whitespace() {
-            only_whitespace = false;
-            break;
-        }
-    }
-    if only_whitespace {
+    let line = &bytes[start..end];
+    let only_whitespace = line.iter().all(u8::is_ascii_whitespace);
+    if only_whitespace || is_comment {
         return Ok(ScanLine {
             indent: 0,
             level: 0,
@@ -88,11 +110,14 @@ fn build_line(
     for &byte in &bytes[start..end] {
         match byte {
             b' ' => {
+                if strict && indent_tabs {
+                    return Err(Error::decode("spaces not allowed in tab indentation"));
+                }
                 indent_columns += 1;
                 indent_chars += 1;
             }
             b'\t' => {
-                if strict {
+                if strict && !indent_tabs {
                     return Err(Error::decode("tabs not allowed in indentation"));
                 }
                 indent_columns = indent_columns.saturating_add(indent_size);
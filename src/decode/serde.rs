@@ -3,15 +3,30 @@ use std::fmt;
 use serde::de::{self, Error as _, IntoDeserializer, MapAccess, SeqAccess, Visitor};
 
 use crate::arena::{ArenaView, NodeData, NodeKind};
+use crate::text::base64;
 
-pub struct ArenaDeserializer<'a> {
-    arena: &'a ArenaView<'a>,
+/// Deserializes one arena node. `'de` is the input's lifetime, so strings
+/// that are plain spans of the input are handed out with `visit_borrowed_str`.
+pub struct ArenaDeserializer<'a, 'de> {
+    arena: &'a ArenaView<'de>,
     node_index: usize,
+    bytes_from_base64: bool,
 }
 
-impl<'a> ArenaDeserializer<'a> {
-    pub fn new(arena: &'a ArenaView<'a>, node_index: usize) -> Self {
-        Self { arena, node_index }
+impl<'a, 'de> ArenaDeserializer<'a, 'de> {
+    pub fn new(arena: &'a ArenaView<'de>, node_index: usize) -> Self {
+        Self {
+            arena,
+            node_index,
+            bytes_from_base64: false,
+        }
+    }
+
+    /// Reads strings requested as bytes as base64; see
+    /// [`crate::DecodeOptions::with_bytes_from_base64`].
+    pub fn with_bytes_from_base64(mut self, enabled: bool) -> Self {
+        self.bytes_from_base64 = enabled;
+        self
     }
 
     fn node(&self) -> &crate::arena::Node {
@@ -40,7 +55,7 @@ impl de::Error for ArenaDeError {
     }
 }
 
-impl<'de> de::Deserializer<'de> for &mut ArenaDeserializer<'de> {
+impl<'de> de::Deserializer<'de> for &mut ArenaDeserializer<'_, 'de> {
     type Error = ArenaDeError;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -55,11 +70,7 @@ impl<'de> de::Deserializer<'de> for &mut ArenaDeserializer<'de> {
                 _ => Err(Self::Error::custom("invalid bool payload")),
             },
             NodeKind::String => match node.data {
-                NodeData::String(index) => self
-                    .arena
-                    .get_str(index)
-                    .map(|s| visitor.visit_str(s))
-                    .unwrap_or_else(|| Err(Self::Error::custom("invalid string span"))),
+                NodeData::String(index) => visit_string_node(self.arena, index, visitor),
                 _ => Err(Self::Error::custom("invalid string payload")),
             },
             NodeKind::Number => {
@@ -82,11 +93,21 @@ impl<'de> de::Deserializer<'de> for &mut ArenaDeserializer<'de> {
                 visitor.visit_f64(value)
             }
             NodeKind::Array => {
-                let iter = ArrayAccess::new(self.arena, node.first_child, node.child_len);
+                let iter = ArrayAccess::new(
+                    self.arena,
+                    node.first_child,
+                    node.child_len,
+                    self.bytes_from_base64,
+                );
                 visitor.visit_seq(iter)
             }
             NodeKind::Object => {
-                let iter = ObjectAccess::new(self.arena, node.first_child, node.child_len);
+                let iter = ObjectAccess::new(
+                    self.arena,
+                    node.first_child,
+                    node.child_len,
+                    self.bytes_from_base64,
+                );
                 visitor.visit_map(iter)
             }
         }
@@ -223,11 +244,7 @@ impl<'de> de::Deserializer<'de> for &mut ArenaDeserializer<'de> {
             return Err(Self::Error::custom("expected string"));
         }
         match node.data {
-            NodeData::String(index) => self
-                .arena
-                .get_str(index)
-                .map(|s| visitor.visit_str(s))
-                .unwrap_or_else(|| Err(Self::Error::custom("invalid string span"))),
+            NodeData::String(index) => visit_string_node(self.arena, index, visitor),
             _ => Err(Self::Error::custom("invalid string payload")),
         }
     }
@@ -258,12 +275,18 @@ impl<'de> de::Deserializer<'de> for &mut ArenaDeserializer<'de> {
         if node.kind != NodeKind::String {
             return Err(Self::Error::custom("expected string"));
         }
+        if self.bytes_from_base64 {
+            return self.deserialize_byte_buf(visitor);
+        }
         match node.data {
-            NodeData::String(index) => self
-                .arena
-                .get_str(index)
-                .map(|s| visitor.visit_bytes(s.as_bytes()))
-                .unwrap_or_else(|| Err(Self::Error::custom("invalid string span"))),
+            NodeData::String(index) => match self.arena.get_str_span(index) {
+                Some(s) => visitor.visit_borrowed_bytes(s.as_bytes()),
+                None => self
+                    .arena
+                    .get_str(index)
+                    .map(|s| visitor.visit_bytes(s.as_bytes()))
+                    .unwrap_or_else(|| Err(Self::Error::custom("invalid string span"))),
+            },
             _ => Err(Self::Error::custom("invalid string payload")),
         }
     }
@@ -277,11 +300,18 @@ impl<'de> de::Deserializer<'de> for &mut ArenaDeserializer<'de> {
             return Err(Self::Error::custom("expected string"));
         }
         match node.data {
-            NodeData::String(index) => self
-                .arena
-                .get_str(index)
-                .map(|s| visitor.visit_byte_buf(s.as_bytes().to_vec()))
-                .unwrap_or_else(|| Err(Self::Error::custom("invalid string span"))),
+            NodeData::String(index) => {
+                let s = self
+                    .arena
+                    .get_str(index)
+                    .ok_or_else(|| Self::Error::custom("invalid string span"))?;
+                if !self.bytes_from_base64 {
+                    return visitor.visit_byte_buf(s.as_bytes().to_vec());
+                }
+                let bytes = base64::decode(s)
+                    .ok_or_else(|| Self::Error::custom("invalid base64 string"))?;
+                visitor.visit_byte_buf(bytes)
+            }
             _ => Err(Self::Error::custom("invalid string payload")),
         }
     }
@@ -327,7 +357,12 @@ impl<'de> de::Deserializer<'de> for &mut ArenaDeserializer<'de> {
         if node.kind != NodeKind::Array {
             return Err(Self::Error::custom("expected array"));
         }
-        let iter = ArrayAccess::new(self.arena, node.first_child, node.child_len);
+        let iter = ArrayAccess::new(
+            self.arena,
+            node.first_child,
+            node.child_len,
+            self.bytes_from_base64,
+        );
         visitor.visit_seq(iter)
     }
 
@@ -339,7 +374,12 @@ impl<'de> de::Deserializer<'de> for &mut ArenaDeserializer<'de> {
         if node.kind != NodeKind::Object {
             return Err(Self::Error::custom("expected object"));
         }
-        let iter = ObjectAccess::new(self.arena, node.first_child, node.child_len);
+        let iter = ObjectAccess::new(
+            self.arena,
+            node.first_child,
+            node.child_len,
+            self.bytes_from_base64,
+        );
         visitor.visit_map(iter)
     }
 
@@ -354,7 +394,12 @@ impl<'de> de::Deserializer<'de> for &mut ArenaDeserializer<'de> {
         if node.child_len != len {
             return Err(Self::Error::custom("tuple length mismatch"));
         }
-        let iter = ArrayAccess::new(self.arena, node.first_child, node.child_len);
+        let iter = ArrayAccess::new(
+            self.arena,
+            node.first_child,
+            node.child_len,
+            self.bytes_from_base64,
+        );
         visitor.visit_seq(iter)
     }
 
@@ -384,13 +429,19 @@ impl<'de> de::Deserializer<'de> for &mut ArenaDeserializer<'de> {
 
     fn deserialize_newtype_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_newtype_struct(self)
+        if name != crate::raw::TOKEN {
+            return visitor.visit_newtype_struct(self);
+        }
+        match self.arena.raw_text(self.node_index) {
+            Some(text) => visitor.visit_string(text),
+            None => visitor.visit_newtype_struct(self),
+        }
     }
 
     fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -405,28 +456,27 @@ impl<'de> de::Deserializer<'de> for &mut ArenaDeserializer<'de> {
     }
 }
 
-struct ArrayAccess<'a> {
-    arena: &'a ArenaView<'a>,
+struct ArrayAccess<'a, 'de> {
+    arena: &'a ArenaView<'de>,
     start: usize,
     len: usize,
     index: usize,
+    bytes_from_base64: bool,
 }
 
-impl<'a> ArrayAccess<'a> {
-    fn new(arena: &'a ArenaView<'a>, start: usize, len: usize) -> Self {
+impl<'a, 'de> ArrayAccess<'a, 'de> {
+    fn new(arena: &'a ArenaView<'de>, start: usize, len: usize, bytes_from_base64: bool) -> Self {
         Self {
             arena,
             start,
             len,
             index: 0,
+            bytes_from_base64,
         }
     }
 }
 
-impl<'de, 'a> SeqAccess<'de> for ArrayAccess<'a>
-where
-    'a: 'de,
-{
+impl<'de> SeqAccess<'de> for ArrayAccess<'_, 'de> {
     type Error = ArenaDeError;
 
     fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
@@ -443,33 +493,33 @@ where
             .copied()
             .ok_or_else(|| ArenaDeError::custom("invalid array index"))?;
         self.index += 1;
-        let mut de = ArenaDeserializer::new(self.arena, node_index);
+        let mut de = ArenaDeserializer::new(self.arena, node_index)
+            .with_bytes_from_base64(self.bytes_from_base64);
         seed.deserialize(&mut de).map(Some)
     }
 }
 
-struct ObjectAccess<'a> {
-    arena: &'a ArenaView<'a>,
+struct ObjectAccess<'a, 'de> {
+    arena: &'a ArenaView<'de>,
     start: usize,
     len: usize,
     index: usize,
+    bytes_from_base64: bool,
 }
 
-impl<'a> ObjectAccess<'a> {
-    fn new(arena: &'a ArenaView<'a>, start: usize, len: usize) -> Self {
+impl<'a, 'de> ObjectAccess<'a, 'de> {
+    fn new(arena: &'a ArenaView<'de>, start: usize, len: usize, bytes_from_base64: bool) -> Self {
         Self {
             arena,
             start,
             len,
             index: 0,
+            bytes_from_base64,
         }
     }
 }
 
-impl<'de, 'a> MapAccess<'de> for ObjectAccess<'a>
-where
-    'a: 'de,
-{
+impl<'de> MapAccess<'de> for ObjectAccess<'_, 'de> {
     type Error = ArenaDeError;
 
     fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
@@ -501,15 +551,33 @@ where
             .get(self.start + self.index)
             .ok_or_else(|| ArenaDeError::custom("invalid object index"))?;
         self.index += 1;
-        let mut de = ArenaDeserializer::new(self.arena, pair.value);
+        let mut de = ArenaDeserializer::new(self.arena, pair.value)
+            .with_bytes_from_base64(self.bytes_from_base64);
         seed.deserialize(&mut de)
     }
 }
 
-fn parse_number_str<'a>(
-    arena: &'a ArenaView<'a>,
+fn visit_string_node<'de, V>(
+    arena: &ArenaView<'de>,
+    index: usize,
+    visitor: V,
+) -> Result<V::Value, ArenaDeError>
+where
+    V: Visitor<'de>,
+{
+    match arena.get_str_span(index) {
+        Some(s) => visitor.visit_borrowed_str(s),
+        None => arena
+            .get_str(index)
+            .map(|s| visitor.visit_str(s))
+            .unwrap_or_else(|| Err(ArenaDeError::custom("invalid string span"))),
+    }
+}
+
+fn parse_number_str<'de>(
+    arena: &ArenaView<'de>,
     node: &crate::arena::Node,
-) -> Result<&'a str, ArenaDeError> {
+) -> Result<&'de str, ArenaDeError> {
     match node.data {
         NodeData::Number(index) => arena
             .get_num_str(index)
@@ -0,0 +1,748 @@
+//! Line-at-a-time validation for [`crate::validate_reader`].
+//!
+//! The tree decoder walks a fully collected `Vec<Line>` with one recursive
+//! function per block kind. This mirrors those functions with an explicit stack
+//! of open blocks and reuses the decoder's token helpers, so it accepts the same
+//! documents while holding only the current line, a short lookahead and one
+//! frame per nesting level.
+
+use std::collections::{HashSet, VecDeque};
+use std::io::BufRead;
+
+use crate::checksum::{verify_checksum, Crc32, CHECKSUM_PREFIX};
+use crate::error::Location;
+use crate::{DecodeOptions, Error, Result};
+
+use smol_str::SmolStr;
+
+use super::diagnostic::{Severity, ToonDiagnostic};
+use super::{
+    check_matrix_row, duplicate_key, indent_width, reject_json_prefix, trim_ascii, CommentScope,
+    Decoder, HeaderLine, KeyToken, Line, TokenBuf,
+};
+
+pub(super) fn validate_reader<R: BufRead>(reader: R, options: &DecodeOptions) -> Result<()> {
+    Validator::new(reader, options).run()
+}
+
+/// Like [`validate_reader`], but records each error and moves on to the next
+/// line. Read failures and the checksum still end the run.
+pub(super) fn validate_collect<R: BufRead>(
+    reader: R,
+    options: &DecodeOptions,
+) -> Vec<ToonDiagnostic> {
+    let mut validator = Validator::new(reader, options);
+    validator.collected = Some(Vec::new());
+    // Misaligned lines are then reported by `handle_line` and read at an
+    // inferred level instead of failing in `build_line`.
+    validator.decoder.indent_warnings = true;
+    let result = validator.run_collecting();
+    let mut collected = validator.collected.take().unwrap_or_default();
+    if let Err(err) = result {
+        collected.push(ToonDiagnostic::from_error(&err, Location::default()));
+    }
+    collected
+}
+
+/// One `\n`-separated piece of the raw input, with its 1-based line number and
+/// byte offset.
+struct Segment {
+    text: String,
+    line: usize,
+    offset: usize,
+}
+
+enum Frame {
+    /// `parse_object_block`: fields at `level`. `keys` holds the literal keys
+    /// seen so far, for the strict duplicate check.
+    Object {
+        level: usize,
+        override_level: Option<usize>,
+        keys: HashSet<SmolStr>,
+    },
+    /// `parse_list_block`: `- ` items at `item_level`.
+    List {
+        item_level: usize,
+        header: HeaderLine,
+        count: usize,
+        at: Location,
+    },
+    /// `parse_tabular_block`: rows deeper than `base_level`. `deindent` marks
+    /// arrays whose parent object reads a following non-row line as a sibling.
+    Tabular {
+        base_level: usize,
+        row_level: Option<usize>,
+        header: HeaderLine,
+        count: usize,
+        deindent: bool,
+        at: Location,
+    },
+    /// `parse_matrix_block`: `columns`-wide rows at `row_level`.
+    Matrix {
+        row_level: usize,
+        columns: usize,
+        header: HeaderLine,
+        count: usize,
+        at: Location,
+    },
+    /// A root array has ended; only blank lines may follow.
+    Done,
+}
+
+enum Root {
+    Empty,
+    /// The first non-blank line, held until we know whether it is the only one.
+    First(Line, Location),
+    Started,
+}
+
+struct Validator<'o, R> {
+    reader: R,
+    options: &'o DecodeOptions,
+    decoder: Decoder,
+    lookahead: VecDeque<Segment>,
+    eof: bool,
+    next_line: usize,
+    next_offset: usize,
+    crc: Option<Crc32>,
+    crc_started: bool,
+    json_prefix: [Option<u8>; 2],
+    kept: usize,
+    last_kept: Option<(bool, Location)>,
+    /// Start of the empty segment after a trailing checksum line.
+    checksum_newline: Option<Location>,
+    root: Root,
+    frames: Vec<Frame>,
+    pending_blank: Option<Location>,
+    previous: Option<(usize, usize)>,
+    /// Set by [`validate_collect`]: errors are pushed here instead of returned.
+    collected: Option<Vec<ToonDiagnostic>>,
+    /// Classifies the lines `capture_comments` drops in `next_segment`.
+    captured: CommentScope,
+    /// Classifies the lines left for `allow_comments` to skip.
+    comments: CommentScope,
+}
+
+impl<'o, R: BufRead> Validator<'o, R> {
+    fn new(reader: R, options: &'o DecodeOptions) -> Self {
+        Self {
+            reader,
+            options,
+            decoder: Decoder::new_validator(options),
+            lookahead: VecDeque::with_capacity(3),
+            eof: false,
+            next_line: 1,
+            next_offset: 0,
+            crc: options.verify_checksum.then(Crc32::new),
+            crc_started: false,
+            json_prefix: [None; 2],
+            kept: 0,
+            last_kept: None,
+            checksum_newline: None,
+            root: Root::Empty,
+            frames: Vec::new(),
+            pending_blank: None,
+            previous: None,
+            collected: None,
+            captured: CommentScope::default(),
+            comments: CommentScope::default(),
+        }
+    }
+
+    fn run(mut self) -> Result<()> {
+        self.run_collecting()
+    }
+
+    fn run_collecting(&mut self) -> Result<()> {
+        if self.decoder.indent_size == 0 {
+            return Err(Error::decode("indent size must be greater than zero"));
+        }
+        while let Some(segment) = self.next_segment()? {
+            self.kept += 1;
+            let start = Location {
+                offset: segment.offset,
+                line: segment.line,
+                column: 1,
+            };
+            self.last_kept = Some((segment.text.is_empty(), start));
+            let text = segment.text.strip_suffix('\r').unwrap_or(&segment.text);
+            let body = text.trim_end_matches([' ', '\t']);
+            if body.len() < text.len() {
+                let err = Error::decode("trailing whitespace not allowed")
+                    .with_location(column(start, body.len()));
+                self.tolerate(Err(err), start)?;
+            }
+            let comment = self.decoder.allow_comments && self.comments.is_comment(text);
+            let line = match self.decoder.build_line(text, comment) {
+                Ok(line) => line,
+                Err(err) => {
+                    let at = column(start, indent_width(text));
+                    self.tolerate(Err(err.with_location(at)), at)?;
+                    continue;
+                }
+            };
+            let at = column(start, text.len() - line.content.len());
+            let result = self.handle_line(line, at);
+            self.tolerate(result, at)?;
+        }
+        let trailing = match self.last_kept {
+            Some((true, at)) if self.kept > 1 => Some(at),
+            _ => self.checksum_newline,
+        };
+        if let Some(at) = trailing {
+            let err = Error::decode("trailing newline not allowed").with_location(at);
+            self.tolerate(Err(err), at)?;
+        }
+        let result = self.finish();
+        self.tolerate(result, Location::default())
+    }
+
+    /// When collecting, records the error in `result` (at `at` if it has no
+    /// location of its own) and carries on; otherwise returns it.
+    fn tolerate(&mut self, result: Result<()>, at: Location) -> Result<()> {
+        match (result, self.collected.as_mut()) {
+            (Err(err), Some(collected)) => {
+                collected.push(ToonDiagnostic::from_error(&err, at));
+                Ok(())
+            }
+            (result, _) => result,
+        }
+    }
+
+    /// Returns the next document line, after dropping a trailing checksum line
+    /// and, when comments are captured, comment lines.
+    fn next_segment(&mut self) -> Result<Option<Segment>> {
+        loop {
+            while self.lookahead.len() < 2 && !self.eof {
+                let segment = self.read_segment()?;
+                self.lookahead.push_back(segment);
+            }
+            let Some(segment) = self.lookahead.pop_front() else {
+                if self.crc.is_some() {
+                    return Err(Error::decode("missing checksum line"));
+                }
+                return Ok(None);
+            };
+            let last = match self.lookahead.front() {
+                None => true,
+                Some(next) => self.eof && self.lookahead.len() == 1 && next.text.is_empty(),
+            };
+            if last {
+                if let Some(hex) = segment.text.strip_prefix(CHECKSUM_PREFIX) {
+                    if let Some(crc) = &self.crc {
+                        let at = Location {
+                            offset: segment.offset,
+                            line: segment.line,
+                            column: 1,
+                        };
+                        verify_checksum(hex, crc.finish()).map_err(|err| err.with_location(at))?;
+                    }
+                    self.checksum_newline = self.lookahead.front().map(|next| Location {
+                        offset: next.offset,
+                        line: next.line,
+                        column: 1,
+                    });
+                    self.lookahead.clear();
+                    return Ok(None);
+                }
+            }
+            if let Some(crc) = &mut self.crc {
+                if self.crc_started {
+                    crc.update(b"\n");
+                }
+                crc.update(segment.text.as_bytes());
+                self.crc_started = true;
+            }
+            if segment.line == 1 {
+                let mut bytes = segment.text.trim_start_matches(' ').bytes();
+                self.json_prefix = [bytes.next(), bytes.next()];
+            }
+            if self.options.capture_comments && self.captured.is_comment(&segment.text) {
+                continue;
+            }
+            return Ok(Some(segment));
+        }
+    }
+
+    fn read_segment(&mut self) -> Result<Segment> {
+        let mut bytes = Vec::new();
+        let read = self
+            .reader
+            .read_until(b'\n', &mut bytes)
+            .map_err(|err| Error::decode_with_source(format!("read failed: {err}"), err))?;
+        if bytes.last() == Some(&b'\n') {
+            bytes.pop();
+        } else {
+            self.eof = true;
+        }
+        let text = String::from_utf8(bytes)
+            .map_err(|err| Error::decode_with_source(format!("read failed: {err}"), err))?;
+        let segment = Segment {
+            text,
+            line: self.next_line,
+            offset: self.next_offset,
+        };
+        self.next_line += 1;
+        self.next_offset += read;
+        Ok(segment)
+    }
+
+    fn handle_line(&mut self, mut line: Line, at: Location) -> Result<()> {
+        if line.is_blank {
+            if !matches!(self.root, Root::Empty) && self.pending_blank.is_none() {
+                self.pending_blank = Some(at);
+            }
+            return Ok(());
+        }
+        let indent_size = self.decoder.indent_size;
+        let infer =
+            self.options.indent_warnings || (self.collected.is_some() && self.decoder.strict);
+        if infer && !line.indent.is_multiple_of(indent_size) {
+            line.level = self.decoder.inferred_level(line.indent, self.previous);
+            if let Some(collected) = self.collected.as_mut() {
+                let (severity, message) = if self.options.indent_warnings {
+                    let message = format!(
+                        "indent of {} spaces is not a multiple of the indent size; read as level {}",
+                        line.indent, line.level
+                    );
+                    (Severity::Warning, message)
+                } else {
+                    (Severity::Error, "invalid indentation".to_string())
+                };
+                collected.push(ToonDiagnostic {
+                    line: at.line,
+                    column: at.column,
+                    message,
+                    severity,
+                });
+            }
+        }
+        self.previous = Some((line.indent, line.level));
+
+        match std::mem::replace(&mut self.root, Root::Started) {
+            Root::Empty => {
+                let content = trim_ascii(&line.content);
+                if content.starts_with('[') {
+                    self.reject_json(at)?;
+                    let header = self
+                        .decoder
+                        .parse_array_header(content)
+                        .map_err(|err| err.with_location(at))?;
+                    if let Some(header) = header.filter(|header| header.key.is_none()) {
+                        if line.indent != 0 {
+                            return Err(Error::decode("unexpected indentation").with_location(at));
+                        }
+                        self.frames.push(Frame::Done);
+                        return self.start_array(header, 0, false, at);
+                    }
+                }
+                self.root = Root::First(line, at);
+                Ok(())
+            }
+            Root::First(first, first_at) => {
+                self.reject_json(first_at)?;
+                self.frames.push(Frame::Object {
+                    level: 0,
+                    override_level: None,
+                    keys: HashSet::new(),
+                });
+                let result = self.dispatch(&first, first_at);
+                self.tolerate(result, first_at)?;
+                self.flush_blank(Some(line.level))?;
+                self.dispatch(&line, at)
+            }
+            Root::Started => {
+                self.flush_blank(Some(line.level))?;
+                self.dispatch(&line, at)
+            }
+        }
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        match std::mem::replace(&mut self.root, Root::Started) {
+            Root::Empty => return Ok(()),
+            Root::First(line, at) => {
+                self.reject_json(at)?;
+                if line.indent == 0 {
+                    let content = trim_ascii(&line.content);
+                    if self.decoder.reject_root_unquoted_string(content) {
+                        return Err(Error::decode("root string must be quoted").with_location(at));
+                    }
+                    return self
+                        .decoder
+                        .decode_single_line(content)
+                        .map(drop)
+                        .map_err(|err| err.with_location(at));
+                }
+                if self.decoder.strict {
+                    return Err(Error::decode("unexpected indentation").with_location(at));
+                }
+                self.frames.push(Frame::Object {
+                    level: 0,
+                    override_level: None,
+                    keys: HashSet::new(),
+                });
+                self.dispatch(&line, at)?;
+            }
+            Root::Started => {}
+        }
+        while !self.frames.is_empty() {
+            self.close_top(false)?;
+        }
+        Ok(())
+    }
+
+    fn reject_json(&self, at: Location) -> Result<()> {
+        let [first, second] = self.json_prefix;
+        reject_json_prefix(first, second).map_err(|err| err.with_location(at))
+    }
+
+    /// Applies blank lines seen before a line at `next_level` (`None` at the end
+    /// of input): open arrays end there or reject them, as `parse_list_block`
+    /// and `parse_tabular_block` do after peeking past the blanks.
+    fn flush_blank(&mut self, next_level: Option<usize>) -> Result<()> {
+        let Some(at) = self.pending_blank.take() else {
+            return Ok(());
+        };
+        loop {
+            let ends = match self.frames.last() {
+                Some(Frame::List { item_level, .. }) => {
+                    next_level.is_none_or(|level| level < *item_level)
+                }
+                Some(Frame::Tabular { base_level, .. }) => {
+                    next_level.is_none_or(|level| level <= *base_level)
+                }
+                Some(Frame::Matrix { row_level, .. }) => {
+                    next_level.is_none_or(|level| level < *row_level)
+                }
+                _ => return Ok(()),
+            };
+            if !self.decoder.strict {
+                return Ok(());
+            }
+            if !ends {
+                return Err(Error::decode("blank line not allowed in array").with_location(at));
+            }
+            self.close_top(false)?;
+        }
+    }
+
+    fn dispatch(&mut self, line: &Line, at: Location) -> Result<()> {
+        let open = self.frames.len();
+        self.dispatch_inner(line, at)
+            .and_then(|()| match self.frames.len() > open {
+                true => self.check_depth(),
+                false => Ok(()),
+            })
+            .map_err(|err| match err.location {
+                Some(_) => err,
+                None => err.with_location(at),
+            })
+    }
+
+    fn dispatch_inner(&mut self, line: &Line, at: Location) -> Result<()> {
+        let content = trim_ascii(&line.content);
+        loop {
+            match self.frames.last_mut() {
+                None => return Ok(()),
+                Some(Frame::Done) => return Err(Error::decode("unexpected trailing content")),
+                Some(Frame::Object {
+                    level: base,
+                    override_level,
+                    ..
+                }) => {
+                    let base = *base;
+                    let level = override_level.take().unwrap_or(line.level);
+                    if level < base {
+                        self.frames.pop();
+                        continue;
+                    }
+                    if level > base {
+                        return Err(Error::decode("unexpected indentation"));
+                    }
+                    return self.object_line(content, base, at);
+                }
+                Some(Frame::List {
+                    item_level, count, ..
+                }) => {
+                    let item_level = *item_level;
+                    if line.level < item_level {
+                        self.close_top(false)?;
+                        continue;
+                    }
+                    if line.level > item_level {
+                        return Err(Error::decode("unexpected indentation"));
+                    }
+                    if !content.starts_with('-') {
+                        return Err(Error::decode("expected list item"));
+                    }
+                    *count += 1;
+                    return self.list_item(content[1..].trim_start(), item_level, at);
+                }
+                Some(Frame::Matrix {
+                    row_level,
+                    columns,
+                    header,
+                    count,
+                    ..
+                }) => {
+                    if line.level < *row_level {
+                        self.close_top(false)?;
+                        continue;
+                    }
+                    if line.level > *row_level {
+                        return Err(Error::decode("unexpected indentation"));
+                    }
+                    *count += 1;
+                    let (row, columns) = (*count, *columns);
+                    let values =
+                        self.decoder
+                            .parse_inline_array(content, header.delimiter, columns)?;
+                    return check_matrix_row(row, values.len(), columns);
+                }
+                Some(Frame::Tabular {
+                    base_level,
+                    row_level,
+                    header,
+                    count,
+                    ..
+                }) => {
+                    let row_level = match *row_level {
+                        Some(row_level) => row_level,
+                        None if line.level <= *base_level => {
+                            self.close_top(false)?;
+                            continue;
+                        }
+                        None => *row_level.insert(line.level),
+                    };
+                    if line.level < row_level {
+                        self.close_top(false)?;
+                        continue;
+                    }
+                    if line.level > row_level {
+                        return Err(Error::decode("unexpected indentation"));
+                    }
+                    let mut row = content;
+                    if let Some(stripped) = row.strip_prefix('-') {
+                        if stripped.starts_with(' ') || stripped.starts_with('\t') {
+                            row = stripped.trim_start();
+                        }
+                    }
+                    let delimiter = header.delimiter;
+                    let fields = header.fields.as_ref().map_or(0, Vec::len);
+                    let row_number = *count + 1;
+                    let mut tokens = TokenBuf::new();
+                    let is_row = self.decoder.split_tabular_row_into(
+                        row,
+                        delimiter,
+                        row_number,
+                        &mut tokens,
+                    )?;
+                    if !is_row {
+                        self.close_top(true)?;
+                        continue;
+                    }
+                    if tokens.len() != fields && self.decoder.strict {
+                        return Err(Error::decode("tabular row field count mismatch"));
+                    }
+                    for token in tokens.iter().take(fields) {
+                        if !token.is_empty() {
+                            self.decoder.parse_tabular_cell(token, delimiter)?;
+                        }
+                    }
+                    if let Some(Frame::Tabular { count, .. }) = self.frames.last_mut() {
+                        *count += 1;
+                    }
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    fn object_line(&mut self, content: &str, level: usize, at: Location) -> Result<()> {
+        if let Some(header) = self.decoder.parse_array_header(content)? {
+            let Some(key) = header.key.as_ref() else {
+                return Err(Error::decode("array header missing key in object context"));
+            };
+            let noted = self.note_key(key);
+            self.tolerate(noted, at)?;
+            return self.start_array(header, level, true, at);
+        }
+        if let Some((key, value)) = self.decoder.split_key_value(content)? {
+            let key = self.decoder.parse_key_token(trim_ascii(key))?;
+            let noted = self.note_key(&key);
+            self.tolerate(noted, at)?;
+            if trim_ascii(value).is_empty() {
+                self.frames.push(Frame::Object {
+                    level: level + 1,
+                    override_level: None,
+                    keys: HashSet::new(),
+                });
+            } else {
+                self.decoder.parse_field_value(value)?;
+            }
+            return Ok(());
+        }
+        if self.decoder.strict {
+            return Err(Error::decode("bare key not allowed in strict mode"));
+        }
+        let key = self.decoder.parse_key_token(content)?;
+        self.note_key(&key)
+    }
+
+    /// Records `key` in the innermost object, rejecting repeats in strict mode
+    /// like `insert_key_value`. Keys that expand into paths merge instead.
+    /// Callers still open the block a repeated key starts, so
+    /// [`validate_collect`] reads its lines rather than flagging each one.
+    fn note_key(&mut self, key: &KeyToken) -> Result<()> {
+        if !self.decoder.strict || self.decoder.expandable_path_parts(key).is_some() {
+            return Ok(());
+        }
+        if let Some(Frame::Object { keys, .. }) = self.frames.last_mut() {
+            if !keys.insert(key.value.clone()) {
+                return Err(duplicate_key(&key.value));
+            }
+        }
+        Ok(())
+    }
+
+    fn list_item(&mut self, item: &str, item_level: usize, at: Location) -> Result<()> {
+        if item.is_empty() {
+            return Ok(());
+        }
+        if self.decoder.inline_objects && item.starts_with('{') {
+            self.decoder.parse_inline_object(trim_ascii(item))?;
+            return Ok(());
+        }
+        if let Some(header) = self.decoder.parse_array_header(item)? {
+            if header.key.is_none() {
+                return self.start_array(header, item_level, false, at);
+            }
+            self.frames.push(Frame::Object {
+                level: item_level + 1,
+                override_level: None,
+                keys: HashSet::new(),
+            });
+            if let Some(key) = header.key.as_ref() {
+                let noted = self.note_key(key);
+                self.tolerate(noted, at)?;
+            }
+            return self.start_array(header, item_level + 1, false, at);
+        }
+        if self.decoder.split_key_value(item)?.is_some() {
+            self.frames.push(Frame::Object {
+                level: item_level + 1,
+                override_level: None,
+                keys: HashSet::new(),
+            });
+            return self.object_line(item, item_level + 1, at);
+        }
+        self.decoder.parse_value_token(item)?;
+        Ok(())
+    }
+
+    /// Object and list frames mirror the blocks the tree decoder counts
+    /// against `max_depth`.
+    fn check_depth(&self) -> Result<()> {
+        let depth = self
+            .frames
+            .iter()
+            .filter(|frame| matches!(frame, Frame::Object { .. } | Frame::List { .. }))
+            .count();
+        if depth > self.decoder.max_depth {
+            return Err(Error::decode("maximum nesting depth exceeded"));
+        }
+        Ok(())
+    }
+
+    /// `parse_array_from_header`: inline arrays are checked at once, block
+    /// arrays open a frame for the lines that follow.
+    fn start_array(
+        &mut self,
+        header: HeaderLine,
+        base_level: usize,
+        deindent: bool,
+        at: Location,
+    ) -> Result<()> {
+        if let Some(inline) = header.inline.as_deref() {
+            let items = self
+                .decoder
+                .parse_inline_array(inline, header.delimiter, header.len)?;
+            return self.decoder.check_array_len(&header, items.len());
+        }
+        if let Some(columns) = header.columns {
+            self.frames.push(Frame::Matrix {
+                row_level: base_level + 1,
+                columns,
+                header,
+                count: 0,
+                at,
+            });
+        } else if header.fields.is_some() {
+            self.frames.push(Frame::Tabular {
+                base_level,
+                row_level: None,
+                header,
+                count: 0,
+                deindent,
+                at,
+            });
+        } else if header.len > 0 {
+            self.frames.push(Frame::List {
+                item_level: base_level + 1,
+                header,
+                count: 0,
+                at,
+            });
+        }
+        Ok(())
+    }
+
+    /// Pops the innermost frame and checks the length of an array it closes.
+    /// `deindent` is set when a tabular block ended on a non-row line.
+    fn close_top(&mut self, deindent: bool) -> Result<()> {
+        match self.frames.pop() {
+            Some(
+                Frame::List {
+                    header, count, at, ..
+                }
+                | Frame::Matrix {
+                    header, count, at, ..
+                },
+            ) => {
+                let result = self.decoder.check_array_len(&header, count);
+                self.tolerate(result.map_err(|err| err.with_location(at)), at)
+            }
+            Some(Frame::Tabular {
+                header,
+                count,
+                deindent: sibling,
+                at,
+                ..
+            }) => {
+                let result = self.decoder.check_array_len(&header, count);
+                self.tolerate(result.map_err(|err| err.with_location(at)), at)?;
+                if deindent && sibling {
+                    if let Some(Frame::Object {
+                        level,
+                        override_level,
+                        ..
+                    }) = self.frames.last_mut()
+                    {
+                        *override_level = Some(*level);
+                    }
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn column(start: Location, byte: usize) -> Location {
+    Location {
+        offset: start.offset + byte,
+        line: start.line,
+        column: byte + 1,
+    }
+}
@@ -0,0 +1,134 @@
+use serde_json::Value;
+
+use crate::{decode_to_value_with_options, DecodeOptions, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A single difference between two decoded documents.
+///
+/// `path` uses dotted keys with `[index]` for array elements (`users[1].name`);
+/// the root is the empty path. Keys that are empty or contain `.`, `[`, `]`
+/// or `"` are double-quoted with `"` and `\` backslash-escaped, so
+/// `{"a.b": 1}` reports `"a.b"` while `{"a": {"b": 1}}` reports `a.b`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueDiff {
+    pub path: String,
+    pub kind: DiffKind,
+    pub left: Option<Value>,
+    pub right: Option<Value>,
+}
+
+/// Decodes both documents and returns their structural differences.
+///
+/// Key order and formatting are ignored; an empty result means both documents
+/// decode to the same value.
+pub fn diff(left: &str, right: &str, options: &DecodeOptions) -> Result<Vec<ValueDiff>> {
+    let left = decode_to_value_with_options(left, options)?;
+    let right = decode_to_value_with_options(right, options)?;
+    Ok(diff_values(&left, &right))
+}
+
+pub fn diff_values(left: &Value, right: &Value) -> Vec<ValueDiff> {
+    let mut diffs = Vec::new();
+    let mut path = String::new();
+    diff_into(left, right, &mut path, &mut diffs);
+    diffs
+}
+
+fn diff_into(left: &Value, right: &Value, path: &mut String, diffs: &mut Vec<ValueDiff>) {
+    match (left, right) {
+        (Value::Object(left_map), Value::Object(right_map)) => {
+            for (key, left_value) in left_map {
+                let len = path.len();
+                push_key(path, key);
+                match right_map.get(key) {
+                    Some(right_value) => diff_into(left_value, right_value, path, diffs),
+                    None => diffs.push(removed(path, left_value)),
+                }
+                path.truncate(len);
+            }
+            for (key, right_value) in right_map {
+                if left_map.contains_key(key) {
+                    continue;
+                }
+                let len = path.len();
+                push_key(path, key);
+                diffs.push(added(path, right_value));
+                path.truncate(len);
+            }
+        }
+        (Value::Array(left_items), Value::Array(right_items)) => {
+            let shared = left_items.len().min(right_items.len());
+            for idx in 0..left_items.len().max(right_items.len()) {
+                let len = path.len();
+                push_index(path, idx);
+                if idx < shared {
+                    diff_into(&left_items[idx], &right_items[idx], path, diffs);
+                } else if idx < left_items.len() {
+                    diffs.push(removed(path, &left_items[idx]));
+                } else {
+                    diffs.push(added(path, &right_items[idx]));
+                }
+                path.truncate(len);
+            }
+        }
+        _ => {
+            if left != right {
+                diffs.push(ValueDiff {
+                    path: path.clone(),
+                    kind: DiffKind::Changed,
+                    left: Some(left.clone()),
+                    right: Some(right.clone()),
+                });
+            }
+        }
+    }
+}
+
+fn push_key(path: &mut String, key: &str) {
+    if !path.is_empty() {
+        path.push('.');
+    }
+    if key.is_empty() || key.contains(['.', '[', ']', '"']) {
+        path.push('"');
+        for ch in key.chars() {
+            if matches!(ch, '"' | '\\') {
+                path.push('\\');
+            }
+            path.push(ch);
+        }
+        path.push('"');
+    } else {
+        path.push_str(key);
+    }
+}
+
+fn push_index(path: &mut String, idx: usize) {
+    path.push('[');
+    let mut buffer = itoa::Buffer::new();
+    path.push_str(buffer.format(idx));
+    path.push(']');
+}
+
+fn added(path: &str, value: &Value) -> ValueDiff {
+    ValueDiff {
+        path: path.to_string(),
+        kind: DiffKind::Added,
+        left: None,
+        right: Some(value.clone()),
+    }
+}
+
+fn removed(path: &str, value: &Value) -> ValueDiff {
+    ValueDiff {
+        path: path.to_string(),
+        kind: DiffKind::Removed,
+        left: Some(value.clone()),
+        right: None,
+    }
+}
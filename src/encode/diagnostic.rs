@@ -0,0 +1,43 @@
+use std::fmt;
+
+/// Why the encoder fell back to list form for an array of objects.
+///
+/// Reported through [`EncodeOptions::with_diagnostics_hook`](crate::EncodeOptions::with_diagnostics_hook);
+/// `row` is the zero-based index of the first offending element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Diagnostic {
+    EmptyFirstRow,
+    NonObjectRow { row: usize },
+    FieldMismatch { row: usize },
+    NonScalarField { row: usize, field: String },
+    TooManyColumns { columns: usize, max: usize },
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Diagnostic::EmptyFirstRow => write!(f, "array not tabular: row 0 is an empty object"),
+            Diagnostic::NonObjectRow { row } => {
+                write!(f, "array not tabular: row {row} is not an object")
+            }
+            Diagnostic::FieldMismatch { row } => {
+                write!(
+                    f,
+                    "array not tabular: row {row} has different fields than row 0"
+                )
+            }
+            Diagnostic::NonScalarField { row, field } => {
+                write!(
+                    f,
+                    "array not tabular: field '{field}' is non-scalar in row {row}"
+                )
+            }
+            Diagnostic::TooManyColumns { columns, max } => {
+                write!(
+                    f,
+                    "array not tabular: {columns} columns exceed the maximum of {max}"
+                )
+            }
+        }
+    }
+}
@@ -1,5 +1,9 @@
+pub mod diagnostic;
+mod scalars;
+
 use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
+use std::cmp::Ordering;
+use std::collections::HashSet;
 use std::io::Write;
 
 use serde::Serialize;
@@ -11,31 +15,42 @@ use smol_str::SmolStr;
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
+use crate::checksum::{append_checksum_line, Crc32, CHECKSUM_PREFIX};
+use crate::comments::Comment;
 use crate::num::number::append_json_number_bytes;
+use crate::tabular::tabular_header;
 use crate::text::string::{
     analyze_string, escape_string_into, escape_string_into_bytes, is_canonical_unquoted_key,
-    is_identifier_segment, ByteSink,
+    is_identifier_segment, is_non_finite_literal, is_valid_empty_string_marker,
+    is_valid_path_separator, ByteSink,
+};
+use crate::{
+    cache_map, CacheLimits, CacheMap, ColonSpacing, DiagnosticsHook, EncodeOptions, Error,
+    KeyFolding, KeyOrder, LineLimitPolicy, NumberFormatter, Result, SingleElementArrayStyle,
 };
-use crate::{EncodeOptions, Error, Indent, Result};
+
+pub use diagnostic::Diagnostic;
+
+const TRUNCATION_MARKER: &[u8] = b"# truncated";
+const STREAM_CHUNK_BYTES: usize = 64 * 1024;
 
 const STRING_CACHE_MAX_LEN: usize = 64;
-const STRING_CACHE_MAX_ITEMS: usize = 1024;
 const KEY_CACHE_MAX_LEN: usize = 64;
-const KEY_CACHE_MAX_ITEMS: usize = 1024;
 const LARGE_CONTAINER_THRESHOLD: usize = 64;
 const MAX_RESERVE_DEPTH: usize = 2;
+const MAX_PRECOMPUTE_DEPTH: usize = 2;
 const RESERVE_SAMPLE_ITEMS: usize = 4;
 const PRECOMPUTE_SAMPLE_ITEMS: usize = 4;
 const PRECOMPUTE_MAX_ROWS: usize = 128;
 const PRECOMPUTE_MAX_STRINGS: usize = 2048;
-const TABULAR_STRING_CACHE_MAX_ITEMS: usize = 512;
+const PRECOMPUTE_MIN_STRINGS: usize = 16;
 const NUMBER_CACHE_MAX_LEN: usize = 32;
-const TABULAR_NUMBER_CACHE_MAX_ITEMS: usize = 512;
-const TABULAR_PREFIXED_CACHE_MAX_ITEMS: usize = 512;
 #[cfg(feature = "parallel")]
 const PARALLEL_TABULAR_MIN_ROWS: usize = 256;
 #[cfg(feature = "parallel")]
 const PARALLEL_TABULAR_MIN_CELLS: usize = 2048;
+#[cfg(feature = "parallel")]
+const PARALLEL_LIST_MIN_ITEMS: usize = 256;
 
 #[cfg(feature = "parallel")]
 type RowBuf = SmallVec<[u8; 256]>;
@@ -64,6 +79,36 @@ impl TabularLastCache {
     }
 }
 
+fn append_number_bytes<B: ByteSink>(
+    buf: &mut B,
+    number: &serde_json::Number,
+    formatter: Option<&NumberFormatter>,
+    delimiter: char,
+) {
+    match formatter.and_then(|formatter| formatter.format(number)) {
+        Some(text) if is_bare_formatted_number(&text, delimiter) => {
+            buf.extend_bytes(text.as_bytes())
+        }
+        Some(text) => {
+            buf.push_byte(b'"');
+            escape_string_into_bytes(buf, &text);
+            buf.push_byte(b'"');
+        }
+        None => append_json_number_bytes(buf, number),
+    }
+}
+
+/// Whether formatter output can be written unquoted without splitting a cell
+/// or ending the line's value early.
+fn is_bare_formatted_number(text: &str, delimiter: char) -> bool {
+    !text.is_empty()
+        && !text.starts_with(char::is_whitespace)
+        && !text.ends_with(char::is_whitespace)
+        && !text
+            .chars()
+            .any(|ch| ch == delimiter || matches!(ch, '"' | '\\' | ':') || ch.is_control())
+}
+
 fn number_cache_key(number: &serde_json::Number) -> Option<NumberKey> {
     if let Some(value) = number.as_i64() {
         return Some(NumberKey::I64(value));
@@ -75,12 +120,24 @@ fn number_cache_key(number: &serde_json::Number) -> Option<NumberKey> {
 }
 
 thread_local! {
-    static ENCODER_POOL: RefCell<Encoder> = RefCell::new(Encoder::new(&EncodeOptions::default()));
+    static ENCODER_POOL: RefCell<Encoder<'static>> = RefCell::new(Encoder::new(&EncodeOptions::default()));
+}
+
+fn serialize_value<T: Serialize>(value: &T, options: &EncodeOptions) -> Result<Value> {
+    let policy = scalars::ScalarPolicy {
+        non_finite: options.non_finite,
+        bytes_as_base64: options.bytes_as_base64,
+    };
+    let result = if policy.is_default() {
+        serde_json::to_value(value)
+    } else {
+        serde_json::to_value(scalars::Scalars { value, policy })
+    };
+    result.map_err(|err| Error::serialize_with_source(format!("serialize failed: {err}"), err))
 }
 
 pub fn to_string<T: Serialize>(value: &T, options: &EncodeOptions) -> Result<String> {
-    let value = serde_json::to_value(value)
-        .map_err(|err| Error::serialize_with_source(format!("serialize failed: {err}"), err))?;
+    let value = serialize_value(value, options)?;
     let bytes = encode_value(&value, options)?;
     bytes_to_string(bytes)
 }
@@ -90,8 +147,7 @@ pub fn to_string_into<T: Serialize>(
     options: &EncodeOptions,
     out: &mut String,
 ) -> Result<()> {
-    let value = serde_json::to_value(value)
-        .map_err(|err| Error::serialize_with_source(format!("serialize failed: {err}"), err))?;
+    let value = serialize_value(value, options)?;
     let bytes = encode_value(&value, options)?;
     let encoded = unsafe { std::str::from_utf8_unchecked(&bytes) };
     out.clear();
@@ -108,21 +164,76 @@ pub fn to_string_from_json_str(input: &str, options: &EncodeOptions) -> Result<S
 }
 
 pub fn to_vec<T: Serialize>(value: &T, options: &EncodeOptions) -> Result<Vec<u8>> {
-    let value = serde_json::to_value(value)
-        .map_err(|err| Error::serialize_with_source(format!("serialize failed: {err}"), err))?;
+    let value = serialize_value(value, options)?;
     encode_value(&value, options)
 }
 
 pub fn to_writer<T: Serialize, W: Write>(
+    writer: W,
+    value: &T,
+    options: &EncodeOptions,
+) -> Result<()> {
+    to_writer_streaming(writer, value, options, STREAM_CHUNK_BYTES)
+}
+
+pub fn to_writer_streaming<T: Serialize, W: Write>(
     mut writer: W,
     value: &T,
     options: &EncodeOptions,
+    chunk_bytes: usize,
 ) -> Result<()> {
-    let bytes = to_vec(value, options)?;
+    let value = serialize_value(value, options)?;
+    // Truncation rewrites already-encoded lines and a failed limit must not leave
+    // partial output behind, so a capped document is buffered whole.
+    if options.line_limit.is_some() {
+        let bytes = encode_value(&value, options)?;
+        return write_bytes(&mut writer, &bytes);
+    }
+    with_prepared_value(&value, options, |value| {
+        let mut encoder = Encoder::new(options);
+        encoder.sink = Some(&mut writer);
+        encoder.chunk_bytes = chunk_bytes;
+        encoder.sink_crc = options.checksum.then(Crc32::new);
+        encoder.precompute_string_flags(value);
+        encoder.encode_root(value)?;
+        let len = encoder.out.len();
+        encoder.check_line_limit(len)?;
+        encoder.write_comments(None, 0);
+        encoder.chunk_bytes = 0;
+        encoder.with_out_buf(|encoder, out| encoder.drain_chunk(out))?;
+        let drained = encoder.drained;
+        let crc = encoder.sink_crc.take();
+        drop(encoder);
+        let mut tail = Vec::new();
+        if let Some(crc) = crc {
+            if drained > 0 {
+                tail.push(b'\n');
+            }
+            tail.extend_from_slice(CHECKSUM_PREFIX.as_bytes());
+            tail.extend_from_slice(format!("{:08x}", crc.finish()).as_bytes());
+        }
+        if options.trailing_newline {
+            tail.push(b'\n');
+        }
+        write_bytes(&mut writer, &tail)
+    })
+}
+
+fn write_bytes<W: Write>(writer: &mut W, bytes: &[u8]) -> Result<()> {
     writer
-        .write_all(&bytes)
-        .map_err(|err| Error::encode_with_source(format!("write failed: {err}"), err))?;
-    Ok(())
+        .write_all(bytes)
+        .map_err(|err| Error::encode_with_source(format!("write failed: {err}"), err))
+}
+
+pub fn to_lines(value: &Value, options: &EncodeOptions) -> Result<std::vec::IntoIter<String>> {
+    let encoded = bytes_to_string(encode_value(value, options)?)?;
+    let encoded = encoded.strip_suffix('\n').unwrap_or(&encoded);
+    let lines: Vec<String> = if encoded.is_empty() {
+        Vec::new()
+    } else {
+        encoded.split('\n').map(str::to_string).collect()
+    };
+    Ok(lines.into_iter())
 }
 
 fn bytes_to_string(bytes: Vec<u8>) -> Result<String> {
@@ -134,69 +245,280 @@ fn bytes_to_string(bytes: Vec<u8>) -> Result<String> {
 }
 
 fn encode_value(value: &Value, options: &EncodeOptions) -> Result<Vec<u8>> {
-    ENCODER_POOL.with(|pool| {
-        let mut encoder = pool.borrow_mut();
-        encoder.reset(options);
-        encoder.reserve_for_value(value);
-        encoder.precompute_string_flags(value);
-        encoder.encode_root(value)?;
-        Ok(encoder.take_bytes())
+    with_prepared_value(value, options, |value| {
+        ENCODER_POOL.with(|pool| {
+            let mut encoder = pool.borrow_mut();
+            encoder.reset(options);
+            encoder.reserve_for_value(value);
+            encoder.precompute_string_flags(value);
+            let encoded = encoder.encode_root(value).and_then(|()| {
+                let len = encoder.out.len();
+                encoder.check_line_limit(len)
+            });
+            if let Err(err) = encoded {
+                if !(encoder.line_limit_hit
+                    && options.line_limit_policy == LineLimitPolicy::Truncate)
+                {
+                    return Err(err);
+                }
+                if !encoder.truncate_to_line_limit() {
+                    return Err(err);
+                }
+            }
+            encoder.write_comments(None, 0);
+            let mut bytes = encoder.take_bytes();
+            if options.checksum {
+                append_checksum_line(&mut bytes);
+            }
+            if options.trailing_newline {
+                bytes.push(b'\n');
+            }
+            Ok(bytes)
+        })
     })
 }
 
-struct Encoder {
+// Applies the value rewrites the options ask for (transform, sorting,
+// unwrapping, sparse objects, key/value collapsing) before encoding.
+fn with_prepared_value<R>(
+    value: &Value,
+    options: &EncodeOptions,
+    encode: impl FnOnce(&Value) -> Result<R>,
+) -> Result<R> {
+    if (options.key_folding != KeyFolding::Off || options.collapse_single_chains)
+        && !is_valid_path_separator(&options.flatten_separator)
+    {
+        return Err(Error::encode("invalid flatten separator"));
+    }
+    if let Some(marker) = &options.empty_string_marker {
+        if !is_valid_empty_string_marker(marker, options.delimiter.as_char()) {
+            return Err(Error::encode(format!(
+                "invalid empty string marker {marker:?}"
+            )));
+        }
+    }
+    let transformed;
+    let value = match options.transform {
+        Some(transform) => {
+            let mut owned = value.clone();
+            transform(&mut owned);
+            transformed = owned;
+            &transformed
+        }
+        None => value,
+    };
+    let rewritten;
+    let value =
+        if options.sort_arrays || options.sparse_objects || options.unwrap_single_key_tabular {
+            let mut owned = value.clone();
+            if options.sort_arrays {
+                sort_scalar_arrays(&mut owned);
+            }
+            if options.unwrap_single_key_tabular {
+                unwrap_single_key_rows(&mut owned, options);
+            }
+            if options.sparse_objects {
+                strip_null_fields(&mut owned, options);
+            }
+            rewritten = owned;
+            &rewritten
+        } else {
+            value
+        };
+    let collapsed;
+    let value = match &options.kv_array_collapse {
+        Some((key_field, value_field)) => {
+            collapsed = collapse_kv_arrays(value, key_field, value_field);
+            &collapsed
+        }
+        None => value,
+    };
+    encode(value)
+}
+
+struct Encoder<'w> {
     document_delimiter: char,
     key_folding: bool,
+    quote_folded_segments: bool,
+    collapse_single_chains: bool,
+    /// Path of the object being written, from the document root or the
+    /// enclosing array item, while folding is on.
+    fold_path: String,
+    /// Full paths of the literal dotted keys in `fold_path` and its parents.
+    literal_paths: Vec<String>,
+    /// Segments already folded along `fold_path`, counted against
+    /// `flatten_depth`.
+    folded_segments: usize,
+    comments: Vec<Comment>,
+    comment_path: String,
+    comment_array_depth: usize,
+    comment_key: Option<String>,
+    pending_comment: Option<String>,
+    max_tabular_columns: usize,
+    single_element_list: bool,
+    inline_array_max: usize,
+    empty_containers_in_tabular: bool,
+    escape_leading_special_chars: bool,
     flatten_depth: usize,
+    flatten_separator: String,
+    inline_depth: usize,
+    inline_small_objects: usize,
+    line_limit: usize,
+    /// Bytes the checksum line and trailing newline add after the body.
+    line_limit_tail: usize,
+    line_limit_hit: bool,
+    section_spacing: bool,
+    matrix_tabular: bool,
+    cache_limits: CacheLimits,
+    number_formatter: Option<NumberFormatter>,
+    diagnostics_hook: Option<DiagnosticsHook>,
+    key_order: Vec<String>,
+    key_ordering: KeyOrder,
+    indent_first_level: usize,
+    empty_string_marker: Option<String>,
+    colon_separator: &'static [u8],
     indent_unit: Vec<u8>,
     indent_cache: Vec<Vec<u8>>,
     delimiter_stack: Vec<char>,
-    string_cache: HashMap<char, HashMap<SmolStr, (bool, bool)>>,
-    key_cache: HashMap<SmolStr, String>,
-    tabular_string_cache: HashMap<char, HashMap<SmolStr, Vec<u8>>>,
-    tabular_prefixed_string_cache: HashMap<char, HashMap<SmolStr, Vec<u8>>>,
-    tabular_number_cache: HashMap<NumberKey, Vec<u8>>,
-    tabular_prefixed_number_cache: HashMap<char, HashMap<NumberKey, Vec<u8>>>,
+    string_cache: CacheMap<char, CacheMap<SmolStr, (bool, bool)>>,
+    key_cache: CacheMap<SmolStr, String>,
+    tabular_string_cache: CacheMap<char, CacheMap<SmolStr, Vec<u8>>>,
+    tabular_prefixed_string_cache: CacheMap<char, CacheMap<SmolStr, Vec<u8>>>,
+    tabular_number_cache: CacheMap<NumberKey, Vec<u8>>,
+    tabular_prefixed_number_cache: CacheMap<char, CacheMap<NumberKey, Vec<u8>>>,
     tabular_last_values: Vec<TabularLastCache>,
-    key_intern: HashMap<String, usize>,
+    key_intern: CacheMap<String, usize>,
     interned_keys: Vec<String>,
     line_buf: Vec<u8>,
     out: Vec<u8>,
+    // Streaming target: `out` is handed over once it reaches `chunk_bytes`
+    // at an entry boundary. `drained` counts the bytes already written.
+    sink: Option<&'w mut dyn Write>,
+    chunk_bytes: usize,
+    drained: usize,
+    sink_crc: Option<Crc32>,
 }
 
-impl Encoder {
+impl<'w> Encoder<'w> {
     fn new(options: &EncodeOptions) -> Self {
-        let Indent::Spaces(indent_size) = options.indent;
-        let indent_unit = vec![b' '; indent_size];
+        let indent_unit = vec![options.indent.byte(); options.indent.width()];
         Self {
             document_delimiter: options.delimiter.as_char(),
-            key_folding: matches!(options.key_folding, crate::options::KeyFolding::Safe),
+            key_folding: options.key_folding != KeyFolding::Off,
+            quote_folded_segments: options.key_folding == KeyFolding::Aggressive,
+            collapse_single_chains: options.collapse_single_chains,
+            fold_path: String::new(),
+            literal_paths: Vec::new(),
+            folded_segments: 0,
+            comments: options.comments.clone(),
+            comment_path: String::new(),
+            comment_array_depth: 0,
+            comment_key: options.comment_key.clone(),
+            pending_comment: None,
+            max_tabular_columns: options.max_tabular_columns,
+            single_element_list: options.single_element_array_style
+                == SingleElementArrayStyle::List,
+            inline_array_max: options.inline_array_threshold.unwrap_or(usize::MAX),
+            empty_containers_in_tabular: options.empty_containers_in_tabular,
+            escape_leading_special_chars: escape_leading_hash(options),
             flatten_depth: options.flatten_depth.unwrap_or(usize::MAX),
+            flatten_separator: options.flatten_separator.clone(),
+            inline_depth: options.inline_depth,
+            inline_small_objects: options.inline_small_objects,
+            line_limit: options.line_limit.unwrap_or(usize::MAX),
+            line_limit_tail: line_limit_tail(options),
+            line_limit_hit: false,
+            section_spacing: options.section_spacing,
+            matrix_tabular: options.matrix_tabular,
+            cache_limits: options.cache_limits,
+            number_formatter: options.number_formatter.clone(),
+            diagnostics_hook: options.diagnostics_hook.clone(),
+            key_order: options.key_order.clone(),
+            key_ordering: options.key_ordering,
+            indent_first_level: options.indent_first_level,
+            empty_string_marker: options.empty_string_marker.clone(),
+            colon_separator: colon_separator(options.colon_spacing),
             indent_unit,
             indent_cache: vec![Vec::new()],
             delimiter_stack: Vec::new(),
-            string_cache: HashMap::with_capacity(4),
-            key_cache: HashMap::with_capacity(KEY_CACHE_MAX_ITEMS),
-            tabular_string_cache: HashMap::with_capacity(4),
-            tabular_prefixed_string_cache: HashMap::with_capacity(4),
-            tabular_number_cache: HashMap::with_capacity(TABULAR_NUMBER_CACHE_MAX_ITEMS),
-            tabular_prefixed_number_cache: HashMap::with_capacity(4),
+            string_cache: cache_map(4),
+            key_cache: cache_map(options.cache_limits.keys),
+            tabular_string_cache: cache_map(4),
+            tabular_prefixed_string_cache: cache_map(4),
+            tabular_number_cache: cache_map(options.cache_limits.tabular_numbers),
+            tabular_prefixed_number_cache: cache_map(4),
             tabular_last_values: Vec::new(),
-            key_intern: HashMap::new(),
+            key_intern: cache_map(0),
             interned_keys: Vec::new(),
             line_buf: Vec::with_capacity(128),
             out: Vec::with_capacity(128),
+            sink: None,
+            chunk_bytes: 0,
+            drained: 0,
+            sink_crc: None,
         }
     }
 
     fn reset(&mut self, options: &EncodeOptions) {
-        let Indent::Spaces(indent_size) = options.indent;
         self.document_delimiter = options.delimiter.as_char();
-        self.key_folding = matches!(options.key_folding, crate::options::KeyFolding::Safe);
+        self.key_folding = options.key_folding != KeyFolding::Off;
+        self.quote_folded_segments = options.key_folding == KeyFolding::Aggressive;
+        self.collapse_single_chains = options.collapse_single_chains;
+        self.fold_path.clear();
+        self.literal_paths.clear();
+        self.folded_segments = 0;
+        self.comments.clone_from(&options.comments);
+        self.comment_path.clear();
+        self.comment_array_depth = 0;
+        self.comment_key.clone_from(&options.comment_key);
+        self.pending_comment = None;
+        self.max_tabular_columns = options.max_tabular_columns;
+        self.single_element_list =
+            options.single_element_array_style == SingleElementArrayStyle::List;
+        self.inline_array_max = options.inline_array_threshold.unwrap_or(usize::MAX);
+        self.empty_containers_in_tabular = options.empty_containers_in_tabular;
+        if self.escape_leading_special_chars != escape_leading_hash(options) {
+            self.escape_leading_special_chars = escape_leading_hash(options);
+            self.tabular_string_cache.clear();
+            self.tabular_prefixed_string_cache.clear();
+        }
         self.flatten_depth = options.flatten_depth.unwrap_or(usize::MAX);
-        if self.indent_unit.len() != indent_size {
+        self.flatten_separator
+            .clone_from(&options.flatten_separator);
+        self.inline_depth = options.inline_depth;
+        self.inline_small_objects = options.inline_small_objects;
+        self.line_limit = options.line_limit.unwrap_or(usize::MAX);
+        self.line_limit_tail = line_limit_tail(options);
+        self.line_limit_hit = false;
+        self.section_spacing = options.section_spacing;
+        self.matrix_tabular = options.matrix_tabular;
+        if self.cache_limits != options.cache_limits {
+            self.cache_limits = options.cache_limits;
+            self.string_cache.clear();
+            self.key_cache.clear();
+            self.tabular_string_cache.clear();
+            self.tabular_prefixed_string_cache.clear();
+            self.tabular_number_cache.clear();
+            self.tabular_prefixed_number_cache.clear();
+        }
+        // Cached number cells hold formatter output.
+        if self.number_formatter.is_some() || options.number_formatter.is_some() {
+            self.tabular_number_cache.clear();
+            self.tabular_prefixed_number_cache.clear();
+        }
+        self.number_formatter.clone_from(&options.number_formatter);
+        self.diagnostics_hook.clone_from(&options.diagnostics_hook);
+        self.key_order.clone_from(&options.key_order);
+        self.key_ordering = options.key_ordering;
+        self.indent_first_level = options.indent_first_level;
+        self.empty_string_marker
+            .clone_from(&options.empty_string_marker);
+        self.colon_separator = colon_separator(options.colon_spacing);
+        let (indent_byte, indent_width) = (options.indent.byte(), options.indent.width());
+        if self.indent_unit.len() != indent_width || self.indent_unit.first() != Some(&indent_byte)
+        {
             self.indent_unit.clear();
-            self.indent_unit.resize(indent_size, b' ');
+            self.indent_unit.resize(indent_width, indent_byte);
             self.indent_cache.clear();
             self.indent_cache.push(Vec::new());
         }
@@ -212,6 +534,81 @@ impl Encoder {
         std::mem::take(&mut self.out)
     }
 
+    fn check_line_limit(&mut self, len: usize) -> Result<()> {
+        if self.drained + len + self.line_limit_tail <= self.line_limit {
+            return Ok(());
+        }
+        self.line_limit_hit = true;
+        Err(Error::encode(format!(
+            "encoded output exceeds the line limit of {} bytes",
+            self.line_limit
+        )))
+    }
+
+    // Runs after each object entry, tabular or matrix row and list item:
+    // enforces the line limit and, when streaming, drains `out` to the sink.
+    fn end_entry(&mut self) -> Result<()> {
+        self.with_out_buf(|encoder, out| encoder.end_entry_into(out))
+    }
+
+    fn end_entry_into(&mut self, out: &mut Vec<u8>) -> Result<()> {
+        self.check_line_limit(out.len())?;
+        self.drain_chunk(out)
+    }
+
+    fn drain_chunk(&mut self, out: &mut Vec<u8>) -> Result<()> {
+        let Some(sink) = self.sink.as_mut() else {
+            return Ok(());
+        };
+        if out.is_empty() || out.len() < self.chunk_bytes {
+            return Ok(());
+        }
+        sink.write_all(out)
+            .map_err(|err| Error::encode_with_source(format!("write failed: {err}"), err))?;
+        if let Some(crc) = self.sink_crc.as_mut() {
+            crc.update(out);
+        }
+        self.drained += out.len();
+        out.clear();
+        Ok(())
+    }
+
+    fn has_output(&self, out: &[u8]) -> bool {
+        !out.is_empty() || self.drained > 0
+    }
+
+    // Keeps the whole lines that leave room for the marker line and the tail,
+    // minus any trailing `key:` lines whose children were cut. Later comments
+    // are dropped so the marker stays last. Returns false when even the marker
+    // does not fit.
+    fn truncate_to_line_limit(&mut self) -> bool {
+        let Some(room) = self
+            .line_limit
+            .checked_sub(self.line_limit_tail + TRUNCATION_MARKER.len())
+        else {
+            return false;
+        };
+        // The newline ending the last kept line is reused before the marker, so
+        // it may sit at index `room - 1`.
+        let mut keep = self.out[..room.min(self.out.len())]
+            .iter()
+            .rposition(|&byte| byte == b'\n')
+            .unwrap_or(0);
+        while keep > 0 && self.out[keep - 1] == b':' {
+            keep = self.out[..keep]
+                .iter()
+                .rposition(|&byte| byte == b'\n')
+                .unwrap_or(0);
+        }
+        self.out.truncate(keep);
+        if !self.out.is_empty() {
+            self.out.push(b'\n');
+        }
+        self.out.extend_from_slice(TRUNCATION_MARKER);
+        self.comments.clear();
+        true
+    }
+
     fn active_delimiter(&self) -> char {
         self.delimiter_stack
             .last()
@@ -261,20 +658,20 @@ impl Encoder {
     }
 
     fn precompute_string_flags(&mut self, value: &Value) {
-        self.precompute_string_flags_inner(value);
+        // Documents with only a few strings, at any depth, see each of them
+        // once, so warming the cache first only doubles the lookups.
+        if count_strings(value, PRECOMPUTE_MIN_STRINGS) >= PRECOMPUTE_MIN_STRINGS {
+            self.precompute_string_flags_inner(value, 0);
+        }
     }
 
-    fn precompute_string_flags_inner(&mut self, value: &Value) {
+    fn precompute_string_flags_inner(&mut self, value: &Value, depth: usize) {
         match value {
             Value::String(value) => self.precompute_small_string(value),
             Value::Array(array) => self.precompute_array_strings(array),
-            Value::Object(map) => {
+            Value::Object(map) if depth <= MAX_PRECOMPUTE_DEPTH => {
                 for value in map.values() {
-                    match value {
-                        Value::String(value) => self.precompute_small_string(value),
-                        Value::Array(array) => self.precompute_array_strings(array),
-                        _ => {}
-                    }
+                    self.precompute_string_flags_inner(value, depth + 1);
                 }
             }
             _ => {}
@@ -363,7 +760,10 @@ impl Encoder {
     fn encode_root(&mut self, value: &Value) -> Result<()> {
         match value {
             Value::Object(map) => self.encode_object(map, 0),
-            Value::Array(array) => self.encode_array_value(array, 0, None, b""),
+            Value::Array(array) => {
+                self.write_comments(Some(""), 0);
+                self.encode_array_value(array, 0, None, false, b"")
+            }
             _ => self.with_line_buf(|encoder, line| -> Result<()> {
                 line.clear();
                 encoder.append_scalar_document(line, value)?;
@@ -383,32 +783,225 @@ impl Encoder {
         for key in map.keys() {
             siblings.insert(key.as_str());
         }
-        for (key, value) in map.iter() {
-            if let Some((folded_key, folded_value)) = self.fold_key_value(key, value, &siblings) {
-                self.encode_object_entry(&folded_key, folded_value, indent_level)?;
-            } else {
-                self.encode_object_entry(key, value, indent_level)?;
+        let literal_len = self.literal_paths.len();
+        if self.key_folding || self.collapse_single_chains {
+            self.record_literal_paths(map);
+        }
+        if self.key_order.is_empty() && self.key_ordering == KeyOrder::Preserve {
+            for (key, value) in map.iter() {
+                if self.take_comment_entry(key, value) {
+                    continue;
+                }
+                self.encode_object_field(key, value, &siblings, indent_level)?;
+                self.end_entry()?;
             }
+        } else {
+            for (key, value) in self.ordered_entries(map) {
+                if self.take_comment_entry(key, value) {
+                    continue;
+                }
+                self.encode_object_field(key, value, &siblings, indent_level)?;
+                self.end_entry()?;
+            }
+        }
+        // A comment entry with no field after it trails the object.
+        if let Some(text) = self.pending_comment.take() {
+            self.write_comment_text(&text, indent_level);
         }
+        self.literal_paths.truncate(literal_len);
         Ok(())
     }
 
-    fn encode_object_entry(&mut self, key: &str, value: &Value, indent_level: usize) -> Result<()> {
+    fn record_literal_paths(&mut self, map: &serde_json::Map<String, Value>) {
+        let separator = self.flatten_separator.as_str();
+        for key in map.keys().filter(|key| key.contains(separator)) {
+            let path = if self.fold_path.is_empty() {
+                key.clone()
+            } else {
+                format!("{}{separator}{key}", self.fold_path)
+            };
+            self.literal_paths.push(path);
+        }
+    }
+
+    /// A fold is skipped when its full path repeats a literal dotted key from
+    /// this object or a parent, since that would write the same key twice.
+    fn folds_onto_literal(&self, folded: &str) -> bool {
+        let separator = self.flatten_separator.as_str();
+        self.literal_paths
+            .iter()
+            .any(|path| match path.strip_suffix(folded) {
+                Some("") => self.fold_path.is_empty(),
+                Some(prefix) => prefix.strip_suffix(separator) == Some(self.fold_path.as_str()),
+                None => false,
+            })
+    }
+
+    /// Holds back a string under the comment key so it is written above the
+    /// next field rather than as data.
+    fn take_comment_entry(&mut self, key: &str, value: &Value) -> bool {
+        let Value::String(text) = value else {
+            return false;
+        };
+        if self.comment_key.as_deref() != Some(key) {
+            return false;
+        }
+        match &mut self.pending_comment {
+            Some(pending) => {
+                pending.push('\n');
+                pending.push_str(text);
+            }
+            None => self.pending_comment = Some(text.clone()),
+        }
+        true
+    }
+
+    fn ordered_entries<'a>(
+        &self,
+        map: &'a serde_json::Map<String, Value>,
+    ) -> Vec<(&'a String, &'a Value)> {
+        let mut entries = Vec::with_capacity(map.len());
+        for (idx, key) in self.key_order.iter().enumerate() {
+            if self.key_order[..idx].contains(key) {
+                continue;
+            }
+            if let Some(entry) = map.get_key_value(key) {
+                entries.push(entry);
+            }
+        }
+        let listed = entries.len();
+        entries.extend(map.iter().filter(|(key, _)| !self.key_order.contains(key)));
+        if self.key_ordering == KeyOrder::Sorted {
+            entries[listed..].sort_unstable_by_key(|(key, _)| *key);
+        }
+        entries
+    }
+
+    fn encode_object_field(
+        &mut self,
+        key: &str,
+        value: &Value,
+        siblings: &HashSet<&str>,
+        indent_level: usize,
+    ) -> Result<()> {
+        let folded = self
+            .fold_key_value(key, value, siblings)
+            .or_else(|| self.collapse_single_chain(key, value, siblings));
+        let (key, value, segments) = match &folded {
+            Some((folded_key, folded_value, segments)) => {
+                (folded_key.as_str(), *folded_value, *segments)
+            }
+            None => (key, value, 0),
+        };
+        if self.section_spacing
+            && indent_level == 0
+            && self.has_output(&self.out)
+            && matches!(value, Value::Array(_) | Value::Object(_))
+        {
+            self.out.push(b'\n');
+        }
+        if let Some(text) = self.pending_comment.take() {
+            self.write_comment_text(&text, indent_level);
+        }
+        if !self.comments.is_empty() && self.comment_array_depth == 0 {
+            let parent_len = self.comment_path.len();
+            self.push_comment_segment(key);
+            let path = std::mem::take(&mut self.comment_path);
+            self.write_comments(Some(&path), indent_level);
+            self.comment_path = path;
+            self.comment_path.truncate(parent_len);
+        }
+        // An object left over from a fold spends the folded segments of the
+        // depth budget for the fields below it.
+        self.folded_segments += segments;
+        // Folded keys are already joined by the separator, with non-identifier
+        // segments quoted under `Aggressive`, so they are written as they are
+        // even when the separator is not a canonical key character.
+        let result = self.encode_object_entry(key, folded.is_some(), value, indent_level);
+        self.folded_segments -= segments;
+        result
+    }
+
+    fn push_comment_segment(&mut self, key: &str) {
+        if !self.comment_path.is_empty() && !key.is_empty() {
+            self.comment_path.push('.');
+        }
+        self.comment_path.push_str(key);
+    }
+
+    fn write_comments(&mut self, path: Option<&str>, indent_level: usize) {
+        if self.comments.is_empty() {
+            return;
+        }
+        let (matched, rest): (Vec<Comment>, Vec<Comment>) = std::mem::take(&mut self.comments)
+            .into_iter()
+            .partition(|comment| comment.path.as_deref() == path);
+        self.comments = rest;
+        for comment in matched {
+            self.write_comment_text(&comment.text, indent_level);
+        }
+    }
+
+    fn write_comment_text(&mut self, text: &str, indent_level: usize) {
+        for text in text.split('\n') {
+            let mut line = Vec::with_capacity(text.len() + 2);
+            line.push(b'#');
+            if !text.is_empty() {
+                line.push(b' ');
+                line.extend_from_slice(text.as_bytes());
+            }
+            self.write_line_bytes(indent_level, &line);
+        }
+    }
+
+    fn encode_object_entry(
+        &mut self,
+        key: &str,
+        raw_key: bool,
+        value: &Value,
+        indent_level: usize,
+    ) -> Result<()> {
         match value {
-            Value::Array(array) => self.encode_array_value(array, indent_level, Some(key), b""),
+            Value::Array(array) => {
+                self.encode_array_value(array, indent_level, Some(key), raw_key, b"")
+            }
+            Value::Object(map) if self.fits_inline(map) => {
+                self.with_line_buf(|encoder, line| -> Result<()> {
+                    line.clear();
+                    encoder.append_encoded_key(line, key, raw_key);
+                    line.extend_from_slice(encoder.colon_separator);
+                    encoder.append_inline_object(line, map)?;
+                    encoder.write_line_bytes(indent_level, line);
+                    Ok(())
+                })
+            }
             Value::Object(map) => {
                 self.with_line_buf(|encoder, line| {
                     line.clear();
-                    encoder.append_encoded_key(line, key);
+                    encoder.append_encoded_key(line, key, raw_key);
                     line.push(b':');
                     encoder.write_line_bytes(indent_level, line);
                 });
-                self.encode_object(map, indent_level + 1)
+                let parent_len = self.comment_path.len();
+                if !self.comments.is_empty() {
+                    self.push_comment_segment(key);
+                }
+                let fold_len = self.fold_path.len();
+                if self.key_folding || self.collapse_single_chains {
+                    if fold_len > 0 {
+                        self.fold_path.push_str(&self.flatten_separator);
+                    }
+                    self.fold_path.push_str(key);
+                }
+                let result = self.encode_object(map, indent_level + 1);
+                self.comment_path.truncate(parent_len);
+                self.fold_path.truncate(fold_len);
+                result
             }
             _ => self.with_line_buf(|encoder, line| -> Result<()> {
                 line.clear();
-                encoder.append_encoded_key(line, key);
-                line.extend_from_slice(b": ");
+                encoder.append_encoded_key(line, key, raw_key);
+                line.extend_from_slice(encoder.colon_separator);
                 encoder.append_scalar_document(line, value)?;
                 encoder.write_line_bytes(indent_level, line);
                 Ok(())
@@ -416,13 +1009,47 @@ impl Encoder {
         }
     }
 
+    fn fits_inline(&self, map: &serde_json::Map<String, Value>) -> bool {
+        (self.inline_depth > 0
+            && inline_object_depth(map).is_some_and(|depth| depth <= self.inline_depth))
+            || self.fits_small_inline(map)
+    }
+
+    fn fits_small_inline(&self, map: &serde_json::Map<String, Value>) -> bool {
+        map.len() <= self.inline_small_objects && inline_object_depth(map) == Some(1)
+    }
+
+    // Inline objects always separate entries with `, `, so values are quoted as if the
+    // delimiter were a comma regardless of the document delimiter.
+    fn append_inline_object(
+        &mut self,
+        buf: &mut Vec<u8>,
+        map: &serde_json::Map<String, Value>,
+    ) -> Result<()> {
+        buf.push(b'{');
+        for (idx, (key, value)) in map.iter().enumerate() {
+            if idx > 0 {
+                buf.extend_from_slice(b", ");
+            }
+            self.append_encoded_key(buf, key, false);
+            buf.extend_from_slice(b": ");
+            match value {
+                Value::Object(inner) => self.append_inline_object(buf, inner)?,
+                _ => self.append_scalar_with_delimiter(buf, value, ',')?,
+            }
+        }
+        buf.push(b'}');
+        Ok(())
+    }
+
     fn fold_key_value<'a>(
         &self,
         key: &str,
         value: &'a Value,
         siblings: &HashSet<&str>,
-    ) -> Option<(String, &'a Value)> {
-        if !self.key_folding || self.flatten_depth < 2 {
+    ) -> Option<(String, &'a Value, usize)> {
+        let flatten_depth = self.flatten_depth.saturating_sub(self.folded_segments);
+        if !self.key_folding || flatten_depth < 2 {
             return None;
         }
 
@@ -438,21 +1065,37 @@ impl Encoder {
         }
 
         let chain_len = segments.len();
-        let depth = std::cmp::min(chain_len, self.flatten_depth);
+        let depth = std::cmp::min(chain_len, flatten_depth);
         if depth < 2 {
             return None;
         }
-        if !segments[..depth]
+        let identifiers = segments[..depth]
             .iter()
-            .all(|segment| is_identifier_segment(segment))
-        {
+            .all(|segment| is_identifier_segment(segment));
+        if !identifiers && !self.quote_folded_segments {
             return None;
         }
 
-        let folded = segments[..depth].join(".");
-        if siblings.contains(folded.as_str()) {
+        let separator = self.flatten_separator.as_str();
+        let mut folded = segments[..depth].join(separator);
+        if siblings.contains(folded.as_str()) || self.folds_onto_literal(&folded) {
             return None;
         }
+        if !identifiers {
+            folded.clear();
+            for (idx, segment) in segments[..depth].iter().enumerate() {
+                if idx > 0 {
+                    folded.push_str(separator);
+                }
+                if is_identifier_segment(segment) {
+                    folded.push_str(segment);
+                } else {
+                    folded.push('"');
+                    escape_string_into(&mut folded, segment);
+                    folded.push('"');
+                }
+            }
+        }
 
         let mut folded_value = value;
         for _ in 1..depth {
@@ -464,7 +1107,43 @@ impl Encoder {
             }
         }
 
-        Some((folded, folded_value))
+        Some((folded, folded_value, depth))
+    }
+
+    fn collapse_single_chain<'a>(
+        &self,
+        key: &str,
+        value: &'a Value,
+        siblings: &HashSet<&str>,
+    ) -> Option<(String, &'a Value, usize)> {
+        if !self.collapse_single_chains {
+            return None;
+        }
+        let mut segments = vec![key];
+        let mut cursor = value;
+        while let Value::Object(map) = cursor {
+            match map.len() {
+                0 => break,
+                1 => {
+                    let (next_key, next_val) = map.iter().next()?;
+                    segments.push(next_key.as_str());
+                    cursor = next_val;
+                }
+                _ => return None,
+            }
+        }
+        if segments.len() < 2
+            || !segments
+                .iter()
+                .all(|segment| is_identifier_segment(segment))
+        {
+            return None;
+        }
+        let collapsed = segments.join(self.flatten_separator.as_str());
+        if siblings.contains(collapsed.as_str()) || self.folds_onto_literal(&collapsed) {
+            return None;
+        }
+        Some((collapsed, cursor, segments.len()))
     }
 
     fn encode_array_value(
@@ -472,12 +1151,23 @@ impl Encoder {
         array: &[Value],
         indent_level: usize,
         key: Option<&str>,
+        raw_key: bool,
         prefix: &[u8],
     ) -> Result<()> {
         let delimiter = self.active_delimiter();
-        self.with_array_delimiter(delimiter, |encoder| {
-            encoder.encode_array_value_inner(array, indent_level, key, prefix)
-        })
+        self.comment_array_depth += 1;
+        // Keys inside array items start a new path scope.
+        let fold_path = std::mem::take(&mut self.fold_path);
+        let literal_paths = std::mem::take(&mut self.literal_paths);
+        let folded_segments = std::mem::take(&mut self.folded_segments);
+        let result = self.with_array_delimiter(delimiter, |encoder| {
+            encoder.encode_array_value_inner(array, indent_level, key, raw_key, prefix)
+        });
+        self.fold_path = fold_path;
+        self.literal_paths = literal_paths;
+        self.folded_segments = folded_segments;
+        self.comment_array_depth -= 1;
+        result
     }
 
     fn encode_array_value_inner(
@@ -485,12 +1175,13 @@ impl Encoder {
         array: &[Value],
         indent_level: usize,
         key: Option<&str>,
+        raw_key: bool,
         prefix: &[u8],
     ) -> Result<()> {
         if let Some(fields) = self.tabular_fields(array) {
             self.with_line_buf(|encoder, line| {
                 line.clear();
-                encoder.append_array_header(line, array.len(), key, Some(&fields));
+                encoder.append_array_header(line, array.len(), key, raw_key, Some(&fields));
                 line.push(b':');
                 encoder.write_line_with_prefix_bytes(indent_level, prefix, line);
             });
@@ -506,16 +1197,29 @@ impl Encoder {
                     .iter()
                     .map(|field| SmolStr::new(self.interned_key(*field)))
                     .collect();
+                let marker = self.empty_string_marker.clone();
+                let escape_leading = self.escape_leading_special_chars;
+                let limits = self.cache_limits;
+                let formatter = self.number_formatter.clone();
                 let results: Vec<Result<RowBuf>> = array
                     .par_iter()
                     .map_init(
-                        || RowEncoder::new(delimiter_char),
+                        || {
+                            RowEncoder::new(
+                                delimiter_char,
+                                formatter.clone(),
+                                limits,
+                                marker.clone(),
+                                escape_leading,
+                            )
+                        },
                         |encoder, item| encoder.encode_tabular_row(item, &field_names),
                     )
                     .collect();
                 for result in results {
                     let row = result?;
                     self.write_line_bytes(row_indent, &row);
+                    self.end_entry()?;
                 }
                 return Ok(());
             }
@@ -527,6 +1231,7 @@ impl Encoder {
             self.with_out_buf(|encoder, out| -> Result<()> {
                 encoder.reset_tabular_last_values(field_names.len());
                 for item in array {
+                    encoder.end_entry_into(out)?;
                     let obj = item
                         .as_object()
                         .ok_or_else(|| Error::encode("tabular row is not an object"))?;
@@ -538,12 +1243,26 @@ impl Encoder {
                     let value = obj
                         .get(first_field.as_str())
                         .ok_or_else(|| Error::encode("tabular row missing field"))?;
-                    encoder.append_scalar_tabular(out, value, delimiter_char)?;
+                    if let Some(marker) = encoder.empty_cell_marker(value) {
+                        out.extend_from_slice(marker.as_bytes());
+                    } else {
+                        encoder.append_scalar_tabular(out, value, delimiter_char)?;
+                    }
                     for (idx, field) in iter {
                         let value = obj
                             .get(field.as_str())
                             .ok_or_else(|| Error::encode("tabular row missing field"))?;
-                        encoder.append_scalar_tabular_prefixed(out, value, delimiter_char, idx)?;
+                        if let Some(marker) = encoder.empty_cell_marker(value) {
+                            out.push(delimiter_char as u8);
+                            out.extend_from_slice(marker.as_bytes());
+                        } else {
+                            encoder.append_scalar_tabular_prefixed(
+                                out,
+                                value,
+                                delimiter_char,
+                                idx,
+                            )?;
+                        }
                     }
                 }
                 Ok(())
@@ -551,15 +1270,41 @@ impl Encoder {
             return Ok(());
         }
 
-        if array.iter().all(is_scalar) {
+        if let Some(columns) = self.matrix_columns(array) {
+            self.with_line_buf(|encoder, line| {
+                line.clear();
+                encoder.append_matrix_header(line, array.len(), columns, key, raw_key);
+                line.push(b':');
+                encoder.write_line_with_prefix_bytes(indent_level, prefix, line);
+            });
+            let mut row_indent = indent_level + 1;
+            if prefix == b"- " && key.is_some() {
+                row_indent += 1;
+            }
+            for row in array.iter().filter_map(Value::as_array) {
+                self.with_line_buf(|encoder, line| -> Result<()> {
+                    line.clear();
+                    encoder.append_inline_scalars(line, row)?;
+                    encoder.write_line_bytes(row_indent, line);
+                    Ok(())
+                })?;
+                self.end_entry()?;
+            }
+            return Ok(());
+        }
+
+        if array.len() <= self.inline_array_max
+            && array.iter().all(is_scalar)
+            && !(self.single_element_list && array.len() == 1)
+        {
             self.reserve_inline_array(array.len());
             self.with_line_buf(|encoder, line| -> Result<()> {
                 line.clear();
-                encoder.append_array_header(line, array.len(), key, None);
+                encoder.append_array_header(line, array.len(), key, raw_key, None);
                 if array.is_empty() {
                     line.push(b':');
                 } else {
-                    line.extend_from_slice(b": ");
+                    line.extend_from_slice(encoder.colon_separator);
                     encoder.append_inline_scalars(line, array)?;
                 }
                 encoder.write_line_with_prefix_bytes(indent_level, prefix, line);
@@ -570,7 +1315,7 @@ impl Encoder {
 
         self.with_line_buf(|encoder, line| {
             line.clear();
-            encoder.append_array_header(line, array.len(), key, None);
+            encoder.append_array_header(line, array.len(), key, raw_key, None);
             line.push(b':');
             encoder.write_line_with_prefix_bytes(indent_level, prefix, line);
         });
@@ -578,15 +1323,48 @@ impl Encoder {
         if prefix == b"- " && key.is_some() {
             item_indent += 1;
         }
+        #[cfg(feature = "parallel")]
+        if self.should_parallel_list(array.len()) {
+            let settings = self.fork_settings();
+            let results: Vec<Result<Vec<u8>>> = array
+                .par_iter()
+                .map_init(
+                    || settings.encoder(),
+                    |encoder, item| {
+                        encoder.out.clear();
+                        encoder.encode_list_item(item, item_indent)?;
+                        Ok(encoder.out.clone())
+                    },
+                )
+                .collect();
+            for result in results {
+                let item = result?;
+                if self.has_output(&self.out) {
+                    self.out.push(b'\n');
+                }
+                self.out.extend_from_slice(&item);
+                self.end_entry()?;
+            }
+            return Ok(());
+        }
         for item in array {
             self.encode_list_item(item, item_indent)?;
+            self.end_entry()?;
         }
         Ok(())
     }
 
     fn encode_list_item(&mut self, value: &Value, indent_level: usize) -> Result<()> {
         match value {
-            Value::Array(array) => self.encode_array_value(array, indent_level, None, b"- "),
+            Value::Array(array) => self.encode_array_value(array, indent_level, None, false, b"- "),
+            Value::Object(map) if self.fits_small_inline(map) => {
+                self.with_line_buf(|encoder, line| -> Result<()> {
+                    line.clear();
+                    encoder.append_inline_object(line, map)?;
+                    encoder.write_line_with_prefix_bytes(indent_level, b"- ", line);
+                    Ok(())
+                })
+            }
             Value::Object(map) => self.encode_object_item(map, indent_level),
             _ => self.with_line_buf(|encoder, line| -> Result<()> {
                 line.clear();
@@ -602,30 +1380,60 @@ impl Encoder {
         map: &serde_json::Map<String, Value>,
         indent_level: usize,
     ) -> Result<()> {
-        let mut iter = map.iter();
-        let Some((first_key, first_value)) = iter.next() else {
+        if self.key_order.is_empty() && self.key_ordering == KeyOrder::Preserve {
+            self.encode_object_item_entries(map, map.iter(), indent_level)
+        } else {
+            let entries = self.ordered_entries(map);
+            self.encode_object_item_entries(map, entries.into_iter(), indent_level)
+        }
+    }
+
+    /// Writes the first entry on the `- ` line and the rest as fields of the
+    /// item, one level deeper.
+    fn encode_object_item_entries<'a>(
+        &mut self,
+        map: &'a serde_json::Map<String, Value>,
+        mut entries: impl Iterator<Item = (&'a String, &'a Value)>,
+        indent_level: usize,
+    ) -> Result<()> {
+        let first = entries.find(|(key, value)| !self.take_comment_entry(key, value));
+        // Comments before the first field go above the `- ` line.
+        if let Some(text) = self.pending_comment.take() {
+            self.write_comment_text(&text, indent_level);
+        }
+        let Some((first_key, first_value)) = first else {
             self.write_line_with_prefix_bytes(indent_level, b"-", b"");
             return Ok(());
         };
 
         match first_value {
             Value::Array(array) => {
-                self.encode_array_value(array, indent_level, Some(first_key), b"- ")?;
+                self.encode_array_value(array, indent_level, Some(first_key), false, b"- ")?;
+            }
+            Value::Object(nested) if self.fits_inline(nested) => {
+                self.with_line_buf(|encoder, line| -> Result<()> {
+                    line.clear();
+                    encoder.append_encoded_key(line, first_key, false);
+                    line.extend_from_slice(encoder.colon_separator);
+                    encoder.append_inline_object(line, nested)?;
+                    encoder.write_line_with_prefix_bytes(indent_level, b"- ", line);
+                    Ok(())
+                })?;
             }
             Value::Object(nested) => {
                 self.with_line_buf(|encoder, line| {
                     line.clear();
-                    encoder.append_encoded_key(line, first_key);
+                    encoder.append_encoded_key(line, first_key, false);
                     line.push(b':');
                     encoder.write_line_with_prefix_bytes(indent_level, b"- ", line);
                 });
-                self.encode_object(nested, indent_level + 1)?;
+                self.encode_object(nested, indent_level + 2)?;
             }
             _ => {
                 self.with_line_buf(|encoder, line| -> Result<()> {
                     line.clear();
-                    encoder.append_encoded_key(line, first_key);
-                    line.extend_from_slice(b": ");
+                    encoder.append_encoded_key(line, first_key, false);
+                    line.extend_from_slice(encoder.colon_separator);
                     encoder.append_scalar_document(line, first_value)?;
                     encoder.write_line_with_prefix_bytes(indent_level, b"- ", line);
                     Ok(())
@@ -633,8 +1441,19 @@ impl Encoder {
             }
         }
 
-        for (key, value) in iter {
-            self.encode_object_entry(key, value, indent_level + 1)?;
+        let mut siblings = HashSet::with_capacity(map.len());
+        for key in map.keys() {
+            siblings.insert(key.as_str());
+        }
+        for (key, value) in entries {
+            if self.take_comment_entry(key, value) {
+                continue;
+            }
+            self.encode_object_field(key, value, &siblings, indent_level + 1)?;
+            self.end_entry()?;
+        }
+        if let Some(text) = self.pending_comment.take() {
+            self.write_comment_text(&text, indent_level + 1);
         }
         Ok(())
     }
@@ -659,7 +1478,7 @@ impl Encoder {
                 Ok(())
             }
             Value::Number(number) => {
-                append_json_number_bytes(buf, number);
+                append_number_bytes(buf, number, self.number_formatter.as_ref(), delimiter);
                 Ok(())
             }
             Value::String(value) => {
@@ -705,17 +1524,20 @@ impl Encoder {
                         return Ok(());
                     }
                     let start = buf.len();
-                    append_json_number_bytes(buf, number);
+                    append_number_bytes(buf, number, self.number_formatter.as_ref(), delimiter);
                     let len = buf.len() - start;
-                    if len <= NUMBER_CACHE_MAX_LEN
-                        && self.tabular_number_cache.len() < TABULAR_NUMBER_CACHE_MAX_ITEMS
+                    // Formatter output may be quoted for one delimiter and bare
+                    // for another, so only the per-delimiter cache keeps it.
+                    if self.number_formatter.is_none()
+                        && len <= NUMBER_CACHE_MAX_LEN
+                        && self.tabular_number_cache.len() < self.cache_limits.tabular_numbers
                     {
                         self.tabular_number_cache
                             .insert(key, buf.as_slice()[start..].to_vec());
                     }
                     return Ok(());
                 }
-                append_json_number_bytes(buf, number);
+                append_number_bytes(buf, number, self.number_formatter.as_ref(), delimiter);
                 Ok(())
             }
             Value::String(value) => {
@@ -733,8 +1555,8 @@ impl Encoder {
                     let cache = self
                         .tabular_string_cache
                         .entry(delimiter)
-                        .or_insert_with(|| HashMap::with_capacity(TABULAR_STRING_CACHE_MAX_ITEMS));
-                    if cache.len() < TABULAR_STRING_CACHE_MAX_ITEMS {
+                        .or_insert_with(|| cache_map(self.cache_limits.tabular_strings));
+                    if cache.len() < self.cache_limits.tabular_strings {
                         cache.insert(SmolStr::new(value), buf.as_slice()[start..].to_vec());
                     }
                     return Ok(());
@@ -742,6 +1564,10 @@ impl Encoder {
                 self.append_string(buf, value, delimiter);
                 Ok(())
             }
+            Value::Array(_) | Value::Object(_) if is_empty_container(value) => {
+                buf.extend_bytes(empty_container_text(value));
+                Ok(())
+            }
             _ => Err(Error::encode("non-scalar value in scalar position")),
         }
     }
@@ -772,7 +1598,7 @@ impl Encoder {
             Value::Number(number) => {
                 let Some(key) = number_cache_key(number) else {
                     buf.push_byte(delimiter_byte);
-                    append_json_number_bytes(buf, number);
+                    append_number_bytes(buf, number, self.number_formatter.as_ref(), delimiter);
                     return Ok(());
                 };
                 if let Some(last) = self.tabular_last_values.get(column) {
@@ -794,25 +1620,22 @@ impl Encoder {
                     let cache = self
                         .tabular_prefixed_number_cache
                         .entry(delimiter)
-                        .or_insert_with(|| {
-                            HashMap::with_capacity(TABULAR_PREFIXED_CACHE_MAX_ITEMS)
-                        });
-                    if cache.len() < TABULAR_PREFIXED_CACHE_MAX_ITEMS {
+                        .or_insert_with(|| cache_map(self.cache_limits.tabular_prefixed));
+                    if cache.len() < self.cache_limits.tabular_prefixed {
                         cache
                             .entry(key)
                             .or_insert_with(|| buf.as_slice()[start..].to_vec());
                     }
                 } else {
                     buf.push_byte(delimiter_byte);
-                    append_json_number_bytes(buf, number);
+                    append_number_bytes(buf, number, self.number_formatter.as_ref(), delimiter);
                     let len = buf.len() - start - 1;
                     let cache = self
                         .tabular_prefixed_number_cache
                         .entry(delimiter)
-                        .or_insert_with(|| {
-                            HashMap::with_capacity(TABULAR_PREFIXED_CACHE_MAX_ITEMS)
-                        });
-                    if len <= NUMBER_CACHE_MAX_LEN && cache.len() < TABULAR_PREFIXED_CACHE_MAX_ITEMS
+                        .or_insert_with(|| cache_map(self.cache_limits.tabular_prefixed));
+                    if len <= NUMBER_CACHE_MAX_LEN
+                        && cache.len() < self.cache_limits.tabular_prefixed
                     {
                         cache.insert(key, buf.as_slice()[start..].to_vec());
                     }
@@ -848,10 +1671,8 @@ impl Encoder {
                         let cache = self
                             .tabular_prefixed_string_cache
                             .entry(delimiter)
-                            .or_insert_with(|| {
-                                HashMap::with_capacity(TABULAR_PREFIXED_CACHE_MAX_ITEMS)
-                            });
-                        if cache.len() < TABULAR_PREFIXED_CACHE_MAX_ITEMS {
+                            .or_insert_with(|| cache_map(self.cache_limits.tabular_prefixed));
+                        if cache.len() < self.cache_limits.tabular_prefixed {
                             cache
                                 .entry(SmolStr::new(value))
                                 .or_insert_with(|| buf.as_slice()[start..].to_vec());
@@ -864,10 +1685,8 @@ impl Encoder {
                     let cache = self
                         .tabular_prefixed_string_cache
                         .entry(delimiter)
-                        .or_insert_with(|| {
-                            HashMap::with_capacity(TABULAR_PREFIXED_CACHE_MAX_ITEMS)
-                        });
-                    if cache.len() < TABULAR_PREFIXED_CACHE_MAX_ITEMS {
+                        .or_insert_with(|| cache_map(self.cache_limits.tabular_prefixed));
+                    if cache.len() < self.cache_limits.tabular_prefixed {
                         cache.insert(SmolStr::new(value), buf.as_slice()[start..].to_vec());
                     }
                     self.update_tabular_last_string(column, value, &buf.as_slice()[start..]);
@@ -878,10 +1697,22 @@ impl Encoder {
                 self.update_tabular_last_string(column, value, &buf.as_slice()[start..]);
                 Ok(())
             }
+            Value::Array(_) | Value::Object(_) if is_empty_container(value) => {
+                buf.push_byte(delimiter_byte);
+                buf.extend_bytes(empty_container_text(value));
+                Ok(())
+            }
             _ => Err(Error::encode("non-scalar value in scalar position")),
         }
     }
 
+    fn empty_cell_marker(&self, value: &Value) -> Option<&str> {
+        match value {
+            Value::String(value) if value.is_empty() => self.empty_string_marker.as_deref(),
+            _ => None,
+        }
+    }
+
     fn reset_tabular_last_values(&mut self, columns: usize) {
         if self.tabular_last_values.len() < columns {
             self.tabular_last_values
@@ -911,11 +1742,16 @@ impl Encoder {
     }
 
     fn append_string<B: ByteSink>(&mut self, buf: &mut B, value: &str, delimiter: char) {
-        if is_canonical_unquoted_key(value) && !matches!(value, "true" | "false" | "null") {
+        if is_canonical_unquoted_key(value)
+            && !matches!(value, "true" | "false" | "null")
+            && !is_non_finite_literal(value)
+        {
             buf.extend_bytes(value.as_bytes());
             return;
         }
         let (needs_quote, needs_escape) = self.analyze_string_cached(value, delimiter);
+        let needs_quote =
+            needs_quote || (self.escape_leading_special_chars && value.starts_with('#'));
         if !needs_quote {
             buf.extend_bytes(value.as_bytes());
             return;
@@ -936,19 +1772,21 @@ impl Encoder {
         let cache = self
             .string_cache
             .entry(delimiter)
-            .or_insert_with(|| HashMap::with_capacity(STRING_CACHE_MAX_ITEMS));
+            .or_insert_with(|| cache_map(self.cache_limits.strings));
         if let Some(flags) = cache.get(value) {
             return *flags;
         }
         let flags = analyze_string(value, delimiter);
-        if cache.len() < STRING_CACHE_MAX_ITEMS {
+        if cache.len() < self.cache_limits.strings {
             cache.insert(SmolStr::new(value), flags);
         }
         flags
     }
 
-    fn append_encoded_key(&mut self, buf: &mut Vec<u8>, key: &str) {
-        if is_canonical_unquoted_key(key) {
+    /// Writes `key`, quoting it unless it is canonical or `raw` marks it as
+    /// already encoded (folded keys).
+    fn append_encoded_key(&mut self, buf: &mut Vec<u8>, key: &str, raw: bool) {
+        if raw || is_canonical_unquoted_key(key) {
             buf.extend_from_slice(key.as_bytes());
             return;
         }
@@ -960,7 +1798,7 @@ impl Encoder {
         encoded.push('"');
         escape_string_into(&mut encoded, key);
         encoded.push('"');
-        if key.len() <= KEY_CACHE_MAX_LEN && self.key_cache.len() < KEY_CACHE_MAX_ITEMS {
+        if key.len() <= KEY_CACHE_MAX_LEN && self.key_cache.len() < self.cache_limits.keys {
             let entry = self.key_cache.entry(SmolStr::new(key)).or_insert(encoded);
             buf.extend_from_slice(entry.as_bytes());
             return;
@@ -1003,11 +1841,12 @@ impl Encoder {
         buf: &mut Vec<u8>,
         len: usize,
         key: Option<&str>,
+        raw_key: bool,
         fields: Option<&[usize]>,
     ) {
         let delimiter = self.active_delimiter();
         if let Some(key) = key {
-            self.append_encoded_key(buf, key);
+            self.append_encoded_key(buf, key, raw_key);
         }
         buf.push(b'[');
         let mut num = itoa::Buffer::new();
@@ -1029,6 +1868,29 @@ impl Encoder {
         }
     }
 
+    fn append_matrix_header(
+        &mut self,
+        buf: &mut Vec<u8>,
+        rows: usize,
+        columns: usize,
+        key: Option<&str>,
+        raw_key: bool,
+    ) {
+        let delimiter = self.active_delimiter();
+        if let Some(key) = key {
+            self.append_encoded_key(buf, key, raw_key);
+        }
+        buf.push(b'[');
+        let mut num = itoa::Buffer::new();
+        buf.extend_from_slice(num.format(rows).as_bytes());
+        buf.push(b'x');
+        buf.extend_from_slice(num.format(columns).as_bytes());
+        if delimiter != ',' {
+            buf.push(delimiter as u8);
+        }
+        buf.push(b']');
+    }
+
     fn append_inline_scalars(&mut self, buf: &mut Vec<u8>, array: &[Value]) -> Result<()> {
         for (idx, value) in array.iter().enumerate() {
             if idx > 0 {
@@ -1065,26 +1927,116 @@ impl Encoder {
             && rows.saturating_mul(fields) >= PARALLEL_TABULAR_MIN_CELLS
     }
 
-    fn tabular_fields(&mut self, array: &[Value]) -> Option<Vec<usize>> {
-        let first = array.first()?.as_object()?;
-        if first.is_empty() {
+    // Line limits are checked entry by entry and diagnostics must arrive in
+    // document order, so both keep list items on the serial path.
+    #[cfg(feature = "parallel")]
+    fn should_parallel_list(&self, items: usize) -> bool {
+        items >= PARALLEL_LIST_MIN_ITEMS
+            && self.line_limit == usize::MAX
+            && self.diagnostics_hook.is_none()
+    }
+
+    /// The settings and array state a worker thread needs to encode list
+    /// items. Comments are dropped since they are never written inside arrays.
+    #[cfg(feature = "parallel")]
+    fn fork_settings(&self) -> ForkSettings {
+        ForkSettings {
+            document_delimiter: self.document_delimiter,
+            key_folding: self.key_folding,
+            quote_folded_segments: self.quote_folded_segments,
+            collapse_single_chains: self.collapse_single_chains,
+            comment_array_depth: self.comment_array_depth,
+            comment_key: self.comment_key.clone(),
+            max_tabular_columns: self.max_tabular_columns,
+            single_element_list: self.single_element_list,
+            inline_array_max: self.inline_array_max,
+            empty_containers_in_tabular: self.empty_containers_in_tabular,
+            escape_leading_special_chars: self.escape_leading_special_chars,
+            flatten_depth: self.flatten_depth,
+            flatten_separator: self.flatten_separator.clone(),
+            inline_depth: self.inline_depth,
+            inline_small_objects: self.inline_small_objects,
+            line_limit: self.line_limit,
+            line_limit_tail: self.line_limit_tail,
+            section_spacing: self.section_spacing,
+            matrix_tabular: self.matrix_tabular,
+            cache_limits: self.cache_limits,
+            number_formatter: self.number_formatter.clone(),
+            diagnostics_hook: self.diagnostics_hook.clone(),
+            key_order: self.key_order.clone(),
+            key_ordering: self.key_ordering,
+            indent_first_level: self.indent_first_level,
+            empty_string_marker: self.empty_string_marker.clone(),
+            colon_separator: self.colon_separator,
+            indent_unit: self.indent_unit.clone(),
+            indent_cache: self.indent_cache.clone(),
+            delimiter_stack: self.delimiter_stack.clone(),
+        }
+    }
+
+    /// Row width when `array` is a non-empty list of equal-length, non-empty
+    /// scalar arrays and matrix output is enabled.
+    fn matrix_columns(&self, array: &[Value]) -> Option<usize> {
+        if !self.matrix_tabular {
             return None;
         }
-        let fields: Vec<usize> = first.keys().map(|key| self.intern_key_id(key)).collect();
-        for item in array {
-            let row = item.as_object()?;
-            if row.len() != fields.len() {
+        let columns = array.first()?.as_array()?.len();
+        if columns == 0 {
+            return None;
+        }
+        array
+            .iter()
+            .all(|row| {
+                row.as_array()
+                    .is_some_and(|row| row.len() == columns && row.iter().all(is_scalar))
+            })
+            .then_some(columns)
+    }
+
+    fn tabular_fields(&mut self, array: &[Value]) -> Option<Vec<usize>> {
+        // Rows carrying comments need their own lines.
+        if let Some(comment_key) = self.comment_key.as_deref() {
+            if array
+                .iter()
+                .any(|row| matches!(row.get(comment_key), Some(Value::String(_))))
+            {
                 return None;
             }
-            for field in &fields {
-                let key = self.interned_key(*field);
-                let value = row.get(key)?;
-                if !is_scalar(value) {
-                    return None;
+        }
+        match tabular_header(array, self.empty_containers_in_tabular) {
+            Ok(fields) if fields.len() > self.max_tabular_columns => {
+                self.report(Diagnostic::TooManyColumns {
+                    columns: fields.len(),
+                    max: self.max_tabular_columns,
+                });
+                None
+            }
+            Ok(fields) if self.key_order.is_empty() && self.key_ordering == KeyOrder::Preserve => {
+                Some(fields.iter().map(|key| self.intern_key_id(key)).collect())
+            }
+            Ok(_) => {
+                // Every row has the first row's keys, so its order is the header's.
+                let first = array.first()?.as_object()?;
+                let fields: Vec<&str> = self
+                    .ordered_entries(first)
+                    .into_iter()
+                    .map(|(key, _)| key.as_str())
+                    .collect();
+                Some(fields.iter().map(|key| self.intern_key_id(key)).collect())
+            }
+            Err(diagnostic) => {
+                if let Some(diagnostic) = diagnostic {
+                    self.report(diagnostic);
                 }
+                None
             }
         }
-        Some(fields)
+    }
+
+    fn report(&self, diagnostic: Diagnostic) {
+        if let Some(hook) = &self.diagnostics_hook {
+            hook.report(diagnostic);
+        }
     }
 
     fn write_line_bytes(&mut self, indent_level: usize, content: &[u8]) {
@@ -1092,9 +2044,13 @@ impl Encoder {
     }
 
     fn begin_line_with_prefix(&mut self, indent_level: usize, prefix: &[u8]) {
-        if !self.out.is_empty() {
+        if self.has_output(&self.out) {
             self.out.push(b'\n');
         }
+        if self.indent_first_level > 0 {
+            self.out
+                .resize(self.out.len() + self.indent_first_level, b' ');
+        }
         if indent_level > 0 && !self.indent_unit.is_empty() {
             self.ensure_indent_cache(indent_level);
             let indent = &self.indent_cache[indent_level];
@@ -1109,9 +2065,12 @@ impl Encoder {
         indent_level: usize,
         prefix: &[u8],
     ) {
-        if !out.is_empty() {
+        if self.has_output(out) {
             out.push(b'\n');
         }
+        if self.indent_first_level > 0 {
+            out.resize(out.len() + self.indent_first_level, b' ');
+        }
         if indent_level > 0 && !self.indent_unit.is_empty() {
             self.ensure_indent_cache(indent_level);
             let indent = &self.indent_cache[indent_level];
@@ -1188,6 +2147,260 @@ impl Encoder {
     }
 }
 
+fn collapse_kv_arrays(value: &Value, key_field: &str, value_field: &str) -> Value {
+    match value {
+        Value::Array(array) => {
+            if let Some(map) = kv_array_as_object(array, key_field, value_field) {
+                return Value::Object(map);
+            }
+            Value::Array(
+                array
+                    .iter()
+                    .map(|item| collapse_kv_arrays(item, key_field, value_field))
+                    .collect(),
+            )
+        }
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, item)| {
+                    (
+                        key.clone(),
+                        collapse_kv_arrays(item, key_field, value_field),
+                    )
+                })
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+fn sort_scalar_arrays(value: &mut Value) {
+    match value {
+        Value::Array(array) if array.iter().all(is_scalar) => {
+            array.sort_by(compare_scalars);
+        }
+        Value::Array(array) => array.iter_mut().for_each(sort_scalar_arrays),
+        Value::Object(map) => map.values_mut().for_each(sort_scalar_arrays),
+        _ => {}
+    }
+}
+
+/// Drops null-valued object fields, except in arrays that stay tabular: their
+/// rows need every header field, and a `null` cell is cheaper than list form.
+fn strip_null_fields(value: &mut Value, options: &EncodeOptions) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|_, value| !value.is_null());
+            map.values_mut()
+                .for_each(|value| strip_null_fields(value, options));
+        }
+        Value::Array(array) => {
+            let tabular = tabular_header(array, options.empty_containers_in_tabular)
+                .is_ok_and(|fields| fields.len() <= options.max_tabular_columns);
+            if !tabular {
+                array
+                    .iter_mut()
+                    .for_each(|value| strip_null_fields(value, options));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Rewrites `[{w: {a: 1}}, {w: {a: 2}}]` to `[{"w.a": 1}, {"w.a": 2}]` when the
+/// inner objects form a tabular array and every segment expands back safely.
+fn unwrap_single_key_rows(value: &mut Value, options: &EncodeOptions) {
+    match value {
+        Value::Object(map) => map
+            .values_mut()
+            .for_each(|value| unwrap_single_key_rows(value, options)),
+        Value::Array(array) => {
+            array
+                .iter_mut()
+                .for_each(|value| unwrap_single_key_rows(value, options));
+            let Some(wrapper) = single_key_wrapper(array) else {
+                return;
+            };
+            let inner: Vec<Value> = array
+                .iter()
+                .filter_map(|row| row.as_object()?.values().next().cloned())
+                .collect();
+            let tabular = tabular_header(&inner, options.empty_containers_in_tabular)
+                .is_ok_and(|fields| fields.iter().all(|field| is_identifier_segment(field)));
+            if !tabular {
+                return;
+            }
+            *array = inner
+                .into_iter()
+                .map(|row| match row {
+                    Value::Object(row) => Value::Object(
+                        row.into_iter()
+                            .map(|(field, cell)| (format!("{wrapper}.{field}"), cell))
+                            .collect(),
+                    ),
+                    other => other,
+                })
+                .collect();
+        }
+        _ => {}
+    }
+}
+
+fn single_key_wrapper(array: &[Value]) -> Option<String> {
+    let mut wrapper: Option<&str> = None;
+    for row in array {
+        let row = row.as_object().filter(|row| row.len() == 1)?;
+        let (key, inner) = row.iter().next()?;
+        if !inner.is_object() || wrapper.is_some_and(|wrapper| wrapper != key) {
+            return None;
+        }
+        wrapper = Some(key);
+    }
+    wrapper
+        .filter(|wrapper| is_identifier_segment(wrapper))
+        .map(str::to_string)
+}
+
+/// Orders `null < false < true < numbers < strings`; numbers compare by value
+/// (ties broken by their text, so large integers stay total), strings by bytes.
+fn compare_scalars(left: &Value, right: &Value) -> Ordering {
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Null => 0,
+            Value::Bool(_) => 1,
+            Value::Number(_) => 2,
+            _ => 3,
+        }
+    }
+    match (left, right) {
+        (Value::Bool(left), Value::Bool(right)) => left.cmp(right),
+        (Value::Number(left), Value::Number(right)) => {
+            let by_value = left
+                .as_f64()
+                .zip(right.as_f64())
+                .and_then(|(left, right)| left.partial_cmp(&right))
+                .unwrap_or(Ordering::Equal);
+            by_value.then_with(|| left.to_string().cmp(&right.to_string()))
+        }
+        (Value::String(left), Value::String(right)) => left.cmp(right),
+        _ => rank(left).cmp(&rank(right)),
+    }
+}
+
+fn kv_array_as_object(
+    array: &[Value],
+    key_field: &str,
+    value_field: &str,
+) -> Option<serde_json::Map<String, Value>> {
+    if array.is_empty() {
+        return None;
+    }
+    let mut map = serde_json::Map::with_capacity(array.len());
+    for item in array {
+        let entry = item.as_object()?;
+        if entry.len() != 2 {
+            return None;
+        }
+        let key = entry.get(key_field)?.as_str()?;
+        let value = entry.get(value_field)?;
+        if map.contains_key(key) {
+            return None;
+        }
+        map.insert(
+            key.to_string(),
+            collapse_kv_arrays(value, key_field, value_field),
+        );
+    }
+    Some(map)
+}
+
+/// Whether strings starting with `#` are quoted: on request, and whenever the
+/// output carries `#` lines that readers must tell apart from values.
+fn escape_leading_hash(options: &EncodeOptions) -> bool {
+    options.escape_leading_special_chars
+        || options.checksum
+        || options.comment_key.is_some()
+        || !options.comments.is_empty()
+        || (options.line_limit.is_some() && options.line_limit_policy == LineLimitPolicy::Truncate)
+}
+
+fn line_limit_tail(options: &EncodeOptions) -> usize {
+    let checksum = if options.checksum {
+        1 + CHECKSUM_PREFIX.len() + 8
+    } else {
+        0
+    };
+    checksum + usize::from(options.trailing_newline)
+}
+
+fn colon_separator(spacing: ColonSpacing) -> &'static [u8] {
+    match spacing {
+        ColonSpacing::Single => b": ",
+        ColonSpacing::None => b":",
+    }
+}
+
+fn inline_object_depth(map: &serde_json::Map<String, Value>) -> Option<usize> {
+    if map.is_empty() {
+        return None;
+    }
+    let mut depth = 1;
+    for value in map.values() {
+        match value {
+            Value::Array(_) => return None,
+            Value::Object(inner) => depth = depth.max(inline_object_depth(inner)? + 1),
+            _ => {}
+        }
+    }
+    Some(depth)
+}
+
+/// Cells the tabular header only admits with `empty_containers_in_tabular`.
+fn is_empty_container(value: &Value) -> bool {
+    match value {
+        Value::Array(items) => items.is_empty(),
+        Value::Object(map) => map.is_empty(),
+        _ => false,
+    }
+}
+
+fn empty_container_text(value: &Value) -> &'static [u8] {
+    if value.is_array() {
+        b"[]"
+    } else {
+        b"{}"
+    }
+}
+
+/// Counts the strings in `value`, stopping once `limit` is reached.
+fn count_strings(value: &Value, limit: usize) -> usize {
+    fn walk(value: &Value, limit: usize, count: &mut usize) {
+        match value {
+            Value::String(_) => *count += 1,
+            Value::Array(items) => {
+                for item in items {
+                    if *count >= limit {
+                        return;
+                    }
+                    walk(item, limit, count);
+                }
+            }
+            Value::Object(map) => {
+                for value in map.values() {
+                    if *count >= limit {
+                        return;
+                    }
+                    walk(value, limit, count);
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut count = 0;
+    walk(value, limit, &mut count);
+    count
+}
+
 fn is_scalar(value: &Value) -> bool {
     matches!(
         value,
@@ -1195,26 +2408,136 @@ fn is_scalar(value: &Value) -> bool {
     )
 }
 
+/// Plain settings shared with rayon's per-thread `map_init`, which builds a
+/// sink-less encoder with empty caches from them.
+#[cfg(feature = "parallel")]
+struct ForkSettings {
+    document_delimiter: char,
+    key_folding: bool,
+    quote_folded_segments: bool,
+    collapse_single_chains: bool,
+    comment_array_depth: usize,
+    comment_key: Option<String>,
+    max_tabular_columns: usize,
+    single_element_list: bool,
+    inline_array_max: usize,
+    empty_containers_in_tabular: bool,
+    escape_leading_special_chars: bool,
+    flatten_depth: usize,
+    flatten_separator: String,
+    inline_depth: usize,
+    inline_small_objects: usize,
+    line_limit: usize,
+    line_limit_tail: usize,
+    section_spacing: bool,
+    matrix_tabular: bool,
+    cache_limits: CacheLimits,
+    number_formatter: Option<NumberFormatter>,
+    diagnostics_hook: Option<DiagnosticsHook>,
+    key_order: Vec<String>,
+    key_ordering: KeyOrder,
+    indent_first_level: usize,
+    empty_string_marker: Option<String>,
+    colon_separator: &'static [u8],
+    indent_unit: Vec<u8>,
+    indent_cache: Vec<Vec<u8>>,
+    delimiter_stack: Vec<char>,
+}
+
+#[cfg(feature = "parallel")]
+impl ForkSettings {
+    fn encoder(&self) -> Encoder<'static> {
+        Encoder {
+            document_delimiter: self.document_delimiter,
+            key_folding: self.key_folding,
+            quote_folded_segments: self.quote_folded_segments,
+            collapse_single_chains: self.collapse_single_chains,
+            fold_path: String::new(),
+            literal_paths: Vec::new(),
+            folded_segments: 0,
+            comments: Vec::new(),
+            comment_path: String::new(),
+            comment_array_depth: self.comment_array_depth,
+            comment_key: self.comment_key.clone(),
+            pending_comment: None,
+            max_tabular_columns: self.max_tabular_columns,
+            single_element_list: self.single_element_list,
+            inline_array_max: self.inline_array_max,
+            empty_containers_in_tabular: self.empty_containers_in_tabular,
+            escape_leading_special_chars: self.escape_leading_special_chars,
+            flatten_depth: self.flatten_depth,
+            flatten_separator: self.flatten_separator.clone(),
+            inline_depth: self.inline_depth,
+            inline_small_objects: self.inline_small_objects,
+            line_limit: self.line_limit,
+            line_limit_tail: self.line_limit_tail,
+            line_limit_hit: false,
+            section_spacing: self.section_spacing,
+            matrix_tabular: self.matrix_tabular,
+            cache_limits: self.cache_limits,
+            number_formatter: self.number_formatter.clone(),
+            diagnostics_hook: self.diagnostics_hook.clone(),
+            key_order: self.key_order.clone(),
+            key_ordering: self.key_ordering,
+            indent_first_level: self.indent_first_level,
+            empty_string_marker: self.empty_string_marker.clone(),
+            colon_separator: self.colon_separator,
+            indent_unit: self.indent_unit.clone(),
+            indent_cache: self.indent_cache.clone(),
+            delimiter_stack: self.delimiter_stack.clone(),
+            string_cache: cache_map(4),
+            key_cache: cache_map(self.cache_limits.keys),
+            tabular_string_cache: cache_map(4),
+            tabular_prefixed_string_cache: cache_map(4),
+            tabular_number_cache: cache_map(self.cache_limits.tabular_numbers),
+            tabular_prefixed_number_cache: cache_map(4),
+            tabular_last_values: Vec::new(),
+            key_intern: cache_map(0),
+            interned_keys: Vec::new(),
+            line_buf: Vec::with_capacity(128),
+            out: Vec::with_capacity(128),
+            sink: None,
+            chunk_bytes: 0,
+            drained: 0,
+            sink_crc: None,
+        }
+    }
+}
+
 #[cfg(feature = "parallel")]
 struct RowEncoder {
     delimiter: char,
-    string_cache: HashMap<SmolStr, (bool, bool)>,
-    string_encoded_cache: HashMap<SmolStr, Vec<u8>>,
-    string_prefixed_cache: HashMap<SmolStr, Vec<u8>>,
-    number_encoded_cache: HashMap<NumberKey, Vec<u8>>,
-    number_prefixed_cache: HashMap<NumberKey, Vec<u8>>,
+    number_formatter: Option<NumberFormatter>,
+    cache_limits: CacheLimits,
+    empty_string_marker: Option<String>,
+    escape_leading_special_chars: bool,
+    string_cache: CacheMap<SmolStr, (bool, bool)>,
+    string_encoded_cache: CacheMap<SmolStr, Vec<u8>>,
+    string_prefixed_cache: CacheMap<SmolStr, Vec<u8>>,
+    number_encoded_cache: CacheMap<NumberKey, Vec<u8>>,
+    number_prefixed_cache: CacheMap<NumberKey, Vec<u8>>,
 }
 
 #[cfg(feature = "parallel")]
 impl RowEncoder {
-    fn new(delimiter: char) -> Self {
+    fn new(
+        delimiter: char,
+        number_formatter: Option<NumberFormatter>,
+        cache_limits: CacheLimits,
+        empty_string_marker: Option<String>,
+        escape_leading_special_chars: bool,
+    ) -> Self {
         Self {
             delimiter,
-            string_cache: HashMap::with_capacity(STRING_CACHE_MAX_ITEMS),
-            string_encoded_cache: HashMap::with_capacity(TABULAR_STRING_CACHE_MAX_ITEMS),
-            string_prefixed_cache: HashMap::with_capacity(TABULAR_PREFIXED_CACHE_MAX_ITEMS),
-            number_encoded_cache: HashMap::with_capacity(TABULAR_NUMBER_CACHE_MAX_ITEMS),
-            number_prefixed_cache: HashMap::with_capacity(TABULAR_PREFIXED_CACHE_MAX_ITEMS),
+            number_formatter,
+            cache_limits,
+            empty_string_marker,
+            escape_leading_special_chars,
+            string_cache: cache_map(cache_limits.strings),
+            string_encoded_cache: cache_map(cache_limits.tabular_strings),
+            string_prefixed_cache: cache_map(cache_limits.tabular_prefixed),
+            number_encoded_cache: cache_map(cache_limits.tabular_numbers),
+            number_prefixed_cache: cache_map(cache_limits.tabular_prefixed),
         }
     }
 
@@ -1232,16 +2555,32 @@ impl RowEncoder {
         let value = obj
             .get(first_field.as_str())
             .ok_or_else(|| Error::encode("tabular row missing field"))?;
-        self.append_scalar(&mut row, value)?;
+        if let Some(marker) = self.empty_cell_marker(value) {
+            row.extend_from_slice(marker.as_bytes());
+        } else {
+            self.append_scalar(&mut row, value)?;
+        }
         for field in iter {
             let value = obj
                 .get(field.as_str())
                 .ok_or_else(|| Error::encode("tabular row missing field"))?;
-            self.append_scalar_prefixed(&mut row, value)?;
+            if let Some(marker) = self.empty_cell_marker(value) {
+                row.push(self.delimiter as u8);
+                row.extend_from_slice(marker.as_bytes());
+            } else {
+                self.append_scalar_prefixed(&mut row, value)?;
+            }
         }
         Ok(row)
     }
 
+    fn empty_cell_marker(&self, value: &Value) -> Option<&str> {
+        match value {
+            Value::String(value) if value.is_empty() => self.empty_string_marker.as_deref(),
+            _ => None,
+        }
+    }
+
     fn append_scalar(&mut self, buf: &mut RowBuf, value: &Value) -> Result<()> {
         match value {
             Value::Null => {
@@ -1265,7 +2604,7 @@ impl RowEncoder {
                     }
                     let start = buf.len();
                     self.append_string(buf, value);
-                    if self.string_encoded_cache.len() < TABULAR_STRING_CACHE_MAX_ITEMS {
+                    if self.string_encoded_cache.len() < self.cache_limits.tabular_strings {
                         self.string_encoded_cache
                             .insert(SmolStr::new(value), buf[start..].to_vec());
                     }
@@ -1274,6 +2613,10 @@ impl RowEncoder {
                 self.append_string(buf, value);
                 Ok(())
             }
+            Value::Array(_) | Value::Object(_) if is_empty_container(value) => {
+                buf.extend_from_slice(empty_container_text(value));
+                Ok(())
+            }
             _ => Err(Error::encode("non-scalar value in scalar position")),
         }
     }
@@ -1306,7 +2649,7 @@ impl RowEncoder {
                         let mut prefixed = Vec::with_capacity(encoded.len() + 1);
                         prefixed.push(delimiter_byte);
                         prefixed.extend_from_slice(encoded);
-                        if self.string_prefixed_cache.len() < TABULAR_PREFIXED_CACHE_MAX_ITEMS {
+                        if self.string_prefixed_cache.len() < self.cache_limits.tabular_prefixed {
                             let entry = self
                                 .string_prefixed_cache
                                 .entry(SmolStr::new(value))
@@ -1320,7 +2663,7 @@ impl RowEncoder {
                     let start = buf.len();
                     buf.push(delimiter_byte);
                     self.append_string(buf, value);
-                    if self.string_prefixed_cache.len() < TABULAR_PREFIXED_CACHE_MAX_ITEMS {
+                    if self.string_prefixed_cache.len() < self.cache_limits.tabular_prefixed {
                         self.string_prefixed_cache
                             .insert(SmolStr::new(value), buf[start..].to_vec());
                     }
@@ -1330,6 +2673,11 @@ impl RowEncoder {
                 self.append_string(buf, value);
                 Ok(())
             }
+            Value::Array(_) | Value::Object(_) if is_empty_container(value) => {
+                buf.push(delimiter_byte);
+                buf.extend_from_slice(empty_container_text(value));
+                Ok(())
+            }
             _ => Err(Error::encode("non-scalar value in scalar position")),
         }
     }
@@ -1341,16 +2689,16 @@ impl RowEncoder {
                 return Ok(());
             }
             let start = buf.len();
-            append_json_number_bytes(buf, number);
+            append_number_bytes(buf, number, self.number_formatter.as_ref(), self.delimiter);
             let len = buf.len() - start;
             if len <= NUMBER_CACHE_MAX_LEN
-                && self.number_encoded_cache.len() < TABULAR_NUMBER_CACHE_MAX_ITEMS
+                && self.number_encoded_cache.len() < self.cache_limits.tabular_numbers
             {
                 self.number_encoded_cache.insert(key, buf[start..].to_vec());
             }
             return Ok(());
         }
-        append_json_number_bytes(buf, number);
+        append_number_bytes(buf, number, self.number_formatter.as_ref(), self.delimiter);
         Ok(())
     }
 
@@ -1369,7 +2717,7 @@ impl RowEncoder {
                 let mut prefixed = Vec::with_capacity(encoded.len() + 1);
                 prefixed.push(delimiter_byte);
                 prefixed.extend_from_slice(encoded);
-                if self.number_prefixed_cache.len() < TABULAR_PREFIXED_CACHE_MAX_ITEMS {
+                if self.number_prefixed_cache.len() < self.cache_limits.tabular_prefixed {
                     let entry = self
                         .number_prefixed_cache
                         .entry(key)
@@ -1382,10 +2730,10 @@ impl RowEncoder {
             }
             let start = buf.len();
             buf.push(delimiter_byte);
-            append_json_number_bytes(buf, number);
+            append_number_bytes(buf, number, self.number_formatter.as_ref(), self.delimiter);
             let len = buf.len() - start - 1;
             if len <= NUMBER_CACHE_MAX_LEN
-                && self.number_prefixed_cache.len() < TABULAR_PREFIXED_CACHE_MAX_ITEMS
+                && self.number_prefixed_cache.len() < self.cache_limits.tabular_prefixed
             {
                 self.number_prefixed_cache
                     .insert(key, buf[start..].to_vec());
@@ -1393,16 +2741,21 @@ impl RowEncoder {
             return Ok(());
         }
         buf.push(delimiter_byte);
-        append_json_number_bytes(buf, number);
+        append_number_bytes(buf, number, self.number_formatter.as_ref(), self.delimiter);
         Ok(())
     }
 
     fn append_string(&mut self, buf: &mut RowBuf, value: &str) {
-        if is_canonical_unquoted_key(value) && !matches!(value, "true" | "false" | "null") {
+        if is_canonical_unquoted_key(value)
+            && !matches!(value, "true" | "false" | "null")
+            && !is_non_finite_literal(value)
+        {
             buf.extend_from_slice(value.as_bytes());
             return;
         }
         let (needs_quote, needs_escape) = self.analyze_string_cached(value);
+        let needs_quote =
+            needs_quote || (self.escape_leading_special_chars && value.starts_with('#'));
         if !needs_quote {
             buf.extend_from_slice(value.as_bytes());
             return;
@@ -1424,7 +2777,7 @@ impl RowEncoder {
             return *flags;
         }
         let flags = analyze_string(value, self.delimiter);
-        if self.string_cache.len() < STRING_CACHE_MAX_ITEMS {
+        if self.string_cache.len() < self.cache_limits.strings {
             self.string_cache.insert(SmolStr::new(value), flags);
         }
         flags
@@ -18,7 +18,7 @@ pub enum ErrorStage {
     Unknown,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub struct Location {
     pub offset: usize,
     pub line: usize,
@@ -26,12 +26,13 @@ pub struct Location {
 }
 
 #[derive(Debug, ThisError)]
-#[error("{message}")]
+#[error("{message}{}", location_suffix(.location))]
 pub struct Error {
     pub kind: ErrorKind,
     pub stage: ErrorStage,
     pub message: String,
     pub location: Option<Location>,
+    recoverable: bool,
     #[source]
     source: Option<Box<dyn StdError + Send + Sync + 'static>>,
 }
@@ -60,6 +61,21 @@ impl Error {
         Self::new(ErrorKind::Decode, ErrorStage::Decode, message)
     }
 
+    /// A decode error caused by input that ends too early (an unterminated
+    /// string or header, or fewer array items than declared), so appending more
+    /// input may make it decode. See [`Error::is_recoverable`].
+    pub fn decode_incomplete(message: impl Into<String>) -> Self {
+        Self {
+            recoverable: true,
+            ..Self::decode(message)
+        }
+    }
+
+    /// A decode error at `location` in the input.
+    pub fn decode_at(message: impl Into<String>, location: Location) -> Self {
+        Self::decode(message).with_location(location)
+    }
+
     pub fn decode_with_source(
         message: impl Into<String>,
         source: impl StdError + Send + Sync + 'static,
@@ -89,6 +105,13 @@ impl Error {
         Self::new_with_source(ErrorKind::Deserialize, ErrorStage::Decode, message, source)
     }
 
+    /// Whether the error looks like truncated input rather than malformed
+    /// input. Streaming consumers can wait for more data on recoverable errors
+    /// and reject the document on the rest.
+    pub fn is_recoverable(&self) -> bool {
+        self.recoverable
+    }
+
     pub fn with_stage(mut self, stage: ErrorStage) -> Self {
         self.stage = stage;
         self
@@ -99,12 +122,18 @@ impl Error {
         self
     }
 
+    pub fn with_location(mut self, location: Location) -> Self {
+        self.location = Some(location);
+        self
+    }
+
     fn new(kind: ErrorKind, stage: ErrorStage, message: impl Into<String>) -> Self {
         Self {
             kind,
             stage,
             message: message.into(),
             location: None,
+            recoverable: false,
             source: None,
         }
     }
@@ -120,7 +149,15 @@ impl Error {
             stage,
             message: message.into(),
             location: None,
+            recoverable: false,
             source: Some(Box::new(source)),
         }
     }
 }
+
+fn location_suffix(location: &Option<Location>) -> String {
+    match location {
+        Some(at) => format!(" at line {}, column {}", at.line, at.column),
+        None => String::new(),
+    }
+}
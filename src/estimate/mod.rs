@@ -0,0 +1,77 @@
+use serde_json::Value;
+
+use crate::{encode, EncodeOptions};
+
+/// Approximates how many BPE tokens `text` occupies, without a tokenizer.
+///
+/// Word runs cost one token per six characters, digit and punctuation runs one
+/// per three, and whitespace one per run; a single space before a word folds
+/// into it, and a line break followed by indentation costs two. Calibrated
+/// against `cl100k_base`: on the sample documents in `benchmarks/data` it
+/// stays within 10% for both JSON and TOON, except URL-heavy JSON-LD, which
+/// it overestimates by up to 25%.
+pub fn estimate_tokens(text: &str) -> usize {
+    let mut tokens = 0;
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        let class = CharClass::of(ch);
+        let mut len: usize = 1;
+        while chars
+            .peek()
+            .is_some_and(|next| CharClass::of(*next) == class)
+        {
+            chars.next();
+            len += 1;
+        }
+        tokens += match class {
+            CharClass::Word => len.div_ceil(6),
+            CharClass::Digit | CharClass::Punct => len.div_ceil(3),
+            CharClass::Space => {
+                let folds_into_word = len == 1
+                    && ch == ' '
+                    && chars
+                        .peek()
+                        .is_some_and(|next| CharClass::of(*next) == CharClass::Word);
+                let breaks_line = ch == '\n' && len > 1;
+                usize::from(!folds_into_word) + usize::from(breaks_line)
+            }
+        };
+    }
+    tokens
+}
+
+/// Estimated TOON-to-JSON token ratio for `value` using [`estimate_tokens`].
+///
+/// Below `1.0` means the TOON encoding (default options) is expected to be
+/// cheaper than compact JSON. This is an approximation; use a real tokenizer
+/// when exact counts matter.
+pub fn estimate_token_ratio(value: &Value) -> f64 {
+    let json = value.to_string();
+    let Ok(toon) = encode::to_string(value, &EncodeOptions::default()) else {
+        return 1.0;
+    };
+    // Compact JSON is never empty, so the denominator is at least one.
+    estimate_tokens(&toon) as f64 / estimate_tokens(&json) as f64
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Word,
+    Digit,
+    Punct,
+    Space,
+}
+
+impl CharClass {
+    fn of(ch: char) -> Self {
+        if ch.is_ascii_digit() {
+            CharClass::Digit
+        } else if ch.is_alphabetic() || ch == '_' {
+            CharClass::Word
+        } else if ch.is_whitespace() {
+            CharClass::Space
+        } else {
+            CharClass::Punct
+        }
+    }
+}
@@ -129,11 +129,20 @@
 
 pub mod arena;
 pub mod canonical;
+pub mod checksum;
+pub mod comments;
 pub mod decode;
+pub mod diff;
 pub mod encode;
 pub mod error;
+pub mod estimate;
 pub mod num;
 pub mod options;
+pub mod raw;
+#[cfg(feature = "jsonschema")]
+pub mod schema;
+#[cfg(feature = "stats")]
+pub mod stats;
 pub mod tabular;
 pub mod text;
 
@@ -141,15 +150,43 @@ use std::io::{Read, Write};
 
 pub use crate::error::{Error, ErrorKind, ErrorStage, Location};
 pub use crate::options::{
-    DecodeOptions, Delimiter, EncodeOptions, ExpandPaths, Indent, KeyFolding,
+    CacheLimits, ColonSpacing, DecodeOptions, Delimiter, DiagnosticsFn, DiagnosticsHook,
+    EncodeOptions, EscapePolicy, ExpandPaths, Indent, KeyCase, KeyFolding, KeyOrder,
+    LineLimitPolicy, NegZeroPolicy, NonFiniteEncoding, NonFinitePolicy, NumberFormatFn,
+    NumberFormatter, SingleElementArrayStyle,
 };
 pub use canonical::{encode_canonical, CanonicalProfile};
+pub use comments::Comment;
+pub use decode::{shared_str, DecodeDiagnostic, Severity, ToonDiagnostic};
+pub use diff::{diff, diff_values, DiffKind, ValueDiff};
+pub use encode::Diagnostic;
+pub use estimate::{estimate_token_ratio, estimate_tokens};
+pub use raw::RawToon;
+#[cfg(feature = "jsonschema")]
+pub use schema::from_str_validated;
 use serde::de::DeserializeOwned;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 pub use serde_json::Value;
+#[cfg(feature = "stats")]
+pub use stats::{encode_stats, encode_with_stats, EncodeStats};
+pub use tabular::is_tabular_candidate;
+pub use text::string::needs_quoting_for;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Map behind the encoder caches; `fast-hash` swaps SipHash for ahash. Its
+/// fixed seed is fine for the encoder's own values, but maps keyed by decoded
+/// input keep std's randomly seeded hasher so documents cannot flood them.
+#[cfg(feature = "fast-hash")]
+pub(crate) type CacheMap<K, V> =
+    std::collections::HashMap<K, V, std::hash::BuildHasherDefault<ahash::AHasher>>;
+#[cfg(not(feature = "fast-hash"))]
+pub(crate) type CacheMap<K, V> = std::collections::HashMap<K, V>;
+
+pub(crate) fn cache_map<K, V>(capacity: usize) -> CacheMap<K, V> {
+    CacheMap::with_capacity_and_hasher(capacity, Default::default())
+}
+
 pub fn to_string<T: Serialize>(value: &T) -> Result<String> {
     to_string_with_options(value, &EncodeOptions::default())
 }
@@ -158,6 +195,12 @@ pub fn to_string_with_options<T: Serialize>(value: &T, options: &EncodeOptions)
     encode::to_string(value, options)
 }
 
+/// Like [`to_string`], with a blank line before each top-level object or array
+/// (see [`EncodeOptions::with_section_spacing`]).
+pub fn to_string_pretty<T: Serialize>(value: &T) -> Result<String> {
+    to_string_with_options(value, &EncodeOptions::default().with_section_spacing(true))
+}
+
 pub fn to_string_into<T: Serialize>(value: &T, out: &mut String) -> Result<()> {
     to_string_into_with_options(value, &EncodeOptions::default(), out)
 }
@@ -201,6 +244,34 @@ pub fn to_writer_with_options<T: Serialize, W: Write>(
     encode::to_writer(writer, value, options)
 }
 
+/// Encodes `value` into `writer`, flushing every `chunk_bytes` of output.
+///
+/// Output is handed to the writer at entry boundaries, so memory stays bounded
+/// by roughly one chunk plus the longest line instead of the whole document.
+/// [`to_writer_with_options`] uses this with a 64 KiB chunk. On error, the
+/// chunks flushed so far have already been written. Any
+/// [`EncodeOptions::with_line_limit`] falls back to buffering the whole
+/// document, so a failed limit writes nothing and `LineLimitPolicy::Truncate`
+/// can rewrite the tail.
+pub fn to_writer_streaming<T: Serialize, W: Write>(
+    writer: W,
+    value: &T,
+    options: &EncodeOptions,
+    chunk_bytes: usize,
+) -> Result<()> {
+    encode::to_writer_streaming(writer, value, options, chunk_bytes)
+}
+
+/// Encodes `value` and yields the output one line at a time, without newlines.
+///
+/// Lines come in document order; joining them with `\n` gives exactly what
+/// [`to_string_with_options`] returns, minus the final newline added by
+/// [`EncodeOptions::with_trailing_newline`]. The whole value is encoded before the
+/// first line is yielded, so this does not reduce peak memory.
+pub fn to_lines(value: &Value, options: &EncodeOptions) -> Result<impl Iterator<Item = String>> {
+    encode::to_lines(value, options)
+}
+
 pub fn from_str<T: DeserializeOwned>(input: &str) -> Result<T> {
     from_str_with_options(input, &DecodeOptions::default())
 }
@@ -212,6 +283,65 @@ pub fn from_str_with_options<T: DeserializeOwned>(
     decode::from_str(input, options)
 }
 
+/// Decodes `input` and returns the `#` comment lines it contained, each tagged
+/// with the key path of the field it precedes (see [`Comment`]).
+///
+/// Comments are only recognised when
+/// [`DecodeOptions::with_capture_comments`] is enabled; otherwise the list is
+/// empty. Pass them to [`EncodeOptions::with_comments`] to write them back.
+pub fn from_str_with_comments<T: DeserializeOwned>(
+    input: &str,
+    options: &DecodeOptions,
+) -> Result<(T, Vec<Comment>)> {
+    decode::from_str_with_comments(input, options)
+}
+
+/// Deserializes `input` into a `T` that may borrow from it, such as a struct
+/// with `&str` fields.
+///
+/// Unescaped strings are passed as slices of `input` without allocating.
+/// Strings containing escapes must be unescaped into an owned copy, so a
+/// `&str` field fails for them; use `Cow<'de, str>` (with `#[serde(borrow)]`)
+/// to accept both. Uses the default [`DecodeOptions`].
+pub fn from_str_borrowed<'de, T: Deserialize<'de>>(input: &'de str) -> Result<T> {
+    decode::from_str_borrowed(input)
+}
+
+/// Returns the string at `path` (`users[1].name`, dotted keys with `[index]`)
+/// as a slice of `input`, without allocating.
+///
+/// Returns `None` when the path is missing, the value is not a string, the
+/// string contains escapes (which need an owned copy), or the document does
+/// not parse. Keys are matched as written: path expansion, key normalization
+/// and comment capture are not applied, and keys containing `.` or `[` cannot
+/// be addressed.
+pub fn raw_str_span<'a>(input: &'a str, path: &str, options: &DecodeOptions) -> Option<&'a str> {
+    decode::raw_str_span(input, path, options)
+}
+
+/// Parses `input` into an [`arena::ArenaView`] without building a `Value`, for
+/// inspecting node kinds, spans and keys. Start at `arena.nodes[arena.root]`
+/// and walk with [`arena::ArenaView::children`], [`arena::ArenaView::pairs`],
+/// [`arena::ArenaView::get_key`] and [`arena::ArenaView::get_str`].
+///
+/// Uses the same parser as [`from_str_with_options`]; options that need the
+/// tree decoder (path expansion, key normalization, comments, ...) are not
+/// applied.
+pub fn parse_to_arena<'a>(input: &'a str, options: &DecodeOptions) -> Result<arena::ArenaView<'a>> {
+    decode::parse_to_arena(input, options)
+}
+
+/// Decodes `input` and returns the non-conformities the decoder tolerated, such
+/// as indentation accepted through [`DecodeOptions::with_indent_warnings`].
+///
+/// Always uses the tree decoder. Without tolerant options the list is empty.
+pub fn from_str_with_diagnostics<T: DeserializeOwned>(
+    input: &str,
+    options: &DecodeOptions,
+) -> Result<(T, Vec<DecodeDiagnostic>)> {
+    decode::from_str_with_diagnostics(input, options)
+}
+
 #[cfg(feature = "parallel")]
 pub fn from_str_parallel<T: DeserializeOwned + Send>(input: &str) -> Result<Vec<T>> {
     from_str_parallel_with_options(input, &DecodeOptions::default())
@@ -247,6 +377,23 @@ pub fn from_reader_with_options<T: DeserializeOwned, R: Read>(
     decode::from_reader(reader, options)
 }
 
+/// Converts `value` into a [`Value`], like `serde_json::to_value` but failing
+/// with this crate's [`Error`] (kind [`ErrorKind::Serialize`]).
+pub fn to_value<T: Serialize>(value: &T) -> Result<Value> {
+    serde_json::to_value(value)
+        .map_err(|err| Error::serialize_with_source(format!("serialize failed: {err}"), err))
+}
+
+/// Converts a [`Value`] into `T`, like `serde_json::from_value` but failing
+/// with this crate's [`Error`] (kind [`ErrorKind::Deserialize`]).
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T> {
+    serde_json::from_value(value)
+        .map_err(|err| Error::deserialize_with_source(format!("deserialize failed: {err}"), err))
+}
+
+/// Decodes into a [`Value`] with canonical numbers: integral floats become
+/// integers (`2.0` → `2`) and `-0` becomes `0`, matching what the encoder writes.
+/// `from_str::<Value>` instead keeps each number as written.
 pub fn decode_to_value(input: &str) -> Result<Value> {
     decode_to_value_with_options(input, &DecodeOptions::default())
 }
@@ -269,9 +416,12 @@ pub fn decode_to_value_auto_with_options<S: AsRef<str>>(
         Ok(value) => Ok(canonicalize_numbers(value)),
         Err(json_err) => match decode_to_value_with_options(input, options) {
             Ok(value) => Ok(value),
-            Err(toon_err) => Err(Error::decode(format!(
-                "input is neither valid JSON nor TOON: json error: {json_err}; toon error: {toon_err}"
-            ))),
+            Err(toon_err) => Err(Error::decode_with_source(
+                format!(
+                    "input is neither valid JSON nor TOON: json error: {json_err}; toon error: {toon_err}"
+                ),
+                toon_err,
+            )),
         },
     }
 }
@@ -305,6 +455,34 @@ pub fn validate_str_with_options(input: &str, options: &DecodeOptions) -> Result
     decode::validate_str(input, options)
 }
 
+/// Validates a TOON document read line by line, without building a value.
+///
+/// Accepts exactly the documents [`validate_str`] accepts (except that key
+/// conflicts from [`DecodeOptions::with_expand_paths`] are not detected), but
+/// memory stays proportional to the longest line and the nesting depth, so it
+/// suits large generated files. The first violation is returned with its
+/// [`Location`]; array length mismatches point at the array header.
+pub fn validate_reader<R: Read>(reader: R) -> Result<()> {
+    validate_reader_with_options(reader, &DecodeOptions::default())
+}
+
+pub fn validate_reader_with_options<R: Read>(reader: R, options: &DecodeOptions) -> Result<()> {
+    decode::validate_reader(reader, options)
+}
+
+/// Validates `input` like [`validate_reader_with_options`], but instead of
+/// stopping at the first problem it reports each one and continues with the
+/// next line, for editor integrations. A misaligned line is read at the level
+/// it most likely meant and an array whose length does not match its header
+/// still closes normally, so later problems are found too. Later diagnostics
+/// after a structural error can be follow-on effects of it.
+///
+/// With [`DecodeOptions::with_indent_warnings`], misaligned lines are reported
+/// as [`Severity::Warning`]. An empty list means the document is valid.
+pub fn validate_collect(input: &str, options: &DecodeOptions) -> Vec<ToonDiagnostic> {
+    decode::validate_collect(input, options)
+}
+
 #[macro_export]
 /// Parse a JSON or TOON string into a `serde_json::Value`, or encode values into TOON.
 ///
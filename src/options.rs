@@ -1,9 +1,23 @@
+use std::fmt;
+use std::sync::Arc;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::comments::Comment;
+use crate::encode::Diagnostic;
+
+pub const DEFAULT_PATH_SEPARATOR: &str = ".";
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum Delimiter {
     #[default]
     Comma,
     Tab,
     Pipe,
+    /// Not a spec delimiter: decoding `[N;]` headers needs
+    /// [`DecodeOptions::with_semicolon_delimiter`].
+    Semicolon,
 }
 
 impl Delimiter {
@@ -12,6 +26,29 @@ impl Delimiter {
             Delimiter::Comma => ',',
             Delimiter::Tab => '\t',
             Delimiter::Pipe => '|',
+            Delimiter::Semicolon => ';',
+        }
+    }
+}
+
+/// Serialized as the delimiter character itself.
+impl Serialize for Delimiter {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_char(self.as_char())
+    }
+}
+
+impl<'de> Deserialize<'de> for Delimiter {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match String::deserialize(deserializer)?.as_str() {
+            "," => Ok(Delimiter::Comma),
+            "\t" => Ok(Delimiter::Tab),
+            "|" => Ok(Delimiter::Pipe),
+            ";" => Ok(Delimiter::Semicolon),
+            other => Err(serde::de::Error::invalid_value(
+                serde::de::Unexpected::Str(other),
+                &"one of \",\", \"\\t\", \"|\", \";\"",
+            )),
         }
     }
 }
@@ -19,12 +56,80 @@ impl Delimiter {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Indent {
     Spaces(usize),
+    /// One tab per level. Strict decoding then rejects spaces in indentation
+    /// instead of tabs.
+    Tabs,
 }
 
 impl Indent {
     pub fn spaces(count: usize) -> Self {
         Indent::Spaces(count)
     }
+
+    pub fn tabs() -> Self {
+        Indent::Tabs
+    }
+
+    /// Indentation columns per level, counting a tab as one column under `Tabs`.
+    pub(crate) fn width(self) -> usize {
+        match self {
+            Indent::Spaces(count) => count,
+            Indent::Tabs => 1,
+        }
+    }
+
+    /// The whitespace byte written for each level.
+    pub(crate) fn byte(self) -> u8 {
+        match self {
+            Indent::Spaces(_) => b' ',
+            Indent::Tabs => b'\t',
+        }
+    }
+}
+
+/// Serialized as the number of spaces, or `"tabs"`.
+impl Serialize for Indent {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Indent::Spaces(count) => serializer.serialize_u64(*count as u64),
+            Indent::Tabs => serializer.serialize_str("tabs"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Indent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct IndentVisitor;
+
+        impl serde::de::Visitor<'_> for IndentVisitor {
+            type Value = Indent;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a number of spaces or \"tabs\"")
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<Indent, E> {
+                usize::try_from(value)
+                    .map(Indent::Spaces)
+                    .map_err(|_| E::invalid_value(serde::de::Unexpected::Unsigned(value), &self))
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<Indent, E> {
+                u64::try_from(value)
+                    .map_err(|_| E::invalid_value(serde::de::Unexpected::Signed(value), &self))
+                    .and_then(|value| self.visit_u64(value))
+            }
+
+            fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Indent, E> {
+                match value {
+                    "tabs" => Ok(Indent::Tabs),
+                    other => Err(E::invalid_value(serde::de::Unexpected::Str(other), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(IndentVisitor)
+    }
 }
 
 impl Default for Indent {
@@ -33,26 +138,212 @@ impl Default for Indent {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum KeyFolding {
     #[default]
     Off,
     Safe,
+    /// Like `Safe`, but also folds chains through keys that are not
+    /// identifiers by quoting those segments, as in `"a b".c: 1`.
+    Aggressive,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ExpandPaths {
     #[default]
     Off,
     Safe,
 }
 
-#[derive(Debug, Clone, Default)]
+/// Whitespace written after the `:` of a `key: value` line.
+///
+/// `None` saves a byte per entry but is not canonical TOON (SPEC §7.2 requires
+/// exactly one space); decoders in this crate accept both forms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColonSpacing {
+    #[default]
+    Single,
+    None,
+}
+
+/// How a one-item scalar array is written: `tags[1]: only` (`Inline`) or as a
+/// one-item list block (`List`). Both decode to the same array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SingleElementArrayStyle {
+    #[default]
+    Inline,
+    List,
+}
+
+/// Order of the remaining keys in object blocks, after any keys listed in
+/// [`EncodeOptions::with_key_order`]. `Preserve` keeps the serialized order
+/// (struct field order for derived types); `Sorted` sorts them by byte value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyOrder {
+    #[default]
+    Preserve,
+    Sorted,
+}
+
+/// How the encoder writes `NaN` and infinite floats. `Null` matches JSON and
+/// is the default; `Error` rejects them; `Quoted` writes `"NaN"`,
+/// `"Infinity"` or `"-Infinity"`, which decode back as strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NonFiniteEncoding {
+    #[default]
+    Null,
+    Error,
+    Quoted,
+}
+
+/// Entry limits for the encoder's per-thread caches of encoded strings, keys
+/// and numbers. Once a cache is full, new values are encoded without being
+/// cached. Raise the limits for documents with many distinct short values;
+/// lower them to bound memory held by pooled encoders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheLimits {
+    /// Quoting decisions for strings outside tabular rows.
+    pub strings: usize,
+    /// Encoded object keys.
+    pub keys: usize,
+    /// Encoded tabular string cells, per delimiter.
+    pub tabular_strings: usize,
+    /// Encoded tabular number cells.
+    pub tabular_numbers: usize,
+    /// Encoded tabular cells with their leading delimiter, per delimiter.
+    pub tabular_prefixed: usize,
+}
+
+impl CacheLimits {
+    /// Applies `limit` to every cache.
+    pub fn uniform(limit: usize) -> Self {
+        Self {
+            strings: limit,
+            keys: limit,
+            tabular_strings: limit,
+            tabular_numbers: limit,
+            tabular_prefixed: limit,
+        }
+    }
+}
+
+impl Default for CacheLimits {
+    fn default() -> Self {
+        Self {
+            strings: 1024,
+            keys: 1024,
+            tabular_strings: 512,
+            tabular_numbers: 512,
+            tabular_prefixed: 512,
+        }
+    }
+}
+
+/// What happens once encoded output would exceed
+/// [`EncodeOptions::with_line_limit`]: fail with an encode error, or keep the
+/// whole lines that fit, minus trailing `key:` lines whose children were cut,
+/// and end with a `# truncated` line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineLimitPolicy {
+    #[default]
+    Error,
+    Truncate,
+}
+
+/// Formats a number for [`EncodeOptions::with_number_formatter`]; `None` keeps
+/// the default formatting.
+pub type NumberFormatFn = dyn Fn(&serde_json::Number) -> Option<String> + Send + Sync;
+
+/// A shared [`NumberFormatFn`], so [`EncodeOptions`] stays `Clone`.
+#[derive(Clone)]
+pub struct NumberFormatter(Arc<NumberFormatFn>);
+
+impl NumberFormatter {
+    pub(crate) fn format(&self, number: &serde_json::Number) -> Option<String> {
+        (self.0)(number)
+    }
+}
+
+impl fmt::Debug for NumberFormatter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("NumberFormatter(..)")
+    }
+}
+
+/// Receives the reasons reported through
+/// [`EncodeOptions::with_diagnostics_hook`]. It can capture state such as a
+/// collector or logger.
+pub type DiagnosticsFn = dyn Fn(Diagnostic) + Send + Sync;
+
+/// A shared [`DiagnosticsFn`], so [`EncodeOptions`] stays `Clone`.
+#[derive(Clone)]
+pub struct DiagnosticsHook(Arc<DiagnosticsFn>);
+
+impl DiagnosticsHook {
+    pub(crate) fn report(&self, diagnostic: Diagnostic) {
+        (self.0)(diagnostic)
+    }
+}
+
+impl fmt::Debug for DiagnosticsHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DiagnosticsHook(..)")
+    }
+}
+
+/// Serializable for storing settings in a config file; missing fields take
+/// their defaults. `diagnostics_hook`, `transform` and `number_formatter` are
+/// functions and are skipped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct EncodeOptions {
     pub indent: Indent,
     pub delimiter: Delimiter,
     pub key_folding: KeyFolding,
     pub flatten_depth: Option<usize>,
+    pub flatten_separator: String,
+    pub kv_array_collapse: Option<(String, String)>,
+    pub inline_depth: usize,
+    pub inline_small_objects: usize,
+    pub checksum: bool,
+    #[serde(skip)]
+    pub diagnostics_hook: Option<DiagnosticsHook>,
+    pub key_order: Vec<String>,
+    pub key_ordering: KeyOrder,
+    pub indent_first_level: usize,
+    pub empty_string_marker: Option<String>,
+    pub colon_spacing: ColonSpacing,
+    #[serde(skip)]
+    pub transform: Option<fn(&mut Value)>,
+    pub collapse_single_chains: bool,
+    pub comments: Vec<Comment>,
+    pub comment_key: Option<String>,
+    pub max_tabular_columns: usize,
+    pub sort_arrays: bool,
+    pub single_element_array_style: SingleElementArrayStyle,
+    pub sparse_objects: bool,
+    pub escape_leading_special_chars: bool,
+    pub empty_containers_in_tabular: bool,
+    pub unwrap_single_key_tabular: bool,
+    pub line_limit: Option<usize>,
+    pub line_limit_policy: LineLimitPolicy,
+    pub section_spacing: bool,
+    pub matrix_tabular: bool,
+    pub cache_limits: CacheLimits,
+    pub trailing_newline: bool,
+    #[serde(skip)]
+    pub number_formatter: Option<NumberFormatter>,
+    pub non_finite: NonFiniteEncoding,
+    pub bytes_as_base64: bool,
+    pub inline_array_threshold: Option<usize>,
 }
 
 impl EncodeOptions {
@@ -70,6 +361,11 @@ impl EncodeOptions {
         self
     }
 
+    /// `KeyFolding::Safe` folds single-key chains into dotted keys, skipping a
+    /// fold whose path matches a literal dotted key already in the object or
+    /// one of its parents.
+    /// `KeyFolding::Aggressive` additionally folds through non-identifier keys,
+    /// quoting each such segment.
     pub fn with_key_folding(mut self, key_folding: KeyFolding) -> Self {
         self.key_folding = key_folding;
         self
@@ -79,13 +375,408 @@ impl EncodeOptions {
         self.flatten_depth = flatten_depth;
         self
     }
+
+    pub fn with_flatten_separator(mut self, flatten_separator: impl Into<String>) -> Self {
+        self.flatten_separator = flatten_separator.into();
+        self
+    }
+
+    /// Encodes arrays of `{key_field: "name", value_field: value}` objects as a
+    /// single object keyed by `name`. This is lossy: decoding yields the object,
+    /// not the original array.
+    pub fn with_kv_array_collapse(mut self, fields: Option<(String, String)>) -> Self {
+        self.kv_array_collapse = fields;
+        self
+    }
+
+    /// Writes object fields whose value is a non-empty object of scalars (nested at
+    /// most `depth` levels) inline as `key: {a: 1, b: {c: 2}}`. Arrays are never
+    /// inlined. `0` disables inlining; decoding needs
+    /// [`DecodeOptions::with_inline_objects`].
+    pub fn with_inline_depth(mut self, depth: usize) -> Self {
+        self.inline_depth = depth;
+        self
+    }
+
+    /// Writes objects of at most `max_fields` scalar fields inline as `{a: 1, b: 2}`,
+    /// both as field values and as list items (`- {a: 1, b: 2}`). Independent of
+    /// [`with_inline_depth`](Self::with_inline_depth); `0` disables it. Decoding
+    /// needs [`DecodeOptions::with_inline_objects`].
+    pub fn with_inline_small_objects(mut self, max_fields: usize) -> Self {
+        self.inline_small_objects = max_fields;
+        self
+    }
+
+    /// Appends a final `#checksum: <crc32>` line covering everything before it.
+    /// This crate's decoders always drop the line; see
+    /// [`DecodeOptions::with_verify_checksum`].
+    pub fn with_checksum(mut self, checksum: bool) -> Self {
+        self.checksum = checksum;
+        self
+    }
+
+    /// Calls `hook` whenever an array of objects is written in list form instead
+    /// of tabular form, with the reason. No-op when unset. The hook is a shared
+    /// closure, so it can push into a collector or forward to a logger.
+    pub fn with_diagnostics_hook(mut self, hook: Arc<DiagnosticsFn>) -> Self {
+        self.diagnostics_hook = Some(DiagnosticsHook(hook));
+        self
+    }
+
+    /// Writes the listed keys first, in this order, in every object block and
+    /// list-item object, and in tabular headers; other keys follow in their
+    /// original order and absent keys are skipped.
+    pub fn with_key_order(mut self, key_order: Vec<String>) -> Self {
+        self.key_order = key_order;
+        self
+    }
+
+    /// Sorts object keys, including those of list-item objects and tabular
+    /// headers, for diff-stable output, or keeps them in serialized order (the
+    /// default).
+    pub fn with_key_ordering(mut self, key_ordering: KeyOrder) -> Self {
+        self.key_ordering = key_ordering;
+        self
+    }
+
+    /// Prefixes every emitted line with `spaces` spaces, for embedding the output
+    /// at an indented position in another document.
+    pub fn with_indent_first_level(mut self, spaces: usize) -> Self {
+        self.indent_first_level = spaces;
+        self
+    }
+
+    /// Writes `marker` verbatim for empty-string cells in tabular rows instead of
+    /// the default `""`. `Some(String::new())` gives the compact `1,,3` form,
+    /// which still decodes as an empty string; `null` cells are unaffected.
+    ///
+    /// Any other marker is not reversed on decode: `Some("-")` reads back as
+    /// the string `"-"`. Encoding fails for markers that would not read back as
+    /// one unquoted string cell, such as ones containing the delimiter, a
+    /// colon, quotes or brackets, with surrounding whitespace, or spelling a
+    /// number, boolean or null.
+    pub fn with_empty_string_marker(mut self, marker: Option<String>) -> Self {
+        self.empty_string_marker = marker;
+        self
+    }
+
+    /// Chooses between `key: value` and `key:value` for fields and inline arrays.
+    /// Inline objects from [`with_inline_depth`](Self::with_inline_depth) keep
+    /// their `, `/`: ` spacing.
+    pub fn with_colon_spacing(mut self, colon_spacing: ColonSpacing) -> Self {
+        self.colon_spacing = colon_spacing;
+        self
+    }
+
+    /// Runs `transform` on an owned copy of the value before encoding, e.g. to
+    /// redact secrets or drop fields. The caller's value is never modified.
+    pub fn with_transform(mut self, transform: fn(&mut Value)) -> Self {
+        self.transform = Some(transform);
+        self
+    }
+
+    /// Writes a field whose value is a chain of single-key objects ending in a
+    /// non-object (or empty object) as one dotted key: `a.b.c.d.e: 1`. Unlike
+    /// [`KeyFolding::Safe`], a chain that reaches an object with several keys is
+    /// left fully expanded, and `flatten_depth` does not apply. Segments must be
+    /// identifier segments, the folded key must not collide with a sibling, and
+    /// `flatten_separator` joins them. When both are enabled, key folding wins.
+    pub fn with_collapse_single_chains(mut self, enabled: bool) -> Self {
+        self.collapse_single_chains = enabled;
+        self
+    }
+
+    /// Writes each comment as a `# text` line directly above the field at its
+    /// path, at that field's indentation; root-array comments go above the header
+    /// and `path: None` comments at the end. Comments whose path is not in the
+    /// value are dropped. Usually fed from [`crate::from_str_with_comments`].
+    pub fn with_comments(mut self, comments: Vec<Comment>) -> Self {
+        self.comments = comments;
+        self
+    }
+
+    /// Writes a string-valued object field named `key` as `# text` lines (one per
+    /// `\n`-separated line) above the next field instead of as data, so values can
+    /// carry their own comments (`{"_comment": "limits", "max": 5}`). This applies
+    /// to list-item objects too, and arrays whose rows carry comments are not
+    /// written as tables. Non-string values under `key` stay data. Read them back with
+    /// [`DecodeOptions::with_capture_comments`].
+    pub fn with_comment_key(mut self, key: Option<String>) -> Self {
+        self.comment_key = key;
+        self
+    }
+
+    /// Writes object arrays with more than `columns` fields as list items instead
+    /// of tabular rows, so very wide tables do not become single huge lines.
+    pub fn with_max_tabular_columns(mut self, columns: usize) -> Self {
+        self.max_tabular_columns = columns;
+        self
+    }
+
+    /// Sorts every array whose items are all scalars before encoding, so values
+    /// serialized from a `HashSet` encode the same way every time. This changes
+    /// element order: `null`, then `false`/`true`, then numbers by value, then
+    /// strings by bytes. Arrays containing objects or arrays are left in place.
+    pub fn with_sort_arrays(mut self, enabled: bool) -> Self {
+        self.sort_arrays = enabled;
+        self
+    }
+
+    /// Chooses how arrays holding exactly one scalar are written; `List` matches
+    /// the layout of larger arrays written as list blocks. Other arrays are
+    /// unaffected.
+    pub fn with_single_element_array_style(mut self, style: SingleElementArrayStyle) -> Self {
+        self.single_element_array_style = style;
+        self
+    }
+
+    /// Omits object fields whose value is `null`, which shrinks wide, mostly
+    /// empty records. Rows of tabular arrays keep their `null` cells. Decoding
+    /// does not restore the omitted fields: typed targets need `Option` or
+    /// `#[serde(default)]` fields to accept their absence.
+    pub fn with_sparse_objects(mut self, enabled: bool) -> Self {
+        self.sparse_objects = enabled;
+        self
+    }
+
+    /// Also quotes string values that start with `#`, so a value alone on its
+    /// line is not read back as a comment by [`crate::from_str_with_comments`].
+    /// Values starting with `-`, `[`, `{` or `"` are always quoted. Output that
+    /// carries `#` lines of its own (from [`EncodeOptions::with_comments`],
+    /// [`EncodeOptions::with_comment_key`], [`EncodeOptions::with_checksum`]
+    /// or a truncating [`EncodeOptions::with_line_limit`]) quotes them anyway.
+    pub fn with_escape_leading_special_chars(mut self, enabled: bool) -> Self {
+        self.escape_leading_special_chars = enabled;
+        self
+    }
+
+    /// Lets arrays of objects stay tabular when some fields hold an empty array
+    /// or object, written as a bare `[]` or `{}` cell. Non-empty containers
+    /// still force list form. Decoding needs
+    /// [`DecodeOptions::with_empty_containers_in_tabular`].
+    pub fn with_empty_containers_in_tabular(mut self, enabled: bool) -> Self {
+        self.empty_containers_in_tabular = enabled;
+        self
+    }
+
+    /// Writes an array whose items are all `{wrapper: {..}}`, with the same
+    /// identifier-segment `wrapper` key around objects that would be tabular on
+    /// their own, as a tabular array with dotted fields:
+    /// `rows[2]{user.id,user.name}:`. Inner keys must be identifier segments
+    /// too. Decoding with [`ExpandPaths::Safe`] restores the wrappers.
+    pub fn with_unwrap_single_key_tabular(mut self, enabled: bool) -> Self {
+        self.unwrap_single_key_tabular = enabled;
+        self
+    }
+
+    /// Caps the encoded output, including any checksum line and trailing
+    /// newline, at `max_bytes`. Encoding stops soon after the output passes the
+    /// cap rather than finishing the whole value; `policy` picks between an
+    /// error and a truncated document whose `# truncated` marker line still
+    /// fits within `max_bytes`. A cap too small for the marker fails as with
+    /// [`LineLimitPolicy::Error`]. With a cap set, `to_writer` writes nothing
+    /// when encoding fails.
+    ///
+    /// Truncated output is a preview, not a complete document: it decodes only
+    /// with [`DecodeOptions::with_allow_comments`], and arrays cut short still
+    /// fail the length check in strict mode.
+    pub fn with_line_limit(mut self, max_bytes: usize, policy: LineLimitPolicy) -> Self {
+        self.line_limit = Some(max_bytes);
+        self.line_limit_policy = policy;
+        self
+    }
+
+    /// Inserts a blank line before each top-level key whose value is an object
+    /// or array, so large config-style documents read as sections. Scalar keys
+    /// stay packed together. Blank lines between top-level keys decode in both
+    /// strict and non-strict mode.
+    pub fn with_section_spacing(mut self, enabled: bool) -> Self {
+        self.section_spacing = enabled;
+        self
+    }
+
+    /// Writes an array of equal-length scalar arrays as a matrix: a
+    /// `[RxC]` header followed by one delimited row per line, e.g.
+    /// `matrix[2x3]:` then `1,2,3` and `4,5,6`. Decoding it needs
+    /// [`DecodeOptions::with_matrix_tabular`].
+    pub fn with_matrix_tabular(mut self, enabled: bool) -> Self {
+        self.matrix_tabular = enabled;
+        self
+    }
+
+    /// Overrides the entry limits of the encoder's string, key and number
+    /// caches. Output is identical for any limits; only speed and retained
+    /// memory change.
+    pub fn with_cache_limits(mut self, limits: CacheLimits) -> Self {
+        self.cache_limits = limits;
+        self
+    }
+
+    /// Ends the output with a single `\n`, after the checksum line if any. The
+    /// spec forbids a trailing newline, so this output is not canonical TOON:
+    /// `validate_str` and `validate_reader` reject it with "trailing newline not
+    /// allowed", even outside strict mode. Decoding accepts it in both modes,
+    /// so strip the newline only before validating.
+    pub fn with_trailing_newline(mut self, enabled: bool) -> Self {
+        self.trailing_newline = enabled;
+        self
+    }
+
+    /// Writes numbers through `formatter` wherever a scalar is emitted, including
+    /// tabular cells; returning `None` keeps the default formatting. The text is
+    /// written verbatim unless it is empty, has surrounding whitespace, or holds
+    /// the active delimiter, a quote, a backslash, a colon or a control
+    /// character; then it is written as a quoted string so the line still
+    /// parses. Only bare text that reads back as a number (e.g. cents as
+    /// `12.34`) decodes as one. Digit separators such as `1_000` decode only
+    /// with [`DecodeOptions::with_numeric_separators`].
+    pub fn with_number_formatter(mut self, formatter: Arc<NumberFormatFn>) -> Self {
+        self.number_formatter = Some(NumberFormatter(formatter));
+        self
+    }
+
+    /// Chooses how `f32`/`f64` values that are `NaN` or infinite encode. The
+    /// check runs while the input is serialized, so it applies to every
+    /// `to_*` entry point that takes a `Serialize` value.
+    pub fn with_non_finite(mut self, policy: NonFiniteEncoding) -> Self {
+        self.non_finite = policy;
+        self
+    }
+
+    /// Writes byte strings (`serialize_bytes`, as used by `serde_bytes`) as one
+    /// base64 string instead of an array of numbers. Like
+    /// [`EncodeOptions::with_non_finite`] this happens while the input is
+    /// serialized, so plain `Vec<u8>` fields, which serialize as sequences, are
+    /// unaffected. Read them back with [`DecodeOptions::with_bytes_from_base64`].
+    pub fn with_bytes_as_base64(mut self, enabled: bool) -> Self {
+        self.bytes_as_base64 = enabled;
+        self
+    }
+
+    /// Writes scalar arrays longer than `threshold` as list blocks, one `- `
+    /// item per line, instead of on a single `key[N]: a,b,...` line. `None`
+    /// (the default) keeps every scalar array inline.
+    pub fn with_inline_array_threshold(mut self, threshold: Option<usize>) -> Self {
+        self.inline_array_threshold = threshold;
+        self
+    }
 }
 
-#[derive(Debug, Clone)]
+impl Default for EncodeOptions {
+    fn default() -> Self {
+        Self {
+            indent: Indent::default(),
+            delimiter: Delimiter::default(),
+            key_folding: KeyFolding::default(),
+            flatten_depth: None,
+            flatten_separator: DEFAULT_PATH_SEPARATOR.to_string(),
+            kv_array_collapse: None,
+            inline_depth: 0,
+            inline_small_objects: 0,
+            checksum: false,
+            diagnostics_hook: None,
+            key_order: Vec::new(),
+            key_ordering: KeyOrder::default(),
+            indent_first_level: 0,
+            empty_string_marker: None,
+            colon_spacing: ColonSpacing::default(),
+            transform: None,
+            collapse_single_chains: false,
+            comments: Vec::new(),
+            comment_key: None,
+            max_tabular_columns: usize::MAX,
+            sort_arrays: false,
+            single_element_array_style: SingleElementArrayStyle::default(),
+            sparse_objects: false,
+            escape_leading_special_chars: false,
+            empty_containers_in_tabular: false,
+            unwrap_single_key_tabular: false,
+            line_limit: None,
+            line_limit_policy: LineLimitPolicy::default(),
+            section_spacing: false,
+            matrix_tabular: false,
+            cache_limits: CacheLimits::default(),
+            trailing_newline: false,
+            number_formatter: None,
+            non_finite: NonFiniteEncoding::default(),
+            bytes_as_base64: false,
+            inline_array_threshold: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EscapePolicy {
+    #[default]
+    Error,
+    Keep,
+    Strip,
+}
+
+/// How unquoted `NaN`, `Infinity`, `-Infinity` and `+Infinity` tokens decode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NonFinitePolicy {
+    #[default]
+    Error,
+    Null,
+    Keep,
+}
+
+/// How numbers equal to negative zero (`-0`, `-0.0`, `-0e3`) decode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NegZeroPolicy {
+    /// Drop the sign: `-0` decodes like `0` and `-0.0` like `0.0`, as the spec
+    /// asks.
+    Zero,
+    /// Keep the IEEE float `-0.0`.
+    #[default]
+    PreserveFloat,
+    /// Reject the token.
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyCase {
+    #[default]
+    Preserve,
+    Lower,
+    Snake,
+}
+
+/// Serializable like [`EncodeOptions`]; missing fields take their defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct DecodeOptions {
     pub indent: Indent,
     pub strict: bool,
     pub expand_paths: ExpandPaths,
+    pub expand_separator: String,
+    pub on_unknown_escape: EscapePolicy,
+    pub strict_number_canonicalization: bool,
+    pub inline_objects: bool,
+    pub key_normalize: KeyCase,
+    pub verify_checksum: bool,
+    pub delimiter_escape: bool,
+    pub semicolon_delimiter: bool,
+    pub numeric_separators: bool,
+    pub coerce_numbers: bool,
+    pub bytes_from_base64: bool,
+    pub non_finite: NonFinitePolicy,
+    pub negative_zero: NegZeroPolicy,
+    pub trim_quoted: bool,
+    pub capture_comments: bool,
+    pub allow_comments: bool,
+    pub max_array_len: usize,
+    pub max_depth: usize,
+    pub indent_warnings: bool,
+    pub empty_containers_in_tabular: bool,
+    pub matrix_tabular: bool,
+    pub intern_threshold: Option<usize>,
+    pub string_dedup: bool,
 }
 
 impl DecodeOptions {
@@ -107,6 +798,211 @@ impl DecodeOptions {
         self.expand_paths = expand_paths;
         self
     }
+
+    pub fn with_expand_separator(mut self, expand_separator: impl Into<String>) -> Self {
+        self.expand_separator = expand_separator.into();
+        self
+    }
+
+    pub fn with_on_unknown_escape(mut self, policy: EscapePolicy) -> Self {
+        self.on_unknown_escape = policy;
+        self
+    }
+
+    /// Rejects unquoted numbers that are not in canonical form (`01`, `1.0`,
+    /// `1E3`, `-0`), independent of `strict`.
+    pub fn with_strict_number_canonicalization(mut self, enabled: bool) -> Self {
+        self.strict_number_canonicalization = enabled;
+        self
+    }
+
+    /// Accepts inline object values (`key: {a: 1, b: 2}` or `- {a: 1}`) as written
+    /// by [`EncodeOptions::with_inline_depth`] and
+    /// [`EncodeOptions::with_inline_small_objects`].
+    pub fn with_inline_objects(mut self, enabled: bool) -> Self {
+        self.inline_objects = enabled;
+        self
+    }
+
+    /// Rewrites unquoted keys (including tabular field names) to `key_case` before
+    /// they are inserted, so path expansion and struct fields see the normalized
+    /// name. Quoted keys are kept verbatim. Keys that collide after normalization
+    /// (`Name` and `name`) follow the usual duplicate-key rule: an error in strict
+    /// mode, otherwise the last one wins.
+    pub fn with_key_normalize(mut self, key_case: KeyCase) -> Self {
+        self.key_normalize = key_case;
+        self
+    }
+
+    /// Requires a trailing `#checksum: <crc32>` line and errors when it is missing
+    /// or does not match the document body.
+    pub fn with_verify_checksum(mut self, verify: bool) -> Self {
+        self.verify_checksum = verify;
+        self
+    }
+
+    /// Treats a `\` before the active delimiter in an unquoted array or tabular
+    /// cell as a literal delimiter (`a\,b` is the single string `a,b`). Quoted
+    /// cells keep their normal escape rules.
+    pub fn with_delimiter_escape(mut self, enabled: bool) -> Self {
+        self.delimiter_escape = enabled;
+        self
+    }
+
+    /// Accepts `;` as an array delimiter, as written by
+    /// [`Delimiter::Semicolon`]. The spec only allows comma, tab and pipe, so
+    /// `[N;]` headers are rejected unless this is enabled.
+    pub fn with_semicolon_delimiter(mut self, enabled: bool) -> Self {
+        self.semicolon_delimiter = enabled;
+        self
+    }
+
+    /// Accepts Rust-style `_` digit separators in unquoted numbers (`1_000_000`).
+    /// Each `_` must sit between two digits; `_100`, `100_` and `1__0` stay
+    /// strings. Encoding never writes separators.
+    pub fn with_numeric_separators(mut self, enabled: bool) -> Self {
+        self.numeric_separators = enabled;
+        self
+    }
+
+    /// With `false`, unquoted number tokens decode as strings (`42` becomes
+    /// `"42"`), so IDs and phone numbers keep their text. `true`, `false`, `null`
+    /// and non-finite tokens are still read as literals; quote them to keep them
+    /// as strings. Number canonicalization and negative-zero checks no longer
+    /// apply. On by default.
+    pub fn with_coerce_numbers(mut self, enabled: bool) -> Self {
+        self.coerce_numbers = enabled;
+        self
+    }
+
+    /// Decodes strings read by `deserialize_bytes`/`deserialize_byte_buf` (such
+    /// as `serde_bytes` fields) as base64, the form written by
+    /// [`EncodeOptions::with_bytes_as_base64`]; invalid base64 is an error.
+    /// Applies when deserializing straight from the parsed document, which
+    /// options such as path expansion or key normalization bypass.
+    pub fn with_bytes_from_base64(mut self, enabled: bool) -> Self {
+        self.bytes_from_base64 = enabled;
+        self
+    }
+
+    /// Chooses how bare non-finite tokens decode: an error (the default), `null`
+    /// as the encoder would have written them, or the original string. Quoted
+    /// `"NaN"` is always a string.
+    pub fn with_non_finite(mut self, policy: NonFinitePolicy) -> Self {
+        self.non_finite = policy;
+        self
+    }
+
+    /// Chooses how negative zero decodes. The default keeps it as the float
+    /// `-0.0`; `Zero` drops the sign, so `-0` decodes as `0` and `-0.0` as
+    /// `0.0`. Either way the encoder writes it back as `0`. This is what
+    /// `from_str` returns; [`crate::decode_to_value`] also turns integral floats
+    /// into integers, so there every zero is the integer `0`.
+    pub fn with_negative_zero(mut self, policy: NegZeroPolicy) -> Self {
+        self.negative_zero = policy;
+        self
+    }
+
+    /// Trims surrounding whitespace from quoted string values, so `" hello "`
+    /// decodes as `hello`. Off by default, since quoting is how the encoder
+    /// preserves such whitespace. Keys are left as written.
+    pub fn with_trim_quoted(mut self, enabled: bool) -> Self {
+        self.trim_quoted = enabled;
+        self
+    }
+
+    /// Treats lines whose first non-blank character is `#` as comments and drops
+    /// them before decoding; [`crate::from_str_with_comments`] also returns them.
+    /// Lines indented as rows under a tabular or matrix header are kept as rows
+    /// even when their first cell starts with `#`; any other value alone on its
+    /// line and starting with `#` must be quoted.
+    pub fn with_capture_comments(mut self, enabled: bool) -> Self {
+        self.capture_comments = enabled;
+        self
+    }
+
+    /// Skips lines whose first non-blank character is `#` as if they were blank,
+    /// without collecting them like [`DecodeOptions::with_capture_comments`]. A
+    /// `#` later in a line or inside a quoted value is still data, and so is a
+    /// line under a tabular or matrix header indented as a row, whose first
+    /// cell may start with `#`. Strict mode applies its blank-line rules to
+    /// comment lines, so inside list arrays they need `strict` off.
+    pub fn with_allow_comments(mut self, enabled: bool) -> Self {
+        self.allow_comments = enabled;
+        self
+    }
+
+    /// Rejects any array that declares or contains more than `max` items, before
+    /// its items are collected, so a hostile `[999999999]:` header fails fast.
+    pub fn with_max_array_len(mut self, max: usize) -> Self {
+        self.max_array_len = max;
+        self
+    }
+
+    /// Rejects documents whose objects and arrays nest more than `max` levels
+    /// deep (default 128), including levels created by [`ExpandPaths::Safe`],
+    /// so hostile input fails with a decode error instead of overflowing the
+    /// stack.
+    pub fn with_max_depth(mut self, max: usize) -> Self {
+        self.max_depth = max;
+        self
+    }
+
+    /// Makes the arena decoder store a key once it has been seen more than
+    /// `threshold` times and share that entry between later occurrences,
+    /// which saves memory on list arrays of objects repeating a few keys.
+    /// Counting costs a hash lookup per key, so the default `None` stores
+    /// every occurrence inline; that is faster when keys are mostly unique.
+    /// The decoded value is the same either way.
+    pub fn with_intern_threshold(mut self, threshold: Option<usize>) -> Self {
+        self.intern_threshold = threshold;
+        self
+    }
+
+    /// Shares repeated string values between `Arc<str>` fields marked
+    /// `#[serde(deserialize_with = "serde_toon::shared_str")]`: within one
+    /// decode, each distinct value is allocated once and every field holding
+    /// it gets a clone of that `Arc`, which cuts memory for columns such as a
+    /// status repeated in every row. Other fields, and `serde_json::Value`,
+    /// still get their own `String` per value.
+    pub fn with_string_dedup(mut self, enabled: bool) -> Self {
+        self.string_dedup = enabled;
+        self
+    }
+
+    /// Accepts indentation that is not a multiple of the indent size, even in
+    /// strict mode, inferring each such line's level from the line before it.
+    /// Every tolerated line is reported by [`crate::from_str_with_diagnostics`];
+    /// other entry points decode silently.
+    pub fn with_indent_warnings(mut self, enabled: bool) -> Self {
+        self.indent_warnings = enabled;
+        self
+    }
+
+    /// Reads bare `[]` and `{}` tabular cells as an empty array and object, as
+    /// written by [`EncodeOptions::with_empty_containers_in_tabular`]. Otherwise
+    /// they decode as strings.
+    pub fn with_empty_containers_in_tabular(mut self, enabled: bool) -> Self {
+        self.empty_containers_in_tabular = enabled;
+        self
+    }
+
+    /// Reads `[RxC]` matrix headers, as written by
+    /// [`EncodeOptions::with_matrix_tabular`], rejecting rows that are not `C`
+    /// values wide. `[0xC]:` is an empty array and `[Rx0]` is an error. The
+    /// spec has no matrix form, so such headers are rejected unless enabled.
+    pub fn with_matrix_tabular(mut self, enabled: bool) -> Self {
+        self.matrix_tabular = enabled;
+        self
+    }
+
+    pub(crate) fn unknown_escape_policy(&self) -> EscapePolicy {
+        if self.strict {
+            EscapePolicy::Error
+        } else {
+            self.on_unknown_escape
+        }
+    }
 }
 
 impl Default for DecodeOptions {
@@ -115,6 +1011,29 @@ impl Default for DecodeOptions {
             indent: Indent::default(),
             strict: true,
             expand_paths: ExpandPaths::default(),
+            expand_separator: DEFAULT_PATH_SEPARATOR.to_string(),
+            on_unknown_escape: EscapePolicy::default(),
+            strict_number_canonicalization: false,
+            inline_objects: false,
+            key_normalize: KeyCase::default(),
+            verify_checksum: false,
+            delimiter_escape: false,
+            semicolon_delimiter: false,
+            numeric_separators: false,
+            coerce_numbers: true,
+            bytes_from_base64: false,
+            non_finite: NonFinitePolicy::default(),
+            negative_zero: NegZeroPolicy::default(),
+            trim_quoted: false,
+            capture_comments: false,
+            allow_comments: false,
+            max_array_len: usize::MAX,
+            max_depth: 128,
+            indent_warnings: false,
+            empty_containers_in_tabular: false,
+            matrix_tabular: false,
+            intern_threshold: None,
+            string_dedup: false,
         }
     }
 }
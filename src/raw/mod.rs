@@ -0,0 +1,82 @@
+//! Deferred decoding of TOON subtrees, in the spirit of serde_json's `RawValue`.
+
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::ser::{self, Serialize, Serializer};
+use serde_json::Value;
+
+/// Newtype name the arena deserializer recognises to hand out raw source.
+pub(crate) const TOKEN: &str = "$serde_toon::private::RawToon";
+
+/// A subtree kept as TOON text instead of being decoded.
+///
+/// Deserializing a field typed `RawToon` from [`crate::from_str`] captures the
+/// field's source: an indented object's lines or an array's `[N]...:` header
+/// and rows, without the key and with the block's indentation removed, so the
+/// text decodes on its own. Scalars, single-line values, and documents that go
+/// through the tree decoder (path expansion, inline objects, ...) are
+/// re-encoded with the default options instead. Serializing parses the text and
+/// writes the value it holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawToon {
+    text: String,
+}
+
+impl RawToon {
+    /// The captured TOON text.
+    pub fn get(&self) -> &str {
+        &self.text
+    }
+
+    pub fn into_string(self) -> String {
+        self.text
+    }
+}
+
+impl fmt::Display for RawToon {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl<'de> Deserialize<'de> for RawToon {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_newtype_struct(TOKEN, RawToonVisitor)
+    }
+}
+
+struct RawToonVisitor;
+
+impl<'de> Visitor<'de> for RawToonVisitor {
+    type Value = RawToon;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a TOON subtree")
+    }
+
+    // Only the arena deserializer answers `TOKEN` with a string.
+    fn visit_string<E: de::Error>(self, text: String) -> Result<RawToon, E> {
+        Ok(RawToon { text })
+    }
+
+    fn visit_str<E: de::Error>(self, text: &str) -> Result<RawToon, E> {
+        self.visit_string(text.to_string())
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<RawToon, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        let text = crate::to_string(&value).map_err(de::Error::custom)?;
+        Ok(RawToon { text })
+    }
+}
+
+impl Serialize for RawToon {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let value: Value = crate::from_str(&self.text).map_err(ser::Error::custom)?;
+        value.serialize(serializer)
+    }
+}
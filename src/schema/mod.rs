@@ -0,0 +1,37 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::{decode, DecodeOptions, Error, Result};
+
+/// Decodes `input`, validates it against the JSON Schema `schema`, and only then
+/// deserializes it into `T`.
+///
+/// Every violation is reported in one decode error as
+/// `<instance path>: <message>`, joined with `; `. An invalid schema is also a
+/// decode error.
+pub fn from_str_validated<T: DeserializeOwned>(
+    input: &str,
+    schema: &Value,
+    options: &DecodeOptions,
+) -> Result<T> {
+    let validator = jsonschema::validator_for(schema).map_err(|err| {
+        Error::decode_with_source(format!("invalid schema: {err}"), err.to_owned())
+    })?;
+    let value = decode::from_str_value(input, options)?;
+    let violations: Vec<String> = validator
+        .iter_errors(&value)
+        .map(|err| {
+            let path = err.instance_path.as_str();
+            let path = if path.is_empty() { "/" } else { path };
+            format!("{path}: {err}")
+        })
+        .collect();
+    if !violations.is_empty() {
+        return Err(Error::decode(format!(
+            "schema validation failed: {}",
+            violations.join("; ")
+        )));
+    }
+    serde_json::from_value(value)
+        .map_err(|err| Error::deserialize_with_source(format!("deserialize failed: {err}"), err))
+}
@@ -0,0 +1,59 @@
+use std::sync::OnceLock;
+
+use serde_json::Value;
+use tiktoken_rs::{cl100k_base, CoreBPE};
+
+use crate::{encode, EncodeOptions, Error, Result};
+
+/// Size of an encoding in `cl100k_base` tokens, next to compact JSON for the
+/// same value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EncodeStats {
+    pub toon_tokens: usize,
+    pub json_tokens: usize,
+    /// Length of the TOON output in bytes.
+    pub bytes: usize,
+}
+
+impl EncodeStats {
+    /// Tokens saved relative to JSON; negative when TOON is larger.
+    pub fn saved_tokens(&self) -> isize {
+        self.json_tokens as isize - self.toon_tokens as isize
+    }
+}
+
+/// Encodes `value` and measures the output with [`encode_stats`].
+pub fn encode_with_stats(value: &Value, options: &EncodeOptions) -> Result<(String, EncodeStats)> {
+    let toon = encode::to_string(value, options)?;
+    let stats = encode_stats(value, &toon)?;
+    Ok((toon, stats))
+}
+
+/// Counts the tokens of `toon`, an encoding of `value`, and of `value` as
+/// compact JSON. The tokenizer is loaded once per process.
+pub fn encode_stats(value: &Value, toon: &str) -> Result<EncodeStats> {
+    let json = serde_json::to_string(value)
+        .map_err(|err| Error::serialize_with_source(format!("serialize failed: {err}"), err))?;
+    let bpe = tokenizer()?;
+    Ok(EncodeStats {
+        toon_tokens: count_tokens(bpe, toon),
+        json_tokens: count_tokens(bpe, &json),
+        bytes: toon.len(),
+    })
+}
+
+// Loaded with `cl100k_base`, which every supported tiktoken-rs release has,
+// and kept for the rest of the process.
+fn tokenizer() -> Result<&'static CoreBPE> {
+    static TOKENIZER: OnceLock<CoreBPE> = OnceLock::new();
+    if let Some(bpe) = TOKENIZER.get() {
+        return Ok(bpe);
+    }
+    let bpe =
+        cl100k_base().map_err(|err| Error::encode(format!("failed to load cl100k_base: {err}")))?;
+    Ok(TOKENIZER.get_or_init(|| bpe))
+}
+
+fn count_tokens(bpe: &CoreBPE, text: &str) -> usize {
+    bpe.encode_with_special_tokens(text).len()
+}
@@ -1,2 +1,56 @@
+use serde_json::Value;
+
+use crate::encode::Diagnostic;
+
 #[derive(Debug, Default)]
 pub struct TabularPlaceholder;
+
+/// Returns the header fields the encoder would use to write `array` in tabular
+/// form, or `None` when it would fall back to list form.
+///
+/// An array is tabular when it is non-empty and every element is an object with
+/// exactly the first row's keys (in any order) holding only scalar values.
+/// Fields follow the first row's key order. Pure: no encoder state is touched.
+pub fn is_tabular_candidate(array: &[Value]) -> Option<Vec<String>> {
+    let fields = tabular_header(array, false).ok()?;
+    Some(fields.into_iter().map(str::to_string).collect())
+}
+
+/// Header fields of a tabular `array`, or why it is not tabular. `Err(None)`
+/// means the array is empty or does not start with an object, which is not
+/// worth a diagnostic. With `empty_containers`, `[]` and `{}` count as scalars.
+pub(crate) fn tabular_header(
+    array: &[Value],
+    empty_containers: bool,
+) -> Result<Vec<&str>, Option<Diagnostic>> {
+    let first = array.first().and_then(Value::as_object).ok_or(None)?;
+    if first.is_empty() {
+        return Err(Some(Diagnostic::EmptyFirstRow));
+    }
+    let fields: Vec<&str> = first.keys().map(String::as_str).collect();
+    for (row_idx, item) in array.iter().enumerate() {
+        let Some(row) = item.as_object() else {
+            return Err(Some(Diagnostic::NonObjectRow { row: row_idx }));
+        };
+        if row.len() != fields.len() {
+            return Err(Some(Diagnostic::FieldMismatch { row: row_idx }));
+        }
+        for field in &fields {
+            let Some(value) = row.get(*field) else {
+                return Err(Some(Diagnostic::FieldMismatch { row: row_idx }));
+            };
+            let scalar = match value {
+                Value::Array(items) => empty_containers && items.is_empty(),
+                Value::Object(map) => empty_containers && map.is_empty(),
+                _ => true,
+            };
+            if !scalar {
+                return Err(Some(Diagnostic::NonScalarField {
+                    row: row_idx,
+                    field: (*field).to_string(),
+                }));
+            }
+        }
+    }
+    Ok(fields)
+}
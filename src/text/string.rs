@@ -1,6 +1,8 @@
 use memchr::{memchr, memchr2, memchr3};
 use smallvec::SmallVec;
 
+use crate::Delimiter;
+
 pub trait ByteSink {
     fn push_byte(&mut self, byte: u8);
     fn extend_bytes(&mut self, bytes: &[u8]);
@@ -81,6 +83,13 @@ macro_rules! impl_bytesink_for_smallvec {
 impl_bytesink_for_smallvec!(32);
 impl_bytesink_for_smallvec!(256);
 
+/// Whether the encoder writes `value` as a quoted string when `delimiter` is
+/// active: the document delimiter for object values, the array's own
+/// delimiter inside arrays. Ignores [`crate::EncodeOptions::with_escape_leading_special_chars`].
+pub fn needs_quoting_for(value: &str, delimiter: Delimiter) -> bool {
+    analyze_string(value, delimiter.as_char()).0
+}
+
 pub fn analyze_string(value: &str, delimiter: char) -> (bool, bool) {
     if value.is_empty() {
         return (true, false);
@@ -206,6 +215,10 @@ pub fn escape_string_into_bytes<B: ByteSink>(out: &mut B, value: &str) {
     }
 }
 
+/// Keys matching `^[A-Za-z_][A-Za-z0-9_.]*$` (SPEC §7.3) may be written bare.
+///
+/// Case is irrelevant, so `MAX_CONNECTIONS` stays unquoted; `-` is excluded
+/// because a leading hyphen marks a list item, so kebab-case keys are quoted.
 pub fn is_canonical_unquoted_key(key: &str) -> bool {
     let bytes = key.as_bytes();
     if bytes.is_empty() {
@@ -234,8 +247,93 @@ pub fn is_identifier_segment(key: &str) -> bool {
         .all(|b| b.is_ascii_alphanumeric() || *b == b'_')
 }
 
+pub fn is_valid_path_separator(separator: &str) -> bool {
+    !separator.is_empty()
+        && separator.bytes().all(|byte| {
+            byte.is_ascii_graphic()
+                && !matches!(
+                    byte,
+                    b'"' | b'\\' | b':' | b'[' | b']' | b'{' | b'}' | b',' | b'|' | b';'
+                )
+        })
+}
+
+/// A marker for [`crate::EncodeOptions::with_empty_string_marker`] must read
+/// back as a single unquoted string cell: no `delimiter`, structural
+/// character, quote, backslash or control character, no surrounding
+/// whitespace, no leading `#` or `- `, and nothing that decodes as a number,
+/// boolean or null. The empty marker is always valid.
+pub fn is_valid_empty_string_marker(marker: &str, delimiter: char) -> bool {
+    if marker.is_empty() {
+        return true;
+    }
+    !is_literal_like(marker)
+        && !marker.starts_with(char::is_whitespace)
+        && !marker.ends_with(char::is_whitespace)
+        && !marker.starts_with('#')
+        && !marker
+            .strip_prefix('-')
+            .is_some_and(|rest| rest.starts_with(char::is_whitespace))
+        && !marker.chars().any(|ch| {
+            ch == delimiter
+                || ch.is_control()
+                || matches!(ch, '"' | '\\' | '[' | ']' | '{' | '}' | ':')
+        })
+}
+
+pub fn is_separated_identifier_path(key: &str, separator: &str) -> bool {
+    key.contains(separator) && key.split(separator).all(is_identifier_segment)
+}
+
 fn is_literal_like(value: &str) -> bool {
-    is_keyword(value) || is_numeric_like(value)
+    is_keyword(value) || is_non_finite_literal(value) || is_numeric_like(value)
+}
+
+/// Splits a folded key that mixes quoted and bare segments, such as `"a b".c`
+/// from [`crate::KeyFolding::Aggressive`], at `separator`. Quoted segments are
+/// returned with their quotes. `None` unless there are at least two segments,
+/// one of them quoted, and every bare segment is an identifier.
+pub fn split_quoted_path<'a>(key: &'a str, separator: &str) -> Option<Vec<&'a str>> {
+    if separator.is_empty() || !key.contains('"') {
+        return None;
+    }
+    let bytes = key.as_bytes();
+    let mut segments = Vec::new();
+    let mut quoted = false;
+    let mut start = 0;
+    loop {
+        let end = if bytes.get(start) == Some(&b'"') {
+            quoted = true;
+            let mut idx = start + 1;
+            loop {
+                match bytes.get(idx)? {
+                    b'\\' => idx += 2,
+                    b'"' => break idx + 1,
+                    _ => idx += 1,
+                }
+            }
+        } else {
+            let rest = &key[start..];
+            let len = rest.find(separator).unwrap_or(rest.len());
+            if !is_identifier_segment(&rest[..len]) {
+                return None;
+            }
+            start + len
+        };
+        segments.push(&key[start..end]);
+        if end == key.len() {
+            break;
+        }
+        if !key[end..].starts_with(separator) {
+            return None;
+        }
+        start = end + separator.len();
+    }
+    (quoted && segments.len() > 1).then_some(segments)
+}
+
+pub fn is_non_finite_literal(value: &str) -> bool {
+    matches!(value, "NaN" | "Infinity" | "-Infinity" | "+Infinity")
 }
 
 fn is_keyword(value: &str) -> bool {
@@ -0,0 +1,74 @@
+use serde_json::{json, Value};
+use serde_toon::checksum::crc32;
+use serde_toon::{DecodeOptions, EncodeOptions};
+
+fn checksummed(value: &Value) -> String {
+    serde_toon::to_string_with_options(value, &EncodeOptions::new().with_checksum(true)).unwrap()
+}
+
+fn verifying() -> DecodeOptions {
+    DecodeOptions::new().with_verify_checksum(true)
+}
+
+#[test]
+fn crc32_matches_reference_vector() {
+    assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    assert_eq!(crc32(b""), 0);
+}
+
+#[test]
+fn checksum_line_is_appended_and_verified() {
+    let value = json!({"name": "Ada", "tags": ["a", "b"]});
+    let toon = checksummed(&value);
+    let body = "name: Ada\ntags[2]: a,b";
+    assert_eq!(
+        toon,
+        format!("{body}\n#checksum: {:08x}", crc32(body.as_bytes()))
+    );
+
+    let decoded: Value = serde_toon::from_str_with_options(&toon, &verifying()).unwrap();
+    assert_eq!(decoded, value);
+    assert_eq!(
+        serde_toon::decode_to_value_with_options(&toon, &verifying()).unwrap(),
+        value
+    );
+    assert!(serde_toon::validate_str_with_options(&toon, &verifying()).is_ok());
+}
+
+#[test]
+fn checksum_line_is_ignored_without_verification() {
+    let value = json!({"name": "Ada"});
+    let toon = checksummed(&value);
+    let decoded: Value = serde_toon::from_str(&toon).unwrap();
+    assert_eq!(decoded, value);
+    assert!(serde_toon::validate_str(&toon).is_ok());
+}
+
+#[test]
+fn tampered_document_fails_verification() {
+    let toon = checksummed(&json!({"name": "Ada", "age": 37}));
+    let tampered = toon.replace("37", "38");
+    let err = serde_toon::from_str_with_options::<Value>(&tampered, &verifying()).unwrap_err();
+    assert!(err.to_string().contains("checksum mismatch"), "{err}");
+
+    let truncated = toon.replacen("name: Ada\n", "", 1);
+    assert!(serde_toon::decode_to_value_with_options(&truncated, &verifying()).is_err());
+}
+
+#[test]
+fn verification_requires_checksum_line() {
+    let err = serde_toon::from_str_with_options::<Value>("name: Ada", &verifying()).unwrap_err();
+    assert!(err.to_string().contains("missing checksum line"), "{err}");
+    assert!(
+        serde_toon::from_str_with_options::<Value>("name: Ada\n#checksum: zz", &verifying())
+            .is_err()
+    );
+}
+
+#[test]
+fn empty_document_checksum_round_trips() {
+    let toon = checksummed(&json!({}));
+    assert_eq!(toon, "#checksum: 00000000");
+    let decoded: Value = serde_toon::from_str_with_options(&toon, &verifying()).unwrap();
+    assert_eq!(decoded, json!({}));
+}
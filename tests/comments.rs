@@ -0,0 +1,284 @@
+use serde_json::{json, Value};
+use serde_toon::{Comment, DecodeOptions, EncodeOptions};
+
+fn capturing() -> DecodeOptions {
+    DecodeOptions::new().with_capture_comments(true)
+}
+
+fn comment(path: Option<&str>, text: &str) -> Comment {
+    Comment {
+        path: path.map(str::to_string),
+        text: text.to_string(),
+    }
+}
+
+const CONFIG: &str = "# service config\nname: api\nserver:\n  # listen address\n  host: localhost\n  port: 8080\ntags[2]:\n  # primary first\n  - a\n  - b\n# end";
+
+#[test]
+fn comments_are_captured_with_their_paths() {
+    let (value, comments): (Value, _) =
+        serde_toon::from_str_with_comments(CONFIG, &capturing()).unwrap();
+    assert_eq!(
+        value,
+        json!({"name": "api", "server": {"host": "localhost", "port": 8080}, "tags": ["a", "b"]})
+    );
+    assert_eq!(
+        comments,
+        vec![
+            comment(Some("name"), "service config"),
+            comment(Some("server.host"), "listen address"),
+            comment(Some("tags"), "primary first"),
+            comment(None, "end"),
+        ]
+    );
+}
+
+#[test]
+fn comments_survive_a_decode_encode_cycle() {
+    let (value, comments): (Value, _) =
+        serde_toon::from_str_with_comments(CONFIG, &capturing()).unwrap();
+    let out =
+        serde_toon::to_string_with_options(&value, &EncodeOptions::new().with_comments(comments))
+            .unwrap();
+    assert_eq!(
+        out,
+        "# service config\nname: api\nserver:\n  # listen address\n  host: localhost\n  port: 8080\n# primary first\ntags[2]: a,b\n# end"
+    );
+}
+
+#[test]
+fn root_array_comments_precede_the_header() {
+    let input = "# users\n[2]{id}:\n  1\n  2";
+    let (value, comments): (Value, _) =
+        serde_toon::from_str_with_comments(input, &capturing()).unwrap();
+    assert_eq!(comments, vec![comment(Some(""), "users")]);
+    let out =
+        serde_toon::to_string_with_options(&value, &EncodeOptions::new().with_comments(comments))
+            .unwrap();
+    assert_eq!(out, input);
+}
+
+#[test]
+fn comments_for_missing_paths_are_dropped() {
+    let out = serde_toon::to_string_with_options(
+        &json!({"a": 1}),
+        &EncodeOptions::new().with_comments(vec![comment(Some("b"), "gone")]),
+    )
+    .unwrap();
+    assert_eq!(out, "a: 1");
+}
+
+#[test]
+fn comment_lines_are_dropped_by_from_str_when_capturing() {
+    let value: Value = serde_toon::from_str_with_options(CONFIG, &capturing()).unwrap();
+    assert_eq!(value["server"]["port"], json!(8080));
+    assert!(serde_toon::validate_str_with_options(CONFIG, &capturing()).is_ok());
+}
+
+#[test]
+fn comment_lines_are_not_special_by_default() {
+    assert!(serde_toon::from_str::<Value>(CONFIG).is_err());
+}
+
+#[test]
+fn comments_and_checksum_round_trip() {
+    let out = serde_toon::to_string_with_options(
+        &json!({"a": 1}),
+        &EncodeOptions::new()
+            .with_checksum(true)
+            .with_comments(vec![comment(Some("a"), "first")]),
+    )
+    .unwrap();
+    assert!(out.starts_with("# first\na: 1\n#checksum: "));
+    let options = capturing().with_verify_checksum(true);
+    let (value, comments): (Value, _) = serde_toon::from_str_with_comments(&out, &options).unwrap();
+    assert_eq!(value, json!({"a": 1}));
+    assert_eq!(comments, vec![comment(Some("a"), "first")]);
+}
+
+fn comment_key() -> EncodeOptions {
+    EncodeOptions::new().with_comment_key(Some("_comment".to_string()))
+}
+
+#[test]
+fn comment_key_entries_are_written_as_comment_lines() {
+    let value = json!({
+        "_comment": "service config",
+        "name": "api",
+        "server": {"host": "localhost", "_comment": "listen port\nbehind the proxy", "port": 8080},
+        "_comment_tail": 1,
+    });
+    let out = serde_toon::to_string_with_options(&value, &comment_key()).unwrap();
+    assert_eq!(
+        out,
+        "# service config\nname: api\nserver:\n  host: localhost\n  # listen port\n  # behind the proxy\n  port: 8080\n_comment_tail: 1"
+    );
+    let (decoded, comments): (Value, _) =
+        serde_toon::from_str_with_comments(&out, &capturing()).unwrap();
+    assert_eq!(
+        decoded,
+        json!({"name": "api", "server": {"host": "localhost", "port": 8080}, "_comment_tail": 1})
+    );
+    assert_eq!(
+        comments,
+        vec![
+            comment(Some("name"), "service config"),
+            comment(Some("server.port"), "listen port"),
+            comment(Some("server.port"), "behind the proxy"),
+        ]
+    );
+}
+
+#[test]
+fn comment_key_without_a_following_field_trails_the_object() {
+    let out = serde_toon::to_string_with_options(
+        &json!({"a": {"b": 1, "_comment": "end of a"}, "c": 2}),
+        &comment_key(),
+    )
+    .unwrap();
+    assert_eq!(out, "a:\n  b: 1\n  # end of a\nc: 2");
+}
+
+#[test]
+fn comment_key_entries_inside_arrays_are_written_as_comment_lines() {
+    let value = json!({
+        "items": [
+            {"_comment": "hello", "id": 1, "_comment_tail": "x", "name": "a"},
+            {"id": 2, "_comment": "last"},
+            {"_comment": "only"}
+        ]
+    });
+    let out = serde_toon::to_string_with_options(&value, &comment_key()).unwrap();
+    assert_eq!(
+        out,
+        "items[3]:\n  # hello\n  - id: 1\n    _comment_tail: x\n    name: a\n  - id: 2\n    # last\n  # only\n  -"
+    );
+    let decoded: Value = serde_toon::from_str_with_options(&out, &capturing()).unwrap();
+    assert_eq!(
+        decoded,
+        json!({"items": [{"id": 1, "_comment_tail": "x", "name": "a"}, {"id": 2}, {}]})
+    );
+
+    let rows = json!([{"_comment": "a", "id": 1}, {"_comment": "b", "id": 2}]);
+    let out = serde_toon::to_string_with_options(&rows, &comment_key()).unwrap();
+    assert_eq!(out, "[2]:\n  # a\n  - id: 1\n  # b\n  - id: 2");
+}
+
+#[test]
+fn comment_key_with_a_non_string_value_stays_data() {
+    let value = json!({"_comment": 1, "a": 2});
+    let out = serde_toon::to_string_with_options(&value, &comment_key()).unwrap();
+    assert_eq!(out, "_comment: 1\na: 2");
+    let plain = serde_toon::to_string(&json!({"_comment": "x", "a": 2})).unwrap();
+    assert_eq!(plain, "_comment: x\na: 2");
+}
+
+#[test]
+fn comment_key_output_needs_capture_comments_to_decode() {
+    let out =
+        serde_toon::to_string_with_options(&json!({"_comment": "note", "a": 1}), &comment_key())
+            .unwrap();
+    assert!(serde_toon::from_str::<Value>(&out).is_err());
+    let decoded: Value = serde_toon::from_str_with_options(&out, &capturing()).unwrap();
+    assert_eq!(decoded, json!({"a": 1}));
+}
+
+fn allowing() -> DecodeOptions {
+    DecodeOptions::new().with_allow_comments(true)
+}
+
+fn decode_both(input: &str, options: &DecodeOptions) -> Value {
+    let arena: Value = serde_toon::from_str_with_options(input, options).unwrap();
+    let (tree, _): (Value, _) = serde_toon::from_str_with_diagnostics(input, options).unwrap();
+    assert_eq!(arena, tree, "{input}");
+    assert!(serde_toon::validate_str_with_options(input, options).is_ok());
+    arena
+}
+
+#[test]
+fn allowed_comments_are_skipped_like_blank_lines() {
+    let input = "# leading\nname: api\nserver:\n  # indented\n  host: localhost\n\t# tab-indented\n  port: 8080\n# trailing";
+    let value = decode_both(input, &allowing());
+    assert_eq!(
+        value,
+        json!({"name": "api", "server": {"host": "localhost", "port": 8080}})
+    );
+}
+
+#[test]
+fn allowed_comments_leave_hashes_in_values_alone() {
+    let input = "value: \"# not a comment\"\nnote: a # b\n\"#key\": 1";
+    let value = decode_both(input, &allowing());
+    assert_eq!(
+        value,
+        json!({"value": "# not a comment", "note": "a # b", "#key": 1})
+    );
+}
+
+#[test]
+fn allowed_comments_inside_arrays_follow_blank_line_rules() {
+    let input = "items[2]:\n  # first\n  - a\n  - b";
+    assert!(serde_toon::from_str_with_options::<Value>(input, &allowing()).is_err());
+    let value = decode_both(input, &allowing().with_strict(false));
+    assert_eq!(value, json!({"items": ["a", "b"]}));
+}
+
+#[test]
+fn allowed_comments_do_not_swallow_hash_leading_rows() {
+    let input = "rows[2]{a,b}:\n  #x,1\n  y,2\n# note\nok: true";
+    let value = decode_both(input, &allowing());
+    assert_eq!(
+        value,
+        json!({"rows": [{"a": "#x", "b": 1}, {"a": "y", "b": 2}], "ok": true})
+    );
+    assert!(serde_toon::validate_reader_with_options(input.as_bytes(), &allowing()).is_ok());
+
+    let input = "m[2x2]:\n  #a,1\n  b,2";
+    let options = allowing().with_matrix_tabular(true);
+    let value = decode_both(input, &options);
+    assert_eq!(value, json!({"m": [["#a", 1], ["b", 2]]}));
+    assert!(serde_toon::validate_reader_with_options(input.as_bytes(), &options).is_ok());
+}
+
+#[test]
+fn hash_leading_values_are_quoted_when_the_output_has_comment_lines() {
+    let input = json!({
+        "_comment": "about rows",
+        "rows": [{"a": "#x", "b": 1}, {"a": "y", "b": 2}],
+        "tag": "#y",
+    });
+    let out = serde_toon::to_string_with_options(&input, &comment_key()).unwrap();
+    assert_eq!(
+        out,
+        "# about rows\nrows[2]{a,b}:\n  \"#x\",1\n  y,2\ntag: \"#y\""
+    );
+    let expected = json!({"rows": [{"a": "#x", "b": 1}, {"a": "y", "b": 2}], "tag": "#y"});
+    assert_eq!(decode_both(&out, &allowing()), expected);
+    let decoded: Value = serde_toon::from_str_with_options(&out, &capturing()).unwrap();
+    assert_eq!(decoded, expected);
+
+    let options = EncodeOptions::new().with_checksum(true);
+    let out = serde_toon::to_string_with_options(&json!("#root"), &options).unwrap();
+    assert!(out.starts_with("\"#root\"\n"), "{out}");
+    let decode = allowing().with_verify_checksum(true);
+    let decoded: Value = serde_toon::from_str_with_options(&out, &decode).unwrap();
+    assert_eq!(decoded, json!("#root"));
+}
+
+#[test]
+fn captured_comments_leave_hash_leading_rows_in_place() {
+    let input = "# rows\nrows[2]{a,b}:\n  #x,1\n  y,2\n# after\nm[1x2]:\n  #a,1";
+    let options = capturing().with_matrix_tabular(true);
+    let (value, comments): (Value, _) =
+        serde_toon::from_str_with_comments(input, &options).unwrap();
+    assert_eq!(
+        value,
+        json!({"rows": [{"a": "#x", "b": 1}, {"a": "y", "b": 2}], "m": [["#a", 1]]})
+    );
+    assert_eq!(
+        comments,
+        vec![comment(Some("rows"), "rows"), comment(Some("m"), "after")]
+    );
+    assert!(serde_toon::validate_str_with_options(input, &options).is_ok());
+    assert!(serde_toon::validate_reader_with_options(input.as_bytes(), &options).is_ok());
+}
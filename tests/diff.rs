@@ -0,0 +1,69 @@
+use serde_json::json;
+use serde_toon::{diff, diff_values, DecodeOptions, DiffKind, ValueDiff};
+
+#[test]
+fn diff_ignores_key_order_and_formatting() {
+    let left = "name: Ada\nitems[2]: 1,2";
+    let right = "items[2|]: 1|2\nname: \"Ada\"";
+    let diffs = diff(left, right, &DecodeOptions::default()).unwrap();
+    assert!(diffs.is_empty());
+}
+
+#[test]
+fn diff_reports_paths_and_kinds() {
+    let left = "user:\n  name: Ada\n  age: 36\ntags[2]: a,b";
+    let right = "user:\n  name: Ada\n  age: 37\n  role: admin\ntags[1]: a";
+    let diffs = diff(left, right, &DecodeOptions::default()).unwrap();
+    assert_eq!(
+        diffs,
+        vec![
+            ValueDiff {
+                path: "user.age".to_string(),
+                kind: DiffKind::Changed,
+                left: Some(json!(36)),
+                right: Some(json!(37)),
+            },
+            ValueDiff {
+                path: "user.role".to_string(),
+                kind: DiffKind::Added,
+                left: None,
+                right: Some(json!("admin")),
+            },
+            ValueDiff {
+                path: "tags[1]".to_string(),
+                kind: DiffKind::Removed,
+                left: Some(json!("b")),
+                right: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn diff_quotes_keys_that_would_be_ambiguous_in_paths() {
+    let paths = |left, right| {
+        diff_values(&left, &right)
+            .into_iter()
+            .map(|diff| diff.path)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        paths(json!({"a": {"b": 1}}), json!({"a": {"b": 2}})),
+        ["a.b"]
+    );
+    assert_eq!(paths(json!({"a.b": 1}), json!({"a.b": 2})), ["\"a.b\""]);
+    assert_eq!(
+        paths(json!({"x": {"k[0]": 1}}), json!({"x": {"k[0]": 2}})),
+        ["x.\"k[0]\""]
+    );
+    assert_eq!(
+        paths(json!({"say \"hi\"": 1}), json!({"say \"hi\"": 2})),
+        ["\"say \\\"hi\\\"\""]
+    );
+    assert_eq!(paths(json!({"": 1}), json!({"": 2})), ["\"\""]);
+}
+
+#[test]
+fn diff_propagates_decode_errors() {
+    assert!(diff("a: 1", "a: \"open", &DecodeOptions::default()).is_err());
+}
@@ -0,0 +1,124 @@
+use std::error::Error as _;
+use std::io::{self, Read, Write};
+
+use serde_json::Value;
+use serde_toon::{DecodeOptions, ErrorKind};
+
+fn chain(err: &(dyn std::error::Error + 'static)) -> Vec<String> {
+    let mut messages = Vec::new();
+    let mut current = err.source();
+    while let Some(source) = current {
+        messages.push(source.to_string());
+        current = source.source();
+    }
+    messages
+}
+
+#[test]
+fn utf8_errors_expose_utf8_error() {
+    let err = serde_toon::from_slice::<Value>(b"a: \xff").unwrap_err();
+    assert_eq!(err.kind, ErrorKind::Decode);
+    assert!(err
+        .source()
+        .unwrap()
+        .downcast_ref::<std::str::Utf8Error>()
+        .is_some());
+}
+
+struct Failing;
+
+impl Read for Failing {
+    fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+        Err(io::Error::other("disk gone"))
+    }
+}
+
+impl Write for Failing {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::Error::other("disk gone"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn io_errors_expose_io_error() {
+    let read = serde_toon::from_reader::<Value, _>(Failing).unwrap_err();
+    let source = read.source().unwrap().downcast_ref::<io::Error>().unwrap();
+    assert_eq!(source.to_string(), "disk gone");
+
+    let write = serde_toon::to_writer(Failing, &serde_json::json!({"a": 1})).unwrap_err();
+    assert_eq!(write.kind, ErrorKind::Encode);
+    assert!(write
+        .source()
+        .unwrap()
+        .downcast_ref::<io::Error>()
+        .is_some());
+}
+
+#[test]
+fn deserialize_errors_expose_the_deserializer_error() {
+    let err = serde_toon::from_str::<Vec<u8>>("a: 1").unwrap_err();
+    assert_eq!(err.kind, ErrorKind::Deserialize);
+    assert_eq!(chain(&err).len(), 1);
+
+    let options = DecodeOptions::new().with_inline_objects(true);
+    let err = serde_toon::from_str_with_options::<Vec<u8>>("a: 1", &options).unwrap_err();
+    assert!(err
+        .source()
+        .unwrap()
+        .downcast_ref::<serde_json::Error>()
+        .is_some());
+}
+
+#[test]
+fn json_input_errors_expose_serde_json_error() {
+    let err = serde_toon::to_string_from_json_str("{").unwrap_err();
+    assert_eq!(err.kind, ErrorKind::Serialize);
+    assert!(err
+        .source()
+        .unwrap()
+        .downcast_ref::<serde_json::Error>()
+        .is_some());
+}
+
+#[test]
+fn parse_errors_expose_parse_int_error() {
+    let err = serde_toon::decode_to_value("a[99999999999999999999999]: x").unwrap_err();
+    assert_eq!(err.to_string(), "invalid array length");
+    assert!(err
+        .source()
+        .unwrap()
+        .downcast_ref::<std::num::ParseIntError>()
+        .is_some());
+
+    let options = DecodeOptions::new().with_verify_checksum(true);
+    let err =
+        serde_toon::decode_to_value_with_options("a: 1\n#checksum: zz", &options).unwrap_err();
+    assert!(err
+        .source()
+        .unwrap()
+        .downcast_ref::<std::num::ParseIntError>()
+        .is_some());
+}
+
+#[test]
+fn auto_decode_chains_the_toon_error() {
+    let err = serde_toon::decode_to_value_auto("a: \"open").unwrap_err();
+    let chain = chain(&err);
+    assert_eq!(chain, vec!["unterminated string".to_string()]);
+    let toon = err
+        .source()
+        .unwrap()
+        .downcast_ref::<serde_toon::Error>()
+        .unwrap();
+    assert!(toon.is_recoverable());
+}
+
+#[test]
+fn errors_without_an_underlying_cause_have_no_source() {
+    let err = serde_toon::decode_to_value("a: \"open").unwrap_err();
+    assert!(err.source().is_none());
+}
@@ -0,0 +1,25 @@
+use serde_json::json;
+use serde_toon::{estimate_token_ratio, estimate_tokens};
+
+#[test]
+fn estimate_tokens_counts_runs() {
+    assert_eq!(estimate_tokens(""), 0);
+    assert_eq!(estimate_tokens("name: Ada"), 3);
+    assert_eq!(estimate_tokens("{\"id\":123456}"), 6);
+    assert_eq!(estimate_tokens("a:\n  b: 1"), 8);
+}
+
+#[test]
+fn estimate_token_ratio_favors_tabular_arrays() {
+    let rows: Vec<_> = (0..50)
+        .map(|idx| json!({"id": idx, "name": format!("user_{idx}"), "active": idx % 2 == 0}))
+        .collect();
+    let ratio = estimate_token_ratio(&json!({ "users": rows }));
+    assert!(ratio < 0.7, "ratio was {ratio}");
+}
+
+#[test]
+fn estimate_token_ratio_handles_empty_values() {
+    assert_eq!(estimate_token_ratio(&json!({})), 0.0);
+    assert!(estimate_token_ratio(&json!("hello")) > 0.0);
+}
@@ -0,0 +1,109 @@
+use serde_toon::DecodeOptions;
+
+const DOC: &str = "id: 7\npayload: aGVsbG8gd29ybGQ=\nquoted: \"a, b\"\nescaped: \"line\\nbreak\"\nusers[2]{name,role}:\n  Ada,admin\n  Bob,\"\"\nmeta:\n  tags[2]: x,y\n  owner:\n    name: Grace";
+
+fn span(path: &str) -> Option<&'static str> {
+    serde_toon::raw_str_span(DOC, path, &DecodeOptions::default())
+}
+
+fn offset_in_doc(slice: &str) -> usize {
+    slice.as_ptr() as usize - DOC.as_ptr() as usize
+}
+
+#[test]
+fn raw_str_span_borrows_unescaped_strings() {
+    let payload = span("payload").unwrap();
+    assert_eq!(payload, "aGVsbG8gd29ybGQ=");
+    assert_eq!(&DOC[offset_in_doc(payload)..][..payload.len()], payload);
+
+    assert_eq!(span("quoted"), Some("a, b"));
+    assert_eq!(span("users[0].name"), Some("Ada"));
+    assert_eq!(span("users[1].role"), Some(""));
+    assert_eq!(span("meta.tags[1]"), Some("y"));
+    assert_eq!(span("meta.owner.name"), Some("Grace"));
+}
+
+#[test]
+fn raw_str_span_rejects_escapes_non_strings_and_missing_paths() {
+    assert_eq!(span("escaped"), None);
+    assert_eq!(span("id"), None);
+    assert_eq!(span("meta"), None);
+    assert_eq!(span("missing"), None);
+    assert_eq!(span("users[2].name"), None);
+    assert_eq!(span("meta.tags.x"), None);
+    assert_eq!(span("payload[0]"), None);
+}
+
+#[test]
+fn raw_str_span_handles_root_arrays_and_checksums() {
+    let options = DecodeOptions::default();
+    assert_eq!(
+        serde_toon::raw_str_span("[2]: a,b", "[1]", &options),
+        Some("b")
+    );
+    assert_eq!(serde_toon::raw_str_span("\"hi\"", "", &options), Some("hi"));
+
+    let value = serde_json::json!({"key": "value"});
+    let toon = serde_toon::to_string_with_options(
+        &value,
+        &serde_toon::EncodeOptions::new().with_checksum(true),
+    )
+    .unwrap();
+    let verifying = DecodeOptions::new().with_verify_checksum(true);
+    assert_eq!(
+        serde_toon::raw_str_span(&toon, "key", &verifying),
+        Some("value")
+    );
+    let tampered = toon.replace("value", "other");
+    assert_eq!(serde_toon::raw_str_span(&tampered, "key", &verifying), None);
+    assert_eq!(
+        serde_toon::raw_str_span("a: 1\n  b: [", "a", &options),
+        None
+    );
+}
+
+#[test]
+fn parse_to_arena_exposes_tabular_structure() {
+    use serde_toon::arena::{NodeData, NodeKind};
+
+    let input = "users[2]{id,name}:\n  1,Ada\n  2,Bob";
+    let arena = serde_toon::parse_to_arena(input, &DecodeOptions::default()).unwrap();
+    let root = &arena.nodes[arena.root];
+    assert_eq!(root.kind, NodeKind::Object);
+    let pairs = arena.pairs(root);
+    assert_eq!(pairs.len(), 1);
+    assert_eq!(arena.get_key(pairs[0].key), Some("users"));
+
+    let users = &arena.nodes[pairs[0].value];
+    assert_eq!(users.kind, NodeKind::Array);
+    let rows = arena.children(users);
+    assert_eq!(rows.len(), 2);
+    for (&row, (id, name)) in rows.iter().zip([("1", "Ada"), ("2", "Bob")]) {
+        let row = &arena.nodes[row];
+        assert_eq!(row.kind, NodeKind::Object);
+        let fields = arena.pairs(row);
+        assert_eq!(fields.len(), 2);
+        assert_eq!(arena.get_key(fields[0].key), Some("id"));
+        assert_eq!(arena.get_key(fields[1].key), Some("name"));
+
+        let id_node = &arena.nodes[fields[0].value];
+        assert_eq!(id_node.kind, NodeKind::Number);
+        let NodeData::Number(index) = id_node.data else {
+            panic!("expected number data, got {:?}", id_node.data);
+        };
+        assert_eq!(arena.get_num_str(index), Some(id));
+
+        let name_node = &arena.nodes[fields[1].value];
+        assert_eq!(name_node.kind, NodeKind::String);
+        let NodeData::String(index) = name_node.data else {
+            panic!("expected string data, got {:?}", name_node.data);
+        };
+        assert_eq!(arena.get_str(index), Some(name));
+    }
+}
+
+#[test]
+fn parse_to_arena_reports_parse_errors() {
+    let err = serde_toon::parse_to_arena("users[3]: a,b", &DecodeOptions::default()).unwrap_err();
+    assert!(err.to_string().contains("length mismatch"), "{err}");
+}
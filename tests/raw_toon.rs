@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use serde_toon::{DecodeOptions, ExpandPaths, RawToon};
+
+#[derive(Debug, Deserialize)]
+struct Envelope {
+    kind: String,
+    payload: RawToon,
+}
+
+#[test]
+fn raw_toon_captures_nested_object_source() {
+    let input =
+        "kind: user\npayload:\n  name: Ada\n  tags[2]: a,b\n  owner:\n    id: 7\n\nafter: 1";
+    let envelope: Envelope = serde_toon::from_str(input).unwrap();
+    assert_eq!(envelope.kind, "user");
+    assert_eq!(
+        envelope.payload.get(),
+        "name: Ada\ntags[2]: a,b\nowner:\n  id: 7"
+    );
+    let payload: Value = serde_toon::from_str(envelope.payload.get()).unwrap();
+    assert_eq!(
+        payload,
+        json!({"name": "Ada", "tags": ["a", "b"], "owner": {"id": 7}})
+    );
+}
+
+#[test]
+fn raw_toon_captures_arrays_without_their_key() {
+    let input = "kind: rows\npayload[2]{id,name}:\n  1,Ada\n  2,\"Bo, B\"";
+    let envelope: Envelope = serde_toon::from_str(input).unwrap();
+    assert_eq!(
+        envelope.payload.get(),
+        "[2]{id,name}:\n  1,Ada\n  2,\"Bo, B\""
+    );
+
+    let input = "meta:\n  kind: list\n  payload[2]:\n    - a: 1\n      b: 2\n    - [1]: x";
+    let value: Value = serde_toon::from_str(input).unwrap();
+    #[derive(Deserialize)]
+    struct Meta {
+        meta: Envelope,
+    }
+    let meta: Meta = serde_toon::from_str(input).unwrap();
+    assert_eq!(
+        meta.meta.payload.get(),
+        "[2]:\n  - a: 1\n    b: 2\n  - [1]: x"
+    );
+    let payload: Value = serde_toon::from_str(meta.meta.payload.get()).unwrap();
+    assert_eq!(payload, value["meta"]["payload"]);
+}
+
+#[test]
+fn raw_toon_captures_list_item_objects() {
+    #[derive(Deserialize)]
+    struct Doc {
+        items: Vec<RawToon>,
+    }
+    let input = "items[3]:\n  - id: 1\n    tags[1]: x\n  - 42\n  - inline: \"a, b\"";
+    let doc: Doc = serde_toon::from_str(input).unwrap();
+    let raw: Vec<&str> = doc.items.iter().map(RawToon::get).collect();
+    assert_eq!(raw, ["id: 1\ntags[1]: x", "42", "inline: \"a, b\""]);
+}
+
+#[test]
+fn raw_toon_re_encodes_scalars_and_tree_decoded_values() {
+    let envelope: Envelope = serde_toon::from_str("kind: s\npayload: \"a, b\"").unwrap();
+    assert_eq!(envelope.payload.get(), "\"a, b\"");
+
+    let options = DecodeOptions::new().with_expand_paths(ExpandPaths::Safe);
+    let envelope: Envelope =
+        serde_toon::from_str_with_options("kind: x\npayload.a.b: 1", &options).unwrap();
+    assert_eq!(envelope.payload.get(), "a:\n  b: 1");
+}
+
+#[test]
+fn raw_toon_serializes_as_its_value() {
+    #[derive(Serialize, Deserialize)]
+    struct Forward {
+        id: u32,
+        payload: RawToon,
+    }
+    let input = "id: 1\npayload:\n  x: 1\n  y[2]: 2,3";
+    let forward: Forward = serde_toon::from_str(input).unwrap();
+    assert_eq!(serde_toon::to_string(&forward).unwrap(), input);
+}
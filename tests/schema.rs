@@ -0,0 +1,75 @@
+#![cfg(feature = "jsonschema")]
+
+use serde::Deserialize;
+use serde_json::json;
+use serde_toon::{from_str_validated, DecodeOptions};
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct User {
+    name: String,
+    age: u32,
+}
+
+fn user_schema() -> serde_json::Value {
+    json!({
+        "type": "object",
+        "required": ["name", "age"],
+        "properties": {
+            "name": {"type": "string", "minLength": 1},
+            "age": {"type": "integer", "minimum": 0, "maximum": 150}
+        }
+    })
+}
+
+#[test]
+fn schema_valid_document_deserializes() {
+    let user: User = from_str_validated(
+        "name: Ada\nage: 36",
+        &user_schema(),
+        &DecodeOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        user,
+        User {
+            name: "Ada".to_string(),
+            age: 36
+        }
+    );
+}
+
+#[test]
+fn schema_violations_are_reported_with_paths() {
+    let err = from_str_validated::<User>(
+        "name: \"\"\nage: 200",
+        &user_schema(),
+        &DecodeOptions::default(),
+    )
+    .unwrap_err();
+    let message = err.to_string();
+    assert!(message.contains("schema validation failed"), "{message}");
+    assert!(message.contains("/name: "), "{message}");
+    assert!(
+        message.contains("/age: 200 is greater than the maximum of 150"),
+        "{message}"
+    );
+}
+
+#[test]
+fn schema_root_violation_and_invalid_schema() {
+    let err = from_str_validated::<User>("name: Ada", &user_schema(), &DecodeOptions::default())
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("/: \"age\" is a required property"),
+        "{err}"
+    );
+
+    let err = from_str_validated::<User>(
+        "name: Ada\nage: 1",
+        &json!({"type": "nope"}),
+        &DecodeOptions::default(),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("invalid schema"), "{err}");
+}
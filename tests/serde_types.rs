@@ -0,0 +1,627 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+enum Direction {
+    North,
+    East,
+    South,
+    #[serde(rename = "south-west: 45°")]
+    SouthWest,
+}
+
+#[test]
+fn enum_keys_encode_as_variant_names() {
+    let mut map = BTreeMap::new();
+    map.insert(Direction::North, 1);
+    map.insert(Direction::East, 2);
+    map.insert(Direction::SouthWest, 3);
+
+    let toon = serde_toon::to_string(&map).unwrap();
+    assert_eq!(toon, "North: 1\nEast: 2\n\"south-west: 45°\": 3");
+}
+
+#[test]
+fn enum_keys_round_trip() {
+    let mut map = HashMap::new();
+    map.insert(Direction::North, 1);
+    map.insert(Direction::East, -2);
+    map.insert(Direction::South, 0);
+    map.insert(Direction::SouthWest, 45);
+
+    let toon = serde_toon::to_string(&map).unwrap();
+    let decoded: HashMap<Direction, i32> = serde_toon::from_str(&toon).unwrap();
+    assert_eq!(decoded, map);
+}
+
+#[test]
+fn enum_keys_reject_unknown_variant() {
+    let result = serde_toon::from_str::<HashMap<Direction, i32>>("West: 1");
+    assert!(result.is_err());
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Summary {
+    name: String,
+    count: u32,
+}
+
+#[test]
+fn unknown_fields_are_skipped() {
+    let toon = "name: Ada\nhistory[2]{year,event}:\n  1842,notes\n  1843,\"published, finally\"\nmeta:\n  tags[3]: a,b,c\n  nested:\n    deep: true\ncount: 3";
+    let summary: Summary = serde_toon::from_str(toon).unwrap();
+    assert_eq!(
+        summary,
+        Summary {
+            name: "Ada".to_string(),
+            count: 3
+        }
+    );
+}
+
+#[test]
+fn ignored_any_accepts_any_document() {
+    for toon in ["a: 1", "[2]: x,y", "items[1]:\n  - k: v", "\"text\"", ""] {
+        serde_toon::from_str::<serde::de::IgnoredAny>(toon).unwrap();
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct UserId(i64);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Label(String);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Position(Point);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Row {
+    id: UserId,
+    label: Label,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Record {
+    id: UserId,
+    position: Position,
+    rows: Vec<Row>,
+    ids: Vec<UserId>,
+}
+
+#[test]
+fn newtype_structs_encode_like_inner_value() {
+    let record = Record {
+        id: UserId(7),
+        position: Position(Point { x: 1, y: -2 }),
+        rows: vec![
+            Row {
+                id: UserId(1),
+                label: Label("a".to_string()),
+            },
+            Row {
+                id: UserId(2),
+                label: Label("b c".to_string()),
+            },
+        ],
+        ids: vec![UserId(3), UserId(4)],
+    };
+    let toon = serde_toon::to_string(&record).unwrap();
+    assert_eq!(
+        toon,
+        "id: 7\nposition:\n  x: 1\n  y: -2\nrows[2]{id,label}:\n  1,a\n  2,b c\nids[2]: 3,4"
+    );
+    let decoded: Record = serde_toon::from_str(&toon).unwrap();
+    assert_eq!(decoded, record);
+}
+
+#[test]
+fn newtype_structs_round_trip_at_root() {
+    let toon = serde_toon::to_string(&UserId(42)).unwrap();
+    assert_eq!(toon, "42");
+    assert_eq!(serde_toon::from_str::<UserId>(&toon).unwrap(), UserId(42));
+
+    let position = Position(Point { x: 3, y: 4 });
+    let toon = serde_toon::to_string(&position).unwrap();
+    assert_eq!(toon, "x: 3\ny: 4");
+    assert_eq!(serde_toon::from_str::<Position>(&toon).unwrap(), position);
+
+    let label = Label("hello".to_string());
+    let toon = serde_toon::to_string(&label).unwrap();
+    assert_eq!(serde_toon::from_str::<Label>(&toon).unwrap(), label);
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Glyph {
+    ch: char,
+}
+
+#[test]
+fn char_values_encode_as_strings() {
+    let cases = [
+        ('a', "ch: a"),
+        (',', "ch: \",\""),
+        (':', "ch: \":\""),
+        ('"', "ch: \"\\\"\""),
+        ('7', "ch: \"7\""),
+        (' ', "ch: \" \""),
+        ('世', "ch: 世"),
+        ('😀', "ch: 😀"),
+    ];
+    for (ch, expected) in cases {
+        let toon = serde_toon::to_string(&Glyph { ch }).unwrap();
+        assert_eq!(toon, expected);
+        let decoded: Glyph = serde_toon::from_str(&toon).unwrap();
+        assert_eq!(decoded, Glyph { ch });
+    }
+}
+
+#[test]
+fn char_values_round_trip_in_tabular_rows() {
+    let glyphs = vec![Glyph { ch: 'x' }, Glyph { ch: ',' }, Glyph { ch: '世' }];
+    let toon = serde_toon::to_string(&glyphs).unwrap();
+    assert_eq!(toon, "[3]{ch}:\n  x\n  \",\"\n  世");
+    let decoded: Vec<Glyph> = serde_toon::from_str(&toon).unwrap();
+    assert_eq!(decoded, glyphs);
+}
+
+#[test]
+fn char_rejects_multi_character_strings() {
+    assert!(serde_toon::from_str::<Glyph>("ch: ab").is_err());
+    assert!(serde_toon::from_str::<Glyph>("ch: \"\"").is_err());
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+enum Shape {
+    Circle { r: f64 },
+    Square { side: u32 },
+    Label { text: String, at: Point },
+    Empty,
+}
+
+#[test]
+fn internally_tagged_enums_round_trip_in_arrays() {
+    let shapes = vec![
+        Shape::Circle { r: 1.5 },
+        Shape::Square { side: 2 },
+        Shape::Label {
+            text: "hi, there".to_string(),
+            at: Point { x: 1, y: 2 },
+        },
+        Shape::Empty,
+    ];
+    let toon = serde_toon::to_string(&shapes).unwrap();
+    assert_eq!(
+        toon,
+        "[4]:\n  - type: Circle\n    r: 1.5\n  - type: Square\n    side: 2\n  - type: Label\n    text: \"hi, there\"\n    at:\n      x: 1\n      y: 2\n  - type: Empty"
+    );
+    let decoded: Vec<Shape> = serde_toon::from_str(&toon).unwrap();
+    assert_eq!(decoded, shapes);
+}
+
+#[test]
+fn internally_tagged_enums_round_trip_when_uniform() {
+    let shapes = vec![Shape::Square { side: 1 }, Shape::Square { side: 2 }];
+    let toon = serde_toon::to_string(&shapes).unwrap();
+    assert_eq!(toon, "[2]{type,side}:\n  Square,1\n  Square,2");
+    let decoded: Vec<Shape> = serde_toon::from_str(&toon).unwrap();
+    assert_eq!(decoded, shapes);
+
+    let decoded: Vec<Shape> = serde_toon::from_str("[1]:\n  - side: 3\n    type: Square").unwrap();
+    assert_eq!(decoded, vec![Shape::Square { side: 3 }]);
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+struct ScreamingConfig {
+    max_connections: u32,
+    idle_timeout_ms: u64,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct KebabConfig {
+    max_connections: u32,
+    log_level: String,
+}
+
+#[test]
+fn rename_all_key_conventions_round_trip() {
+    let screaming = ScreamingConfig {
+        max_connections: 10,
+        idle_timeout_ms: 500,
+    };
+    let toon = serde_toon::to_string(&screaming).unwrap();
+    assert_eq!(toon, "MAX_CONNECTIONS: 10\nIDLE_TIMEOUT_MS: 500");
+    assert_eq!(
+        serde_toon::from_str::<ScreamingConfig>(&toon).unwrap(),
+        screaming
+    );
+
+    let kebab = KebabConfig {
+        max_connections: 10,
+        log_level: "debug".to_string(),
+    };
+    let toon = serde_toon::to_string(&kebab).unwrap();
+    assert_eq!(toon, "\"max-connections\": 10\n\"log-level\": debug");
+    assert_eq!(serde_toon::from_str::<KebabConfig>(&toon).unwrap(), kebab);
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct User {
+    id: u32,
+    name: String,
+}
+
+#[test]
+fn root_tabular_array_deserializes_into_vec() {
+    let toon = "[2]{id,name}:\n  1, Ada\n  2, Bob";
+    let users: Vec<User> = serde_toon::from_str(toon).unwrap();
+    assert_eq!(
+        users,
+        vec![
+            User {
+                id: 1,
+                name: "Ada".to_string()
+            },
+            User {
+                id: 2,
+                name: "Bob".to_string()
+            },
+        ]
+    );
+    let encoded = serde_toon::to_string(&users).unwrap();
+    assert_eq!(encoded, "[2]{id,name}:\n  1,Ada\n  2,Bob");
+    assert_eq!(serde_toon::from_str::<Vec<User>>(&encoded).unwrap(), users);
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Service {
+    name: String,
+    port: u16,
+    #[serde(flatten)]
+    extra: BTreeMap<String, serde_json::Value>,
+}
+
+#[test]
+fn flattened_catch_all_map_round_trips() {
+    let service = Service {
+        name: "api".to_string(),
+        port: 8080,
+        extra: BTreeMap::from([
+            ("debug".to_string(), serde_json::json!(true)),
+            (
+                "limits".to_string(),
+                serde_json::json!({"rps": 10, "burst": 2.5}),
+            ),
+            ("tags".to_string(), serde_json::json!(["a", "b"])),
+            ("owner".to_string(), serde_json::json!(null)),
+        ]),
+    };
+    let toon = serde_toon::to_string(&service).unwrap();
+    assert_eq!(
+        toon,
+        "name: api\nport: 8080\ndebug: true\nlimits:\n  rps: 10\n  burst: 2.5\nowner: null\ntags[2]: a,b"
+    );
+    let decoded: Service = serde_toon::from_str(&toon).unwrap();
+    assert_eq!(decoded, service);
+
+    let reordered = "debug: true\nport: 8080\nowner: null\nname: api\ntags[2]: a,b\nlimits:\n  rps: 10\n  burst: 2.5";
+    let decoded: Service = serde_toon::from_str(reordered).unwrap();
+    assert_eq!(decoded, service);
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Endpoint {
+    path: String,
+    #[serde(flatten)]
+    extra: HashMap<String, String>,
+}
+
+#[test]
+fn flattened_maps_round_trip_in_arrays() {
+    let endpoints = vec![
+        Endpoint {
+            path: "/a".to_string(),
+            extra: HashMap::from([("method".to_string(), "GET".to_string())]),
+        },
+        Endpoint {
+            path: "/b".to_string(),
+            extra: HashMap::from([("method".to_string(), "POST".to_string())]),
+        },
+    ];
+    let toon = serde_toon::to_string(&endpoints).unwrap();
+    assert_eq!(toon, "[2]{path,method}:\n  /a,GET\n  /b,POST");
+    let decoded: Vec<Endpoint> = serde_toon::from_str(&toon).unwrap();
+    assert_eq!(decoded, endpoints);
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Limits {
+    name: String,
+    #[serde(flatten)]
+    values: BTreeMap<String, u32>,
+}
+
+#[test]
+fn flattened_numeric_map_captures_unknown_fields() {
+    let decoded: Limits = serde_toon::from_str("name: quota\nrps: 10\nburst: 3").unwrap();
+    assert_eq!(
+        decoded,
+        Limits {
+            name: "quota".to_string(),
+            values: BTreeMap::from([("burst".to_string(), 3), ("rps".to_string(), 10)]),
+        }
+    );
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+struct Config(BTreeMap<String, serde_json::Value>);
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+struct Users(Vec<Point>);
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+struct Port {
+    value: u16,
+}
+
+#[test]
+fn transparent_wrapper_around_map_decodes_at_root() {
+    let config: Config = serde_toon::from_str("name: api\nlimits:\n  rps: 10").unwrap();
+    assert_eq!(
+        config,
+        Config(BTreeMap::from([
+            ("limits".to_string(), serde_json::json!({"rps": 10})),
+            ("name".to_string(), serde_json::json!("api")),
+        ]))
+    );
+    let toon = serde_toon::to_string(&config).unwrap();
+    assert_eq!(toon, "limits:\n  rps: 10\nname: api");
+    assert_eq!(serde_toon::from_str::<Config>(&toon).unwrap(), config);
+}
+
+#[test]
+fn transparent_wrapper_around_vec_decodes_root_array() {
+    let users = Users(vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]);
+    let toon = serde_toon::to_string(&users).unwrap();
+    assert_eq!(toon, "[2]{x,y}:\n  1,2\n  3,4");
+    assert_eq!(serde_toon::from_str::<Users>(&toon).unwrap(), users);
+}
+
+#[test]
+fn transparent_wrapper_around_scalar_decodes_root_primitive() {
+    assert_eq!(
+        serde_toon::from_str::<Port>("8080").unwrap(),
+        Port { value: 8080 }
+    );
+    assert_eq!(
+        serde_toon::to_string(&Port { value: 8080 }).unwrap(),
+        "8080"
+    );
+    let options = serde_toon::DecodeOptions::new().with_inline_objects(true);
+    assert_eq!(
+        serde_toon::from_str_with_options::<Port>("8080", &options).unwrap(),
+        Port { value: 8080 }
+    );
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct SparseRecord {
+    id: u32,
+    email: Option<String>,
+    phone: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+#[test]
+fn sparse_objects_decode_into_option_and_default_fields() {
+    let record = SparseRecord {
+        id: 7,
+        email: Some("a@example.com".to_string()),
+        ..SparseRecord::default()
+    };
+    let options = serde_toon::EncodeOptions::new().with_sparse_objects(true);
+    let toon = serde_toon::to_string_with_options(&record, &options).unwrap();
+    assert_eq!(toon, "id: 7\nemail: a@example.com\ntags[0]:");
+    assert_eq!(serde_toon::from_str::<SparseRecord>(&toon).unwrap(), record);
+    assert_eq!(
+        serde_toon::from_str::<SparseRecord>("id: 7").unwrap(),
+        SparseRecord {
+            id: 7,
+            ..SparseRecord::default()
+        }
+    );
+}
+
+#[test]
+fn value_conversions_mirror_serde_json() {
+    let point = Point { x: 1, y: -2 };
+    let value = serde_toon::to_value(&point).unwrap();
+    assert_eq!(value, serde_json::json!({"x": 1, "y": -2}));
+    assert_eq!(serde_toon::from_value::<Point>(value).unwrap(), point);
+
+    let err = serde_toon::from_value::<Point>(serde_json::json!({"x": "one"})).unwrap_err();
+    assert_eq!(err.kind, serde_toon::ErrorKind::Deserialize);
+    assert!(std::error::Error::source(&err).is_some());
+
+    let mut bad_keys = HashMap::new();
+    bad_keys.insert(vec![1], 1);
+    let err = serde_toon::to_value(&bad_keys).unwrap_err();
+    assert_eq!(err.kind, serde_toon::ErrorKind::Serialize);
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct BorrowedUser<'a> {
+    id: u32,
+    name: &'a str,
+    role: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct BorrowedDoc<'a> {
+    #[serde(borrow)]
+    users: Vec<BorrowedUser<'a>>,
+    #[serde(borrow)]
+    note: std::borrow::Cow<'a, str>,
+}
+
+#[test]
+fn from_str_borrowed_slices_strings_from_input() {
+    let input =
+        "users[2]{id,name,role}:\n  1,Ada,admin\n  2,\"Bob Smith\",user\nnote: \"line\\nbreak\"";
+    let doc: BorrowedDoc = serde_toon::from_str_borrowed(input).unwrap();
+    assert_eq!(
+        doc.users,
+        vec![
+            BorrowedUser {
+                id: 1,
+                name: "Ada",
+                role: "admin"
+            },
+            BorrowedUser {
+                id: 2,
+                name: "Bob Smith",
+                role: "user"
+            },
+        ]
+    );
+    let range = input.as_bytes().as_ptr_range();
+    for user in &doc.users {
+        assert!(range.contains(&user.name.as_ptr()));
+    }
+    assert!(matches!(doc.note, std::borrow::Cow::Owned(ref note) if note == "line\nbreak"));
+
+    let err = serde_toon::from_str_borrowed::<BorrowedUser>("id: 1\nname: \"a\\tb\"\nrole: x")
+        .unwrap_err();
+    assert_eq!(err.kind, serde_toon::ErrorKind::Deserialize);
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Leaf {
+    port: u16,
+    host: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Middle {
+    server: Leaf,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Outer {
+    deploy: Middle,
+    #[serde(flatten)]
+    extra: BTreeMap<String, serde_json::Value>,
+}
+
+#[test]
+fn key_folding_and_path_expansion_round_trip_flattened_structs() {
+    let value = Outer {
+        deploy: Middle {
+            server: Leaf {
+                port: 8080,
+                host: "localhost".to_string(),
+            },
+        },
+        extra: BTreeMap::from([
+            (
+                "region".to_string(),
+                serde_json::json!({"eu": {"zone": "a"}}),
+            ),
+            (
+                "tags".to_string(),
+                serde_json::json!([{"a": {"b": 1}}, {"a": {"b": 2}}]),
+            ),
+        ]),
+    };
+    let expand = serde_toon::DecodeOptions::new().with_expand_paths(serde_toon::ExpandPaths::Safe);
+    for depth in [None, Some(2)] {
+        let options = serde_toon::EncodeOptions::new()
+            .with_key_folding(serde_toon::KeyFolding::Safe)
+            .with_flatten_depth(depth);
+        let toon = serde_toon::to_string_with_options(&value, &options).unwrap();
+        assert!(toon.contains("deploy.server:\n"), "{toon}");
+        assert_eq!(
+            serde_toon::from_str_with_options::<Outer>(&toon, &expand).unwrap(),
+            value
+        );
+        assert_eq!(
+            serde_toon::decode_to_value_with_options(&toon, &expand).unwrap(),
+            serde_toon::to_value(&value).unwrap()
+        );
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Blob {
+    name: String,
+    #[serde(with = "serde_bytes")]
+    data: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    empty: Vec<u8>,
+}
+
+#[test]
+fn bytes_round_trip_as_base64() {
+    let value = Blob {
+        name: "logo".to_string(),
+        data: vec![0, 1, 2, 250, 251, 252, 253, 254],
+        empty: Vec::new(),
+    };
+    let encode = serde_toon::EncodeOptions::new().with_bytes_as_base64(true);
+    let toon = serde_toon::to_string_with_options(&value, &encode).unwrap();
+    assert_eq!(toon, "name: logo\ndata: AAEC+vv8/f4=\nempty: \"\"");
+    let decode = serde_toon::DecodeOptions::new().with_bytes_from_base64(true);
+    assert_eq!(
+        serde_toon::from_str_with_options::<Blob>(&toon, &decode).unwrap(),
+        value
+    );
+    let plain = serde_toon::to_string(&value).unwrap();
+    assert!(plain.contains("data[8]: 0,1,2,250"), "{plain}");
+}
+
+#[test]
+fn bytes_from_base64_rejects_invalid_input() {
+    let decode = serde_toon::DecodeOptions::new().with_bytes_from_base64(true);
+    for data in ["AAE", "A=AA", "AA!A"] {
+        let input = format!("name: x\ndata: \"{data}\"\nempty: \"\"");
+        let err = serde_toon::from_str_with_options::<Blob>(&input, &decode).unwrap_err();
+        assert!(err.to_string().contains("invalid base64 string"), "{err}");
+    }
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Ticket {
+    id: u32,
+    #[serde(deserialize_with = "serde_toon::shared_str")]
+    status: Arc<str>,
+}
+
+#[test]
+fn string_dedup_shares_repeated_values() {
+    let toon = "[3]{id,status}:\n  1,open\n  2,open\n  3,closed";
+    let plain: Vec<Ticket> = serde_toon::from_str(toon).unwrap();
+    assert!(!Arc::ptr_eq(&plain[0].status, &plain[1].status));
+    let decode = serde_toon::DecodeOptions::new().with_string_dedup(true);
+    let shared: Vec<Ticket> = serde_toon::from_str_with_options(toon, &decode).unwrap();
+    assert_eq!(shared, plain);
+    assert!(Arc::ptr_eq(&shared[0].status, &shared[1].status));
+    assert!(!Arc::ptr_eq(&shared[0].status, &shared[2].status));
+    let tree = decode.clone().with_inline_objects(true);
+    let shared: Vec<Ticket> = serde_toon::from_str_with_options(toon, &tree).unwrap();
+    assert!(Arc::ptr_eq(&shared[0].status, &shared[1].status));
+}
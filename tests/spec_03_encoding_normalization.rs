@@ -1,8 +1,10 @@
+use std::sync::Arc;
+
 use rstest::rstest;
 use serde_json::{json, Number, Value};
 use serde_toon::{
     DecodeOptions, Delimiter, EncodeOptions, ExpandPaths as ToonExpandPaths, Indent,
-    KeyFolding as ToonKeyFolding,
+    KeyFolding as ToonKeyFolding, NonFiniteEncoding,
 };
 
 #[allow(dead_code)]
@@ -214,3 +216,237 @@ fn spec03_encoding_normalization_validate(#[case] input: &str, #[case] valid: bo
         assert!(result.is_err());
     }
 }
+
+#[test]
+fn spec03_non_finite_floats_in_nested_values_encode_as_null() {
+    #[derive(serde::Serialize)]
+    struct Reading {
+        id: u32,
+        value: f64,
+    }
+
+    let input = json!({
+        "readings": [
+            Reading { id: 1, value: f64::NAN },
+            Reading { id: 2, value: 0.5 },
+        ],
+        "bounds": [f64::NEG_INFINITY, f64::INFINITY],
+    });
+    assert_eq!(
+        serde_toon::to_string(&input).unwrap(),
+        "readings[2]{id,value}:\n  1,null\n  2,0.5\nbounds[2]: null,null"
+    );
+    assert_eq!(
+        serde_toon::to_string(&vec![f64::NAN, 1.0]).unwrap(),
+        "[2]: null,1"
+    );
+}
+
+fn zeros(count: usize) -> String {
+    "0".repeat(count)
+}
+
+#[rstest]
+#[case(0.1, "0.1".to_string())]
+#[case(0.1 + 0.2, "0.30000000000000004".to_string())]
+#[case(1.0 / 3.0, "0.3333333333333333".to_string())]
+#[case(123456789.125, "123456789.125".to_string())]
+#[case(1e15 + 0.3, "1000000000000000.2".to_string())]
+#[case(2.5e-5, "0.000025".to_string())]
+#[case(-1.5e-10, "-0.00000000015".to_string())]
+#[case(f64::EPSILON, "0.0000000000000002220446049250313".to_string())]
+#[case(9007199254740993.0, "9007199254740992".to_string())]
+#[case(1e20, format!("1{}", zeros(20)))]
+#[case(1e-300, format!("0.{}1", zeros(299)))]
+#[case(5e-324, format!("0.{}5", zeros(323)))]
+#[case(f64::MIN_POSITIVE, format!("0.{}22250738585072014", zeros(307)))]
+#[case(f64::MAX, format!("17976931348623157{}", zeros(292)))]
+#[case(-0.0, "0".to_string())]
+fn spec03_float_output_is_pinned(#[case] input: f64, #[case] expected: String) {
+    let encoded = serde_toon::to_string(&input).unwrap();
+    assert_eq!(encoded, expected);
+    assert_eq!(
+        serde_toon::to_string(&json!({ "v": input })).unwrap(),
+        format!("v: {expected}")
+    );
+    if input.abs() < u64::MAX as f64 {
+        let decoded: f64 = serde_toon::from_str(&encoded).unwrap();
+        assert_eq!(decoded, input);
+    }
+}
+
+#[rstest]
+#[case(json!({"x": 2.0}), "x: 2")]
+#[case(json!({"x": 2}), "x: 2")]
+#[case(json!({"x": -3.0}), "x: -3")]
+#[case(json!({"x": 2.5}), "x: 2.5")]
+#[case(json!({"xs": [1.0, 2, 3.5]}), "xs[3]: 1,2,3.5")]
+fn spec03_integral_floats_encode_canonically(#[case] input: Value, #[case] expected: &str) {
+    let encoded = serde_toon::to_string(&input).unwrap();
+    assert_eq!(encoded, expected);
+    let canonical = serde_toon::decode_to_value(&encoded).unwrap();
+    assert_eq!(serde_toon::to_string(&canonical).unwrap(), expected);
+}
+
+#[test]
+fn spec03_decode_to_value_canonicalizes_but_from_str_keeps_tokens() {
+    let input = "x: 2.0\ny: -0.0\nz: 2";
+    let canonical = serde_toon::decode_to_value(input).unwrap();
+    assert_eq!(canonical, json!({"x": 2, "y": 0, "z": 2}));
+    assert!(canonical["x"].is_i64());
+
+    let as_written: Value = serde_toon::from_str(input).unwrap();
+    assert!(as_written["x"].is_f64());
+    assert!(as_written["z"].is_i64());
+
+    #[derive(serde::Deserialize)]
+    struct Floats {
+        x: f64,
+        z: f64,
+    }
+    let typed: Floats = serde_toon::from_str(input).unwrap();
+    assert_eq!((typed.x, typed.z), (2.0, 2.0));
+}
+
+fn cents(number: &Number) -> Option<String> {
+    let cents = number.as_i64()?;
+    let sign = if cents < 0 { "-" } else { "" };
+    let cents = cents.unsigned_abs();
+    Some(format!("{sign}{}.{:02}", cents / 100, cents % 100))
+}
+
+fn thousands(number: &Number) -> Option<String> {
+    let digits = number.as_u64()?.to_string();
+    let mut out = String::new();
+    for (idx, digit) in digits.chars().enumerate() {
+        if idx > 0 && (digits.len() - idx) % 3 == 0 {
+            out.push('_');
+        }
+        out.push(digit);
+    }
+    Some(out)
+}
+
+#[rstest]
+#[case(
+    false,
+    "total: 1234567\nrows[2]{id,amount}:\n  1,999\n  2,-5\nratio: 0.5"
+)]
+#[case(
+    true,
+    "total: 1_234_567\nrows[2]{id,amount}:\n  1,999\n  2,-5\nratio: 0.5"
+)]
+fn spec03_number_formatter_thousands_separators(#[case] enabled: bool, #[case] expected: &str) {
+    let input = json!({
+        "total": 1234567,
+        "rows": [{"id": 1, "amount": 999}, {"id": 2, "amount": -5}],
+        "ratio": 0.5
+    });
+    let mut options = EncodeOptions::new();
+    if enabled {
+        options = options.with_number_formatter(Arc::new(thousands));
+    }
+    for _ in 0..2 {
+        let toon = serde_toon::to_string_with_options(&input, &options).unwrap();
+        assert_eq!(toon, expected);
+        let decode = DecodeOptions::new().with_numeric_separators(enabled);
+        let decoded: Value = serde_toon::from_str_with_options(&toon, &decode).unwrap();
+        assert_eq!(decoded, input);
+    }
+}
+
+#[test]
+fn spec03_number_formatter_renders_cents_without_floats() {
+    let input =
+        json!({"price": 1234, "items": [5, 120], "rows": [{"amount": -7}, {"amount": 100}]});
+    let options = EncodeOptions::new().with_number_formatter(Arc::new(cents));
+    let toon = serde_toon::to_string_with_options(&input, &options).unwrap();
+    assert_eq!(
+        toon,
+        "price: 12.34\nitems[2]: 0.05,1.20\nrows[2]{amount}:\n  -0.07\n  1.00"
+    );
+    let decoded: Value = serde_toon::from_str(&toon).unwrap();
+    assert_eq!(decoded["price"], json!(12.34));
+
+    let plain = serde_toon::to_string(&input).unwrap();
+    assert!(plain.starts_with("price: 1234\n"), "{plain}");
+}
+
+#[rstest]
+#[case(Delimiter::Comma, "rows[2]{id,n}:\n  1,\"1,500\"\n  2,\"1,500\"")]
+#[case(Delimiter::Pipe, "rows[2|]{id|n}:\n  1|1,500\n  2|1,500")]
+fn spec03_number_formatter_output_holding_delimiter_is_quoted(
+    #[case] delimiter: Delimiter,
+    #[case] expected: &str,
+) {
+    let separator = ',';
+    let input = json!({"rows": [{"id": 1, "n": 1500}, {"id": 2, "n": 1500}]});
+    let options = EncodeOptions::new()
+        .with_delimiter(delimiter)
+        .with_number_formatter(Arc::new(move |number: &Number| {
+            let value = number.as_u64().filter(|value| *value >= 1000)?;
+            Some(format!("{}{separator}{:03}", value / 1000, value % 1000))
+        }));
+    let toon = serde_toon::to_string_with_options(&input, &options.clone()).unwrap();
+    assert_eq!(toon, expected);
+    let decoded: Value = serde_toon::from_str(&toon).unwrap();
+    assert_eq!(decoded["rows"][0]["n"], json!("1,500"));
+}
+
+#[rstest]
+#[case(NonFiniteEncoding::Null, f64::NAN, Ok("x: null"))]
+#[case(NonFiniteEncoding::Quoted, f64::NAN, Ok("x: \"NaN\""))]
+#[case(NonFiniteEncoding::Quoted, f64::INFINITY, Ok("x: \"Infinity\""))]
+#[case(NonFiniteEncoding::Quoted, f64::NEG_INFINITY, Ok("x: \"-Infinity\""))]
+#[case(NonFiniteEncoding::Error, f64::NAN, Err("NaN"))]
+#[case(NonFiniteEncoding::Error, f64::NEG_INFINITY, Err("-Infinity"))]
+#[case(NonFiniteEncoding::Error, 1.5, Ok("x: 1.5"))]
+fn spec03_non_finite_encoding_policy(
+    #[case] policy: NonFiniteEncoding,
+    #[case] x: f64,
+    #[case] expected: Result<&str, &str>,
+) {
+    let mut input = std::collections::BTreeMap::new();
+    input.insert("x", x);
+    let options = EncodeOptions::new().with_non_finite(policy);
+    match (
+        serde_toon::to_string_with_options(&input, &options),
+        expected,
+    ) {
+        (Ok(toon), Ok(expected)) => {
+            assert_eq!(toon, expected);
+            let mut out = Vec::new();
+            serde_toon::to_writer_with_options(&mut out, &input, &options).unwrap();
+            assert_eq!(out, expected.as_bytes());
+        }
+        (Err(err), Err(literal)) => {
+            assert!(err.to_string().contains(literal), "{err}");
+        }
+        (actual, expected) => panic!("expected {expected:?}, got {actual:?}"),
+    }
+}
+
+#[test]
+fn spec03_non_finite_quoted_reaches_nested_floats() {
+    #[derive(serde::Serialize)]
+    struct Reading {
+        id: u32,
+        value: Option<f32>,
+    }
+
+    let input = vec![
+        Reading {
+            id: 1,
+            value: Some(f32::NAN),
+        },
+        Reading {
+            id: 2,
+            value: Some(2.5),
+        },
+    ];
+    let options = EncodeOptions::new().with_non_finite(NonFiniteEncoding::Quoted);
+    let toon = serde_toon::to_string_with_options(&input, &options).unwrap();
+    assert_eq!(toon, "[2]{id,value}:\n  1,\"NaN\"\n  2,2.5");
+    let decoded: Value = serde_toon::from_str(&toon).unwrap();
+    assert_eq!(decoded[0]["value"], json!("NaN"));
+}
@@ -2,7 +2,7 @@ use rstest::rstest;
 use serde_json::{json, Value};
 use serde_toon::{
     DecodeOptions, Delimiter, EncodeOptions, ExpandPaths as ToonExpandPaths, Indent,
-    KeyFolding as ToonKeyFolding,
+    KeyFolding as ToonKeyFolding, NegZeroPolicy, NonFinitePolicy,
 };
 
 #[allow(dead_code)]
@@ -187,3 +187,193 @@ fn spec04_decoding_interpretation_validate(#[case] input: &str, #[case] valid: b
         assert!(result.is_err());
     }
 }
+
+#[rstest]
+#[case("n: 01")]
+#[case("n: -007")]
+#[case("n: 1.0")]
+#[case("n: 1.50")]
+#[case("n: 1E3")]
+#[case("n: 1e3")]
+#[case("n: -0")]
+#[case("items[2]: 1,2.0")]
+#[case("rows[1]{a,b}:\n  1,00")]
+fn spec04_strict_number_canonicalization_rejects(#[case] input: &str) {
+    let options = DecodeOptions::new()
+        .with_strict(false)
+        .with_strict_number_canonicalization(true);
+    assert!(serde_toon::from_str_with_options::<Value>(input, &options).is_err());
+    assert!(serde_toon::decode_to_value_with_options(input, &options).is_err());
+
+    let relaxed = DecodeOptions::new().with_strict(false);
+    assert!(serde_toon::from_str_with_options::<Value>(input, &relaxed).is_ok());
+}
+
+#[rstest]
+#[case("n: 0", json!({"n": 0}))]
+#[case("n: -12", json!({"n": -12}))]
+#[case("n: 1.5", json!({"n": 1.5}))]
+#[case("n: 1000", json!({"n": 1000}))]
+#[case("n: \"01\"", json!({"n": "01"}))]
+#[case("d: 2024-01-01", json!({"d": "2024-01-01"}))]
+fn spec04_strict_number_canonicalization_accepts(#[case] input: &str, #[case] expected: Value) {
+    let options = DecodeOptions::new()
+        .with_strict(false)
+        .with_strict_number_canonicalization(true);
+    let decoded: Value = serde_toon::from_str_with_options(input, &options).unwrap();
+    assert_eq!(decoded, expected);
+    assert_eq!(
+        serde_toon::decode_to_value_with_options(input, &options).unwrap(),
+        expected
+    );
+}
+
+#[rstest]
+#[case("n: 1_000_000", json!({"n": 1_000_000}))]
+#[case("n: -1_024", json!({"n": -1024}))]
+#[case("n: 2.500_25", json!({"n": 2.50025}))]
+#[case("rows[2]{a,b}:\n  1_000,x\n  2_000,y", json!({"rows": [{"a": 1000, "b": "x"}, {"a": 2000, "b": "y"}]}))]
+#[case("n[2]: 1_0,2_0", json!({"n": [10, 20]}))]
+#[case("n: _100", json!({"n": "_100"}))]
+#[case("n: 100_", json!({"n": "100_"}))]
+#[case("n: 1__0", json!({"n": "1__0"}))]
+#[case("n: 1_.5", json!({"n": "1_.5"}))]
+#[case("n: \"1_000\"", json!({"n": "1_000"}))]
+fn spec04_numeric_separators(#[case] input: &str, #[case] expected: Value) {
+    let options = DecodeOptions::new().with_numeric_separators(true);
+    let decoded: Value = serde_toon::from_str_with_options(input, &options).unwrap();
+    assert_eq!(decoded, expected);
+    assert_eq!(
+        serde_toon::decode_to_value_with_options(input, &options).unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn spec04_numeric_separators_are_off_by_default() {
+    let decoded: Value = serde_toon::from_str("n: 1_000").unwrap();
+    assert_eq!(decoded, json!({"n": "1_000"}));
+    let encoded = serde_toon::to_string(&json!({"n": 1_000_000})).unwrap();
+    assert_eq!(encoded, "n: 1000000");
+}
+
+#[rstest]
+#[case("id[3]: 42,07,8", json!({"id": ["42", "07", "8"]}))]
+#[case("n: 3.14\nm: -0", json!({"n": "3.14", "m": "-0"}))]
+#[case("rows[2]{id,ok}:\n  1,true\n  02,null", json!({"rows": [{"id": "1", "ok": true}, {"id": "02", "ok": null}]}))]
+#[case("flags[3]: true,false,null", json!({"flags": [true, false, null]}))]
+#[case("n: \"5\"", json!({"n": "5"}))]
+fn spec04_coerce_numbers_disabled(#[case] input: &str, #[case] expected: Value) {
+    let options = DecodeOptions::new()
+        .with_coerce_numbers(false)
+        .with_strict_number_canonicalization(true);
+    let tree_options = options.clone().with_numeric_separators(true);
+    for options in [&options, &tree_options] {
+        let decoded: Value = serde_toon::from_str_with_options(input, options).unwrap();
+        assert_eq!(decoded, expected);
+        assert!(serde_toon::validate_str_with_options(input, options).is_ok());
+    }
+    assert_eq!(
+        serde_toon::decode_to_value_with_options(input, &options).unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn spec04_coerce_numbers_is_on_by_default() {
+    let decoded: Value = serde_toon::from_str("id[3]: 42,07,8").unwrap();
+    assert_eq!(decoded, json!({"id": [42, "07", 8]}));
+}
+
+#[rstest]
+#[case(NonFinitePolicy::Null, "NaN", json!({"n": null, "v": [null, 1]}))]
+#[case(NonFinitePolicy::Null, "Infinity", json!({"n": null, "v": [null, 1]}))]
+#[case(NonFinitePolicy::Null, "-Infinity", json!({"n": null, "v": [null, 1]}))]
+#[case(NonFinitePolicy::Keep, "NaN", json!({"n": "NaN", "v": ["NaN", 1]}))]
+#[case(NonFinitePolicy::Keep, "Infinity", json!({"n": "Infinity", "v": ["Infinity", 1]}))]
+#[case(NonFinitePolicy::Keep, "-Infinity", json!({"n": "-Infinity", "v": ["-Infinity", 1]}))]
+fn spec04_non_finite_policy(
+    #[case] policy: NonFinitePolicy,
+    #[case] token: &str,
+    #[case] expected: Value,
+) {
+    let input = format!("n: {token}\nv[2]: {token},1");
+    let options = DecodeOptions::new().with_non_finite(policy);
+    let decoded: Value = serde_toon::from_str_with_options(&input, &options).unwrap();
+    assert_eq!(decoded, expected);
+    assert_eq!(
+        serde_toon::decode_to_value_with_options(&input, &options).unwrap(),
+        expected
+    );
+    assert!(serde_toon::validate_str_with_options(&input, &options).is_ok());
+}
+
+#[rstest]
+#[case("n: NaN")]
+#[case("n: Infinity")]
+#[case("n: -Infinity")]
+#[case("rows[1]{a}:\n  +Infinity")]
+fn spec04_non_finite_errors_by_default(#[case] input: &str) {
+    let err = serde_toon::from_str::<Value>(input).unwrap_err();
+    assert!(err.to_string().contains("non-finite numbers must be null"));
+    assert!(serde_toon::decode_to_value(input).is_err());
+    assert!(serde_toon::validate_str(input).is_err());
+}
+
+#[test]
+fn spec04_non_finite_strings_are_quoted_and_round_trip() {
+    let input = json!({"a": "NaN", "b": "Infinity", "c": "+Infinity", "d": "-Infinity"});
+    let toon = serde_toon::to_string(&input).unwrap();
+    assert_eq!(
+        toon,
+        "a: \"NaN\"\nb: \"Infinity\"\nc: \"+Infinity\"\nd: \"-Infinity\""
+    );
+    assert_eq!(serde_toon::decode_to_value(&toon).unwrap(), input);
+}
+
+#[rstest]
+#[case(NegZeroPolicy::Zero, "-0", "0")]
+#[case(NegZeroPolicy::Zero, "-0.0", "0.0")]
+#[case(NegZeroPolicy::Zero, "0", "0")]
+#[case(NegZeroPolicy::Zero, "0.0", "0.0")]
+#[case(NegZeroPolicy::PreserveFloat, "-0", "-0.0")]
+#[case(NegZeroPolicy::PreserveFloat, "-0.0", "-0.0")]
+#[case(NegZeroPolicy::PreserveFloat, "0", "0")]
+#[case(NegZeroPolicy::PreserveFloat, "0.0", "0.0")]
+#[case(NegZeroPolicy::Error, "0", "0")]
+#[case(NegZeroPolicy::Error, "0.0", "0.0")]
+fn spec04_negative_zero_policy(
+    #[case] policy: NegZeroPolicy,
+    #[case] token: &str,
+    #[case] expected: &str,
+) {
+    let input = format!("n: {token}\nv[2]: {token},1");
+    let options = DecodeOptions::new().with_negative_zero(policy);
+    let tree_options = options.clone().with_numeric_separators(true);
+    for options in [&options, &tree_options] {
+        let decoded: Value = serde_toon::from_str_with_options(&input, options).unwrap();
+        for number in [&decoded["n"], &decoded["v"][0]] {
+            assert_eq!(number.to_string(), expected, "{token}");
+        }
+        // `decode_to_value` canonicalizes numbers, so every zero is the integer 0.
+        let canonical = serde_toon::decode_to_value_with_options(&input, options).unwrap();
+        assert_eq!(canonical, json!({"n": 0, "v": [0, 1]}));
+        assert!(canonical["n"].is_u64(), "{token}");
+        assert_eq!(serde_toon::to_string(&decoded).unwrap(), "n: 0\nv[2]: 0,1");
+    }
+}
+
+#[rstest]
+#[case("n: -0")]
+#[case("n: -0.0")]
+#[case("v[2]: 1,-0")]
+#[case("rows[1]{a}:\n  -0")]
+fn spec04_negative_zero_error_policy(#[case] input: &str) {
+    let options = DecodeOptions::new().with_negative_zero(NegZeroPolicy::Error);
+    let err = serde_toon::from_str_with_options::<Value>(input, &options).unwrap_err();
+    assert!(err.to_string().contains("negative zero is not allowed"));
+    let tree_options = options.clone().with_numeric_separators(true);
+    assert!(serde_toon::from_str_with_options::<Value>(input, &tree_options).is_err());
+    assert!(serde_toon::decode_to_value_with_options(input, &options).is_err());
+    assert!(serde_toon::validate_str_with_options(input, &options).is_err());
+}
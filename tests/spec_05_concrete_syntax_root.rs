@@ -161,6 +161,20 @@ fn spec05_concrete_syntax_encode(
 #[case("a: 1\nb: 2", Some(json!({"a": 1, "b": 2})), SpecOptions::default())]
 #[case("\n\n", Some(json!({})), SpecOptions::default())]
 #[case("hello\nworld", None, SpecOptions::default().with_strict(true))]
+#[case(
+    "[2]{id,name}:\n  1, Ada\n  2, Bob",
+    Some(json!([{"id": 1, "name": "Ada"}, {"id": 2, "name": "Bob"}])),
+    SpecOptions::default()
+)]
+#[case(
+    "[2|]{id|name|active}:\n  1|Ada Lovelace|true\n  2|\"Bob|B\"|null",
+    Some(json!([
+        {"id": 1, "name": "Ada Lovelace", "active": true},
+        {"id": 2, "name": "Bob|B", "active": null}
+    ])),
+    SpecOptions::default()
+)]
+#[case("[3]{id,name}:\n  1,Ada\n  2,Bob", None, SpecOptions::default())]
 fn spec05_concrete_syntax_decode(
     #[case] input: &str,
     #[case] expected: Option<Value>,
@@ -189,3 +203,98 @@ fn spec05_concrete_syntax_validate(#[case] input: &str, #[case] valid: bool) {
         assert!(result.is_err());
     }
 }
+
+#[rstest]
+#[case(json!({"a": 1, "b": {"c": "x\ny"}}), vec!["a: 1", "b:", "  c: \"x\\ny\""])]
+#[case(json!([{"id": 1}, {"id": 2}]), vec!["[2]{id}:", "  1", "  2"])]
+#[case(json!("text"), vec!["text"])]
+#[case(json!({}), vec![])]
+fn spec05_to_lines_matches_to_string(#[case] input: Value, #[case] expected: Vec<&str>) {
+    let options = EncodeOptions::default();
+    let lines: Vec<String> = serde_toon::to_lines(&input, &options).unwrap().collect();
+    assert_eq!(lines, expected);
+    assert_eq!(
+        lines.join("\n"),
+        serde_toon::to_string_with_options(&input, &options).unwrap()
+    );
+}
+
+#[rstest]
+#[case(json!({"a": 1}), vec!["a: 1"])]
+#[case(json!({}), vec![])]
+fn spec05_to_lines_ignores_trailing_newline(#[case] input: Value, #[case] expected: Vec<&str>) {
+    let options = EncodeOptions::default().with_trailing_newline(true);
+    let lines: Vec<String> = serde_toon::to_lines(&input, &options).unwrap().collect();
+    assert_eq!(lines, expected);
+    assert_eq!(
+        lines.join("\n") + "\n",
+        serde_toon::to_string_with_options(&input, &options).unwrap()
+    );
+}
+
+#[rstest]
+#[case("{\"a\":1}")]
+#[case("  {\"a\": 1}")]
+#[case("[\"x\"]")]
+fn spec05_json_input_is_rejected_early(#[case] input: &str) {
+    let err = serde_toon::decode_to_value(input).unwrap_err();
+    assert!(err.to_string().contains("input looks like JSON, not TOON"));
+    assert!(serde_toon::from_str::<Value>(input).is_err());
+    assert!(serde_toon::validate_str(input).is_err());
+    assert!(serde_toon::validate_reader(input.as_bytes()).is_err());
+}
+
+#[rstest]
+#[case("{{a}")]
+#[case("{}")]
+fn spec05_root_strings_opening_with_brace_still_decode(#[case] input: &str) {
+    assert_eq!(serde_toon::decode_to_value(input).unwrap(), json!(input));
+    assert!(serde_toon::validate_str(input).is_ok());
+    assert!(serde_toon::validate_reader(input.as_bytes()).is_ok());
+}
+
+#[rstest]
+#[case("\n{\"a\":1}")]
+#[case("{ \"a\": 1 }")]
+fn spec05_json_check_only_reads_the_first_line_prefix(#[case] input: &str) {
+    let err = serde_toon::decode_to_value(input).unwrap_err();
+    assert!(!err.to_string().contains("looks like JSON"), "{err}");
+}
+
+#[rstest]
+fn spec05_root_scalar_decodes_to_typed_primitives(#[values(true, false)] strict: bool) {
+    let options = DecodeOptions::new().with_strict(strict);
+    assert_eq!(
+        serde_toon::from_str_with_options::<i64>("42", &options).unwrap(),
+        42
+    );
+    assert_eq!(
+        serde_toon::from_str_with_options::<i64>("-7\n", &options).unwrap(),
+        -7
+    );
+    assert_eq!(
+        serde_toon::from_str_with_options::<u8>("255", &options).unwrap(),
+        255
+    );
+    assert_eq!(
+        serde_toon::from_str_with_options::<f64>("2.5", &options).unwrap(),
+        2.5
+    );
+    assert!(serde_toon::from_str_with_options::<bool>("true", &options).unwrap());
+    assert_eq!(
+        serde_toon::from_str_with_options::<String>("hello", &options).unwrap(),
+        "hello"
+    );
+    assert_eq!(
+        serde_toon::from_str_with_options::<String>("\"42\"", &options).unwrap(),
+        "42"
+    );
+    assert_eq!(
+        serde_toon::from_str_with_options::<char>("x", &options).unwrap(),
+        'x'
+    );
+    assert_eq!(
+        serde_toon::from_str_with_options::<Option<i32>>("null", &options).unwrap(),
+        None
+    );
+}
@@ -1,7 +1,7 @@
 use rstest::rstest;
 use serde_json::{json, Value};
 use serde_toon::{
-    DecodeOptions, Delimiter, EncodeOptions, ExpandPaths as ToonExpandPaths, Indent,
+    DecodeOptions, Delimiter, EncodeOptions, EscapePolicy, ExpandPaths as ToonExpandPaths, Indent,
     KeyFolding as ToonKeyFolding,
 };
 
@@ -141,6 +141,14 @@ fn map_decode_options(options: &SpecOptions) -> DecodeOptions {
 #[case(json!({"v": "-lead"}), Some("v: \"-lead\""), SpecOptions::default())]
 #[case(json!({"a.b": 1}), Some("a.b: 1"), SpecOptions::default())]
 #[case(json!({"a-b": 1}), Some("\"a-b\": 1"), SpecOptions::default())]
+#[case(json!({"MAX_CONNECTIONS": 10}), Some("MAX_CONNECTIONS: 10"), SpecOptions::default())]
+#[case(json!({"_PRIVATE_2": true}), Some("_PRIVATE_2: true"), SpecOptions::default())]
+#[case(json!({"max-connections": 10}), Some("\"max-connections\": 10"), SpecOptions::default())]
+#[case(
+    json!({"DB": {"HOST_NAME": "x"}}),
+    Some("DB.HOST_NAME: x"),
+    SpecOptions::default().with_key_folding_safe()
+)]
 fn spec07_strings_keys_encode(
     #[case] input: Value,
     #[case] expected: Option<&'static str>,
@@ -169,6 +177,14 @@ fn spec07_strings_keys_encode(
 #[case("a.b: 1", Some(json!({"a.b": 1})), SpecOptions::default())]
 #[case("a-b: 1", None, SpecOptions::default().with_strict(true))]
 #[case("key value", None, SpecOptions::default())]
+#[case("MAX_CONNECTIONS: 10", Some(json!({"MAX_CONNECTIONS": 10})), SpecOptions::default())]
+#[case("\"max-connections\": 10", Some(json!({"max-connections": 10})), SpecOptions::default())]
+#[case(
+    "DB.HOST_NAME: x",
+    Some(json!({"DB": {"HOST_NAME": "x"}})),
+    SpecOptions::default().with_expand_paths_safe()
+)]
+#[case("DB.HOST_NAME: x", Some(json!({"DB.HOST_NAME": "x"})), SpecOptions::default())]
 fn spec07_strings_keys_decode(
     #[case] input: &str,
     #[case] expected: Option<Value>,
@@ -198,3 +214,172 @@ fn spec07_strings_keys_validate(#[case] input: &str, #[case] valid: bool) {
         assert!(result.is_err());
     }
 }
+
+#[rstest]
+#[case(EscapePolicy::Error, false, None)]
+#[case(EscapePolicy::Keep, false, Some(json!({"v": "a\\qb", "w": ["\\q", "x"]})))]
+#[case(EscapePolicy::Strip, false, Some(json!({"v": "aqb", "w": ["q", "x"]})))]
+#[case(EscapePolicy::Keep, true, None)]
+#[case(EscapePolicy::Strip, true, None)]
+fn spec07_unknown_escape_policy(
+    #[case] policy: EscapePolicy,
+    #[case] strict: bool,
+    #[case] expected: Option<Value>,
+) {
+    let input = "v: \"a\\qb\"\nw[2]: \"\\q\",x";
+    let options = DecodeOptions::new()
+        .with_strict(strict)
+        .with_on_unknown_escape(policy);
+    let arena = serde_toon::from_str_with_options::<Value>(input, &options);
+    let tree = serde_toon::decode_to_value_with_options(input, &options);
+    match expected {
+        Some(expected) => {
+            assert_eq!(arena.unwrap(), expected);
+            assert_eq!(tree.unwrap(), expected);
+        }
+        None => {
+            assert!(arena.is_err());
+            assert!(tree.is_err());
+        }
+    }
+}
+
+#[rstest]
+#[case("msg: hello world", json!({"msg": "hello world"}))]
+#[case("msg: hello   world  ", json!({"msg": "hello   world"}))]
+#[case("items[2]: hello world,a b", json!({"items": ["hello world", "a b"]}))]
+#[case("[1]:\n  - hello world", json!(["hello world"]))]
+#[case("rows[1]{a,b}:\n  x y,z  w", json!({"rows": [{"a": "x y", "b": "z  w"}]}))]
+fn spec07_multi_word_unquoted_values_are_not_split(
+    #[case] input: &str,
+    #[case] expected: Value,
+    #[values(true, false)] strict: bool,
+) {
+    let options = DecodeOptions::new().with_strict(strict);
+    assert_eq!(
+        serde_toon::from_str_with_options::<Value>(input, &options).unwrap(),
+        expected
+    );
+    // Inline objects route through the tree decoder.
+    let tree = options.with_inline_objects(true);
+    assert_eq!(
+        serde_toon::from_str_with_options::<Value>(input, &tree).unwrap(),
+        expected
+    );
+}
+
+#[rstest]
+#[case("-hello")]
+#[case("- item")]
+#[case("[bracket")]
+#[case("{brace")]
+#[case("\"quoted")]
+fn spec07_leading_special_chars_are_quoted(#[case] text: &str) {
+    let input = json!({"k": text, "tags": [text, "z"], "rows": [{"id": text}]});
+    let encoded = serde_toon::to_string(&input).unwrap();
+    assert!(
+        !encoded
+            .lines()
+            .any(|line| line.trim_start().starts_with(text)),
+        "{encoded}"
+    );
+    assert_eq!(serde_toon::decode_to_value(&encoded).unwrap(), input);
+}
+
+#[test]
+fn spec07_escape_leading_special_chars_quotes_hash() {
+    let input = json!({"k": "#hash", "rows": [{"id": "#1"}, {"id": "#2"}]});
+    assert_eq!(
+        serde_toon::to_string(&input).unwrap(),
+        "k: #hash\nrows[2]{id}:\n  #1\n  #2"
+    );
+
+    let options = EncodeOptions::new().with_escape_leading_special_chars(true);
+    let encoded = serde_toon::to_string_with_options(&input, &options).unwrap();
+    assert_eq!(encoded, "k: \"#hash\"\nrows[2]{id}:\n  \"#1\"\n  \"#2\"");
+
+    let capturing = DecodeOptions::new().with_capture_comments(true);
+    let (decoded, comments): (Value, _) =
+        serde_toon::from_str_with_comments(&encoded, &capturing).unwrap();
+    assert_eq!(decoded, input);
+    assert!(comments.is_empty());
+}
+
+#[rstest]
+#[case("\"a:b\": 1", json!({"a:b": 1}))]
+#[case("\"http://example\": \"x:y\"", json!({"http://example": "x:y"}))]
+#[case("\"k:1\"[2]: a,b", json!({"k:1": ["a", "b"]}))]
+#[case("\"k:1\"[1]{\"a:b\"}:\n  1", json!({"k:1": [{"a:b": 1}]}))]
+#[case("o:\n  \"a:b\": 1\n  \"k:1\"[1]: x", json!({"o": {"a:b": 1, "k:1": ["x"]}}))]
+fn spec07_quoted_keys_with_colons(#[case] input: &str, #[case] expected: Value) {
+    let tree = DecodeOptions::new().with_numeric_separators(true);
+    assert_eq!(serde_toon::decode_to_value(input).unwrap(), expected);
+    assert_eq!(
+        serde_toon::from_str_with_options::<Value>(input, &tree).unwrap(),
+        expected
+    );
+    assert_eq!(serde_toon::to_string(&expected).unwrap(), input);
+}
+
+#[test]
+fn spec07_surrounding_whitespace_is_quoted_and_preserved() {
+    let input = json!({"s": " hello ", "v": ["\tx", "y "], "rows": [{"a": " z"}]});
+    let toon = serde_toon::to_string(&input).unwrap();
+    assert_eq!(
+        toon,
+        "s: \" hello \"\nv[2]: \"\\tx\",\"y \"\nrows[1]{a}:\n  \" z\""
+    );
+    assert_eq!(serde_toon::from_str::<Value>(&toon).unwrap(), input);
+    assert_eq!(serde_toon::decode_to_value(&toon).unwrap(), input);
+}
+
+#[rstest]
+#[case(DecodeOptions::new())]
+#[case(DecodeOptions::new().with_numeric_separators(true))]
+fn spec07_trim_quoted_trims_values_only(#[case] options: DecodeOptions) {
+    let toon =
+        "\" k \": \" hello \"\nv[2]: \"\\tx\",\" \"\nrows[1]{a}:\n  \" z\"\nitems[1]:\n  - \" w \"";
+    let preserved: Value = serde_toon::from_str_with_options(toon, &options).unwrap();
+    assert_eq!(preserved[" k "], " hello ");
+    assert_eq!(preserved["v"], json!(["\tx", " "]));
+
+    let options = options.with_trim_quoted(true);
+    let trimmed: Value = serde_toon::from_str_with_options(toon, &options).unwrap();
+    assert_eq!(
+        trimmed,
+        json!({" k ": "hello", "v": ["x", ""], "rows": [{"a": "z"}], "items": ["w"]})
+    );
+    assert_eq!(
+        serde_toon::decode_to_value_with_options(toon, &options).unwrap(),
+        trimmed
+    );
+}
+
+#[rstest]
+#[case("a|b", false, true)]
+#[case("a,b", true, false)]
+#[case("plain", false, false)]
+#[case("a b", false, false)]
+#[case("", true, true)]
+#[case("true", true, true)]
+#[case("42", true, true)]
+#[case("- x", true, true)]
+#[case("k: v", true, true)]
+fn spec07_needs_quoting_for_delimiter(
+    #[case] value: &str,
+    #[case] comma: bool,
+    #[case] pipe: bool,
+) {
+    assert_eq!(
+        serde_toon::needs_quoting_for(value, Delimiter::Comma),
+        comma
+    );
+    assert_eq!(serde_toon::needs_quoting_for(value, Delimiter::Pipe), pipe);
+    for (delimiter, quoted) in [(Delimiter::Comma, comma), (Delimiter::Pipe, pipe)] {
+        let options = EncodeOptions::new().with_delimiter(delimiter);
+        let toon =
+            serde_toon::to_string_with_options(&json!({"items": [value, "x"]}), &options).unwrap();
+        let cell = toon.split(": ").nth(1).unwrap();
+        assert_eq!(cell.starts_with('"'), quoted, "{toon}");
+    }
+}
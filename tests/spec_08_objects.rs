@@ -1,8 +1,8 @@
 use rstest::rstest;
 use serde_json::{json, Value};
 use serde_toon::{
-    DecodeOptions, Delimiter, EncodeOptions, ExpandPaths as ToonExpandPaths, Indent,
-    KeyFolding as ToonKeyFolding,
+    ColonSpacing, DecodeOptions, Delimiter, EncodeOptions, ExpandPaths as ToonExpandPaths, Indent,
+    KeyCase, KeyFolding as ToonKeyFolding, KeyOrder,
 };
 
 #[allow(dead_code)]
@@ -170,3 +170,278 @@ fn spec08_objects_validate(#[case] input: &str, #[case] valid: bool) {
         assert!(result.is_err());
     }
 }
+
+#[rstest]
+#[case(
+    "users[2]{id,name}:\n  1,a\n  2,b\nversion: 3",
+    json!({"users": [{"id": 1, "name": "a"}, {"id": 2, "name": "b"}], "version": 3})
+)]
+#[case(
+    "version: 3\nusers[1]{id,name}:\n  1,a\nmeta:\n  x: 1\ntags[2]: a,b\nlast: true",
+    json!({
+        "version": 3,
+        "users": [{"id": 1, "name": "a"}],
+        "meta": {"x": 1},
+        "tags": ["a", "b"],
+        "last": true
+    })
+)]
+#[case(
+    "a:\n  users[1]{id}:\n    1\n  v: 2\nb: 3",
+    json!({"a": {"users": [{"id": 1}], "v": 2}, "b": 3})
+)]
+#[case(
+    "users[1]{id}:\n  1\n\nversion: 3",
+    json!({"users": [{"id": 1}], "version": 3})
+)]
+fn spec08_siblings_after_tabular_block(#[case] input: &str, #[case] expected: Value) {
+    let arena: Value = serde_toon::from_str(input).unwrap();
+    assert_eq!(arena, expected);
+    assert_eq!(serde_toon::decode_to_value(input).unwrap(), expected);
+    assert!(serde_toon::validate_str(input).is_ok());
+    assert_eq!(
+        serde_toon::to_string(&expected).unwrap(),
+        input.replace("\n\n", "\n")
+    );
+}
+
+#[rstest]
+#[case(1, "id: 1\npoint: {x: 1, y: 2}\nmeta:\n  owner: {name: Ada, \"first,last\": \"a,b\"}\n  tags[2|]: a|b\nitems[1|]:\n  - pos: {x: 0}\n    deep:\n      a: {b: true}")]
+#[case(2, "id: 1\npoint: {x: 1, y: 2}\nmeta:\n  owner: {name: Ada, \"first,last\": \"a,b\"}\n  tags[2|]: a|b\nitems[1|]:\n  - pos: {x: 0}\n    deep: {a: {b: true}}")]
+fn spec08_inline_depth_round_trip(#[case] depth: usize, #[case] expected: &str) {
+    let input = json!({
+        "id": 1,
+        "point": {"x": 1, "y": 2},
+        "meta": {"owner": {"name": "Ada", "first,last": "a,b"}, "tags": ["a", "b"]},
+        "items": [{"pos": {"x": 0}, "deep": {"a": {"b": true}}}]
+    });
+    let options = EncodeOptions::new()
+        .with_inline_depth(depth)
+        .with_delimiter(Delimiter::Pipe);
+    let toon = serde_toon::to_string_with_options(&input, &options).unwrap();
+    assert_eq!(toon, expected);
+
+    let decode = DecodeOptions::new().with_inline_objects(true);
+    let decoded: Value = serde_toon::from_str_with_options(&toon, &decode).unwrap();
+    assert_eq!(decoded, input);
+    assert_eq!(
+        serde_toon::decode_to_value_with_options(&toon, &decode).unwrap(),
+        input
+    );
+}
+
+#[rstest]
+#[case(
+    0,
+    "point:\n  x: 1\n  y: 2\nitems[2]:\n  - 1\n  - a: true\n    b: null"
+)]
+#[case(
+    1,
+    "point:\n  x: 1\n  y: 2\nitems[2]:\n  - 1\n  - a: true\n    b: null"
+)]
+#[case(2, "point: {x: 1, y: 2}\nitems[2]:\n  - 1\n  - {a: true, b: null}")]
+fn spec08_inline_small_objects_round_trip(#[case] max_fields: usize, #[case] expected: &str) {
+    let input = json!({"point": {"x": 1, "y": 2}, "items": [1, {"a": true, "b": null}]});
+    let options = EncodeOptions::new().with_inline_small_objects(max_fields);
+    let toon = serde_toon::to_string_with_options(&input, &options).unwrap();
+    assert_eq!(toon, expected);
+
+    let decode = DecodeOptions::new().with_inline_objects(true);
+    let decoded: Value = serde_toon::from_str_with_options(&toon, &decode).unwrap();
+    assert_eq!(decoded, input);
+    assert_eq!(
+        serde_toon::decode_to_value_with_options(&toon, &decode).unwrap(),
+        input
+    );
+    assert!(serde_toon::validate_str_with_options(&toon, &decode).is_ok());
+    assert!(serde_toon::validate_reader_with_options(toon.as_bytes(), &decode).is_ok());
+}
+
+#[test]
+fn spec08_inline_small_objects_skip_nested_values() {
+    let input = json!({"a": {"b": {"c": 1}}, "d": {"e": [1]}});
+    let options = EncodeOptions::new().with_inline_small_objects(3);
+    let toon = serde_toon::to_string_with_options(&input, &options).unwrap();
+    assert_eq!(toon, "a:\n  b: {c: 1}\nd:\n  e[1]: 1");
+}
+
+#[rstest]
+#[case("a: {b: 1")]
+#[case("a: {b: 1}}")]
+#[case("a: {b 1}")]
+#[case("a: {b: }")]
+#[case("a: {b: \"x}")]
+fn spec08_inline_objects_reject_malformed(#[case] input: &str) {
+    let decode = DecodeOptions::new().with_inline_objects(true);
+    assert!(serde_toon::decode_to_value_with_options(input, &decode).is_err());
+}
+
+#[rstest]
+#[case("a: {b: 1}", json!({"a": "{b: 1}"}))]
+#[case("items[1]: {b: 1}", json!({"items": ["{b: 1}"]}))]
+fn spec08_inline_objects_are_strings_without_option(#[case] input: &str, #[case] expected: Value) {
+    let decoded = serde_toon::decode_to_value_with_options(input, &DecodeOptions::new()).unwrap();
+    assert_eq!(decoded, expected);
+    let decoded: Value = serde_toon::from_str(input).unwrap();
+    assert_eq!(decoded, expected);
+}
+
+#[test]
+fn spec08_quoted_braces_stay_strings_with_inline_objects() {
+    let input = "a: \"{b: 1}\"";
+    let decoded = serde_toon::decode_to_value_with_options(
+        input,
+        &DecodeOptions::new().with_inline_objects(true),
+    )
+    .unwrap();
+    assert_eq!(decoded, json!({"a": "{b: 1}"}));
+    let toon = serde_toon::to_string_with_options(
+        &json!({"a": "{b: 1}"}),
+        &EncodeOptions::new().with_inline_depth(1),
+    )
+    .unwrap();
+    assert_eq!(toon, input);
+}
+
+#[rstest]
+#[case(KeyCase::Lower, "Name: Ada\nUserId: 7", json!({"name": "Ada", "userid": 7}))]
+#[case(KeyCase::Snake, "Name: Ada\nUserId: 7", json!({"name": "Ada", "user_id": 7}))]
+#[case(KeyCase::Snake, "HTTPServer: x\nNAME: y", json!({"http_server": "x", "name": "y"}))]
+#[case(
+    KeyCase::Snake,
+    "Rows[1]{FirstName,Age}:\n  Ada,37",
+    json!({"rows": [{"first_name": "Ada", "age": 37}]})
+)]
+#[case(KeyCase::Lower, "\"Quoted\": 1\nPlain: 2", json!({"Quoted": 1, "plain": 2}))]
+#[case(KeyCase::Preserve, "Name: Ada\nname: Grace", json!({"Name": "Ada", "name": "Grace"}))]
+fn spec08_key_normalize(#[case] key_case: KeyCase, #[case] input: &str, #[case] expected: Value) {
+    let options = DecodeOptions::new().with_key_normalize(key_case);
+    let decoded: Value = serde_toon::from_str_with_options(input, &options).unwrap();
+    assert_eq!(decoded, expected);
+}
+
+#[test]
+fn spec08_key_normalize_collisions_are_duplicates() {
+    let options = DecodeOptions::new().with_key_normalize(KeyCase::Lower);
+    let err =
+        serde_toon::from_str_with_options::<Value>("Name: Ada\nname: Grace", &options).unwrap_err();
+    assert!(err.to_string().contains("duplicate key 'name'"), "{err}");
+    let decoded: Value =
+        serde_toon::from_str_with_options("Name: Ada\nname: Grace", &options.with_strict(false))
+            .unwrap();
+    assert_eq!(decoded, json!({"name": "Grace"}));
+}
+
+#[test]
+fn spec08_key_normalize_before_path_expansion() {
+    let options = DecodeOptions::new()
+        .with_key_normalize(KeyCase::Lower)
+        .with_expand_paths(ToonExpandPaths::Safe);
+    let decoded: Value =
+        serde_toon::from_str_with_options("User.Name: Ada\nuser.age: 37", &options).unwrap();
+    assert_eq!(decoded, json!({"user": {"name": "Ada", "age": 37}}));
+}
+
+#[test]
+fn spec08_key_order_leads_with_listed_keys() {
+    let input = json!({
+        "name": "app",
+        "deps": {"b": 1, "version": "2", "a": 3},
+        "version": 3,
+        "author": "Ada"
+    });
+    let options = EncodeOptions::new().with_key_order(vec![
+        "version".to_string(),
+        "missing".to_string(),
+        "author".to_string(),
+        "version".to_string(),
+    ]);
+    let toon = serde_toon::to_string_with_options(&input, &options).unwrap();
+    assert_eq!(
+        toon,
+        "version: 3\nauthor: Ada\nname: app\ndeps:\n  version: \"2\"\n  b: 1\n  a: 3"
+    );
+    let decoded: Value = serde_toon::from_str(&toon).unwrap();
+    assert_eq!(decoded, input);
+}
+
+#[test]
+fn spec08_key_order_applies_to_objects_in_arrays() {
+    let input = json!({"items": [{"id": 1, "b": [1, {"x": 1}], "version": 3}]});
+    let options = EncodeOptions::new().with_key_order(vec!["version".to_string()]);
+    let toon = serde_toon::to_string_with_options(&input, &options).unwrap();
+    assert_eq!(
+        toon,
+        "items[1]:\n  - version: 3\n    id: 1\n    b[2]:\n      - 1\n      - x: 1"
+    );
+    let decoded: Value = serde_toon::from_str(&toon).unwrap();
+    assert_eq!(decoded, input);
+}
+
+#[rstest]
+#[case(
+    ColonSpacing::Single,
+    "name: Ada\ntags[2]: a,b\nuser:\n  id: 1\nitems[1]:\n  - a: 1\n    b[1]: x"
+)]
+#[case(
+    ColonSpacing::None,
+    "name:Ada\ntags[2]:a,b\nuser:\n  id:1\nitems[1]:\n  - a:1\n    b[1]:x"
+)]
+fn spec08_colon_spacing_round_trips(#[case] spacing: ColonSpacing, #[case] expected: &str) {
+    let input = json!({
+        "name": "Ada",
+        "tags": ["a", "b"],
+        "user": {"id": 1},
+        "items": [{"a": 1, "b": ["x"]}]
+    });
+    let options = EncodeOptions::new().with_colon_spacing(spacing);
+    let toon = serde_toon::to_string_with_options(&input, &options).unwrap();
+    assert_eq!(toon, expected);
+    assert_eq!(serde_toon::decode_to_value(&toon).unwrap(), input);
+    let decoded: Value = serde_toon::from_str(&toon).unwrap();
+    assert_eq!(decoded, input);
+}
+
+#[rstest]
+#[case(KeyOrder::Preserve, "b: 1\na: 2\nnested:\n  z: true\n  y: false")]
+#[case(KeyOrder::Sorted, "a: 2\nb: 1\nnested:\n  y: false\n  z: true")]
+fn spec08_key_ordering(#[case] key_ordering: KeyOrder, #[case] expected: &str) {
+    let input = json!({"b": 1, "a": 2, "nested": {"z": true, "y": false}});
+    let options = EncodeOptions::new().with_key_ordering(key_ordering);
+    assert_eq!(
+        serde_toon::to_string_with_options(&input, &options).unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn spec08_key_ordering_sorts_after_listed_keys() {
+    let input = json!({"b": 1, "version": 2, "a": 3});
+    let options = EncodeOptions::new()
+        .with_key_order(vec!["version".to_string()])
+        .with_key_ordering(KeyOrder::Sorted);
+    assert_eq!(
+        serde_toon::to_string_with_options(&input, &options).unwrap(),
+        "version: 2\na: 3\nb: 1"
+    );
+}
+
+#[test]
+fn spec08_key_ordering_sorts_array_items_and_tabular_headers() {
+    let input = json!({
+        "items": [{"id": 1, "zeta": [1, {"b": 1, "a": 2}], "alpha": 2}],
+        "rows": [{"id": 1, "zeta": 2, "alpha": 3}, {"id": 4, "zeta": 5, "alpha": 6}]
+    });
+    let options = EncodeOptions::new().with_key_ordering(KeyOrder::Sorted);
+    let toon = serde_toon::to_string_with_options(&input, &options).unwrap();
+    assert_eq!(
+        toon,
+        "items[1]:\n  - alpha: 2\n    id: 1\n    zeta[2]:\n      - 1\n      - a: 2\n        b: 1\nrows[2]{alpha,id,zeta}:\n  3,1,2\n  6,4,5"
+    );
+    let decoded: Value = serde_toon::from_str(&toon).unwrap();
+    assert_eq!(decoded, input);
+
+    let options = options.with_key_order(vec!["id".to_string()]);
+    let toon = serde_toon::to_string_with_options(&input["rows"], &options).unwrap();
+    assert_eq!(toon, "[2]{id,alpha,zeta}:\n  1,3,2\n  4,6,5");
+}
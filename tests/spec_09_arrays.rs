@@ -1,3 +1,5 @@
+use std::sync::{Arc, Mutex};
+
 use rstest::rstest;
 use serde_json::{json, Value};
 use serde_toon::{
@@ -146,7 +148,7 @@ fn map_decode_options(options: &SpecOptions) -> DecodeOptions {
 )]
 #[case(
     json!({"items": [{"a": {"b": 1}}, {"a": {"b": 2}}]}),
-    Some("items[2]:\n  - a:\n    b: 1\n  - a:\n    b: 2"),
+    Some("items[2]:\n  - a:\n      b: 1\n  - a:\n      b: 2"),
     SpecOptions::default()
 )]
 #[case(
@@ -337,3 +339,510 @@ fn spec09_arrays_validate(#[case] input: &str, #[case] valid: bool) {
         assert!(result.is_err());
     }
 }
+
+#[rstest]
+#[case("items[2]{a,b}:\n  \"line1\nline2\",x\n  y,z", "row 1, column 1")]
+#[case("items[2]{a,b}:\n  y,z\n  x,\"line1\nline2\"", "row 2, column 2")]
+fn spec09_tabular_rejects_raw_newline_in_quoted_cell(#[case] input: &str, #[case] position: &str) {
+    let arena = serde_toon::from_str::<Value>(input).unwrap_err();
+    let tree = serde_toon::decode_to_value(input).unwrap_err();
+    let streamed = serde_toon::validate_reader(input.as_bytes()).unwrap_err();
+    for err in [arena, tree, streamed] {
+        let message = err.to_string();
+        assert!(message.contains("unterminated string"), "{message}");
+        assert!(message.contains(position), "{message}");
+        assert!(err.is_recoverable(), "{message}");
+    }
+}
+
+#[test]
+fn spec09_tabular_header_follows_first_row_order() {
+    let input = json!({"items": [
+        {"b": 1, "a": 2, "c": 3},
+        {"c": 4, "a": 5, "b": 6},
+        {"a": 7, "c": 8, "b": 9}
+    ]});
+    let toon = serde_toon::to_string(&input).unwrap();
+    assert_eq!(toon, "items[3]{b,a,c}:\n  1,2,3\n  6,5,4\n  9,7,8");
+    assert_eq!(serde_toon::to_string(&input).unwrap(), toon);
+}
+
+#[test]
+fn spec09_diagnostics_hook_reports_tabular_fallback() {
+    use serde_toon::Diagnostic;
+
+    let diagnostics = Arc::new(Mutex::new(Vec::new()));
+    let collector = Arc::clone(&diagnostics);
+    let options = EncodeOptions::new().with_diagnostics_hook(Arc::new(move |diagnostic| {
+        collector.lock().unwrap().push(diagnostic)
+    }));
+    let cases = [
+        (
+            json!([{"id": 1, "tags": ["a"]}]),
+            Diagnostic::NonScalarField {
+                row: 0,
+                field: "tags".to_string(),
+            },
+        ),
+        (
+            json!([{"id": 1}, {"id": 2, "x": 3}]),
+            Diagnostic::FieldMismatch { row: 1 },
+        ),
+        (
+            json!([{"id": 1}, {"name": 2}]),
+            Diagnostic::FieldMismatch { row: 1 },
+        ),
+        (json!([{"id": 1}, 2]), Diagnostic::NonObjectRow { row: 1 }),
+        (json!([{}, {"id": 1}]), Diagnostic::EmptyFirstRow),
+    ];
+    for (input, expected) in cases {
+        serde_toon::to_string_with_options(&input, &options).unwrap();
+        let reported = std::mem::take(&mut *diagnostics.lock().unwrap());
+        assert_eq!(reported, vec![expected]);
+    }
+
+    serde_toon::to_string_with_options(&json!({"rows": [{"a": 1}], "n": [1, 2]}), &options)
+        .unwrap();
+    assert!(diagnostics.lock().unwrap().is_empty());
+
+    let options = options.with_max_tabular_columns(2);
+    let toon =
+        serde_toon::to_string_with_options(&json!([{"a": 1, "b": 2, "c": 3}]), &options).unwrap();
+    assert_eq!(toon, "[1]:\n  - a: 1\n    b: 2\n    c: 3");
+    let reported = std::mem::take(&mut *diagnostics.lock().unwrap());
+    assert_eq!(
+        reported,
+        vec![Diagnostic::TooManyColumns { columns: 3, max: 2 }]
+    );
+    assert_eq!(
+        reported[0].to_string(),
+        "array not tabular: 3 columns exceed the maximum of 2"
+    );
+}
+
+#[test]
+fn spec09_diagnostic_messages() {
+    let diagnostic = serde_toon::Diagnostic::NonScalarField {
+        row: 0,
+        field: "tags".to_string(),
+    };
+    assert_eq!(
+        diagnostic.to_string(),
+        "array not tabular: field 'tags' is non-scalar in row 0"
+    );
+}
+
+#[rstest]
+#[case(None, "rows[3]{a,b}:\n  1,\"\"\n  2,null\n  \"\",x")]
+#[case(Some(""), "rows[3]{a,b}:\n  1,\n  2,null\n  ,x")]
+fn spec09_empty_string_marker_keeps_null_distinct(
+    #[case] marker: Option<&str>,
+    #[case] expected: &str,
+) {
+    let input = json!({"rows": [{"a": 1, "b": ""}, {"a": 2, "b": null}, {"a": "", "b": "x"}]});
+    let options = EncodeOptions::new().with_empty_string_marker(marker.map(str::to_string));
+    let encoded = serde_toon::to_string_with_options(&input, &options).unwrap();
+    assert_eq!(encoded, expected);
+    assert_eq!(serde_toon::decode_to_value(&encoded).unwrap(), input);
+}
+
+#[test]
+fn spec09_empty_string_marker_applies_to_tabular_cells_only() {
+    let options = EncodeOptions::new().with_empty_string_marker(Some("-".to_string()));
+    let encoded = serde_toon::to_string_with_options(
+        &json!({"rows": [{"a": ""}], "list": [""], "v": ""}),
+        &options,
+    )
+    .unwrap();
+    assert_eq!(encoded, "rows[1]{a}:\n  -\nlist[1]: \"\"\nv: \"\"");
+}
+
+#[test]
+fn spec09_empty_string_marker_is_not_reversed_on_decode() {
+    let options = EncodeOptions::new().with_empty_string_marker(Some("-".to_string()));
+    let encoded =
+        serde_toon::to_string_with_options(&json!({"rows": [{"a": "", "b": 1}]}), &options)
+            .unwrap();
+    assert_eq!(encoded, "rows[1]{a,b}:\n  -,1");
+    assert_eq!(
+        serde_toon::decode_to_value(&encoded).unwrap(),
+        json!({"rows": [{"a": "-", "b": 1}]})
+    );
+}
+
+#[rstest]
+#[case("x,y", Delimiter::Comma)]
+#[case("x|y", Delimiter::Pipe)]
+#[case("a:b", Delimiter::Comma)]
+#[case("\"\"", Delimiter::Comma)]
+#[case(" -", Delimiter::Comma)]
+#[case("n/a ", Delimiter::Comma)]
+#[case("null", Delimiter::Comma)]
+#[case("0", Delimiter::Comma)]
+#[case("[]", Delimiter::Comma)]
+#[case("#", Delimiter::Comma)]
+fn spec09_empty_string_marker_rejects_markers_that_misdecode(
+    #[case] marker: &str,
+    #[case] delimiter: Delimiter,
+) {
+    let options = EncodeOptions::new()
+        .with_delimiter(delimiter)
+        .with_empty_string_marker(Some(marker.to_string()));
+    let err = serde_toon::to_string_with_options(&json!({"rows": [{"a": "", "b": 1}]}), &options)
+        .unwrap_err();
+    assert!(
+        err.message.starts_with("invalid empty string marker"),
+        "{err}"
+    );
+}
+
+#[rstest]
+#[case(json!([{"id": 1, "name": "a"}, {"name": "b", "id": 2}]), Some(vec!["id", "name"]))]
+#[case(json!([{"id": 1}, {"id": 2, "extra": 3}]), None)]
+#[case(json!([{"id": 1}, {"other": 2}]), None)]
+#[case(json!([{"id": 1}, 2]), None)]
+#[case(json!([{"id": [1]}]), None)]
+#[case(json!([{}]), None)]
+#[case(json!([]), None)]
+#[case(json!([1, 2]), None)]
+fn spec09_is_tabular_candidate_matches_encoder(
+    #[case] input: Value,
+    #[case] expected: Option<Vec<&str>>,
+) {
+    let array = input.as_array().unwrap();
+    let fields = serde_toon::is_tabular_candidate(array);
+    assert_eq!(
+        fields,
+        expected.map(|fields| fields.into_iter().map(str::to_string).collect())
+    );
+    let toon = serde_toon::to_string(&json!({ "items": input })).unwrap();
+    let header = toon.lines().next().unwrap();
+    assert_eq!(fields.is_some(), header.contains('{'), "{toon}");
+}
+
+#[test]
+fn spec09_max_tabular_columns_falls_back_to_list_items() {
+    let row = |id: usize| {
+        Value::Object(
+            (0..300)
+                .map(|col| (format!("c{col:03}"), json!(id * 1000 + col)))
+                .collect(),
+        )
+    };
+    let input = json!({"rows": [row(1), row(2)]});
+
+    let options = EncodeOptions::new().with_max_tabular_columns(100);
+    let encoded = serde_toon::to_string_with_options(&input, &options).unwrap();
+    assert!(encoded.starts_with("rows[2]:\n  - c000: 1000\n    c001: 1001\n"));
+    assert!(encoded.lines().all(|line| line.len() < 40));
+    assert_eq!(serde_toon::decode_to_value(&encoded).unwrap(), input);
+
+    let options = EncodeOptions::new().with_max_tabular_columns(300);
+    let encoded = serde_toon::to_string_with_options(&input, &options).unwrap();
+    assert!(encoded.starts_with("rows[2]{c000,"));
+}
+
+#[rstest]
+#[case(json!({"tags": ["only"]}), "tags[1]: only", "tags[1]:\n  - only")]
+#[case(json!([42]), "[1]: 42", "[1]:\n  - 42")]
+#[case(json!({"a": [[1]]}), "a[1]:\n  - [1]: 1", "a[1]:\n  - [1]:\n    - 1")]
+#[case(
+    json!({"items": [{"tags": ["x"], "id": 1}]}),
+    "items[1]:\n  - tags[1]: x\n    id: 1",
+    "items[1]:\n  - tags[1]:\n      - x\n    id: 1"
+)]
+#[case(json!({"tags": ["a", "b"]}), "tags[2]: a,b", "tags[2]: a,b")]
+#[case(json!({"tags": []}), "tags[0]:", "tags[0]:")]
+fn spec09_single_element_array_style(
+    #[case] input: Value,
+    #[case] inline: &str,
+    #[case] list: &str,
+) {
+    for (style, expected) in [
+        (serde_toon::SingleElementArrayStyle::Inline, inline),
+        (serde_toon::SingleElementArrayStyle::List, list),
+    ] {
+        let options = EncodeOptions::new().with_single_element_array_style(style);
+        let encoded = serde_toon::to_string_with_options(&input, &options).unwrap();
+        assert_eq!(encoded, expected);
+        assert_eq!(serde_toon::decode_to_value(&encoded).unwrap(), input);
+    }
+}
+
+#[rstest]
+#[case(json!({"rows": [{"id": 1}, {"id": 2}]}), "rows[2]{id}:\n  1\n  2")]
+#[case(json!([{"name": "a b"}, {"name": null}]), "[2]{name}:\n  a b\n  null")]
+#[case(json!({"rows": [{"id": 1}]}), "rows[1]{id}:\n  1")]
+fn spec09_single_column_objects_stay_tabular(#[case] input: Value, #[case] expected: &str) {
+    let encoded = serde_toon::to_string(&input).unwrap();
+    assert_eq!(encoded, expected);
+    assert_eq!(serde_toon::decode_to_value(&encoded).unwrap(), input);
+}
+
+#[test]
+fn spec09_empty_containers_in_tabular_cells() {
+    let input = json!({"rows": [
+        {"id": 1, "tags": [], "meta": {}},
+        {"id": 2, "tags": [], "meta": {}},
+    ]});
+    assert!(serde_toon::to_string(&input)
+        .unwrap()
+        .starts_with("rows[2]:\n  - id: 1"));
+
+    let encode = EncodeOptions::new().with_empty_containers_in_tabular(true);
+    let decode = DecodeOptions::new().with_empty_containers_in_tabular(true);
+    let encoded = serde_toon::to_string_with_options(&input, &encode).unwrap();
+    assert_eq!(encoded, "rows[2]{id,tags,meta}:\n  1,[],{}\n  2,[],{}");
+    assert_eq!(
+        serde_toon::from_str_with_options::<Value>(&encoded, &decode).unwrap(),
+        input
+    );
+    assert_eq!(
+        serde_toon::decode_to_value(&encoded).unwrap()["rows"][0]["tags"],
+        json!("[]")
+    );
+
+    let mixed = json!([{"tags": []}, {"tags": ["a"]}]);
+    let encoded = serde_toon::to_string_with_options(&mixed, &encode).unwrap();
+    assert!(encoded.starts_with("[2]:\n"));
+    assert_eq!(
+        serde_toon::from_str_with_options::<Value>(&encoded, &decode).unwrap(),
+        mixed
+    );
+    assert_eq!(
+        serde_toon::from_str_with_options::<Value>("[1]{s}:\n  \"[]\"", &decode).unwrap(),
+        json!([{"s": "[]"}])
+    );
+}
+
+#[test]
+fn spec09_empty_containers_in_large_tabular_arrays() {
+    let rows: Vec<Value> = (0..300)
+        .map(|id| json!({"id": id, "a": [], "b": {}, "c": 1, "d": 2, "e": 3, "f": 4, "g": []}))
+        .collect();
+    let input = Value::Array(rows);
+    let encode = EncodeOptions::new().with_empty_containers_in_tabular(true);
+    let decode = DecodeOptions::new().with_empty_containers_in_tabular(true);
+    let encoded = serde_toon::to_string_with_options(&input, &encode).unwrap();
+    assert!(encoded.starts_with("[300]{id,a,b,c,d,e,f,g}:\n  0,[],{},1,2,3,4,[]\n"));
+    assert_eq!(
+        serde_toon::from_str_with_options::<Value>(&encoded, &decode).unwrap(),
+        input
+    );
+}
+
+#[test]
+fn spec09_unwrap_single_key_tabular_round_trips_with_expand_paths() {
+    let input = json!({
+        "rows": [{"user": {"id": 1, "name": "a"}}, {"user": {"id": 2, "name": "b"}}]
+    });
+    let options = EncodeOptions::new().with_unwrap_single_key_tabular(true);
+    let toon = serde_toon::to_string_with_options(&input, &options).unwrap();
+    assert_eq!(toon, "rows[2]{user.id,user.name}:\n  1,a\n  2,b");
+
+    let decode = DecodeOptions::new().with_expand_paths(ToonExpandPaths::Safe);
+    let decoded: Value = serde_toon::from_str_with_options(&toon, &decode).unwrap();
+    assert_eq!(decoded, input);
+    assert_eq!(
+        serde_toon::decode_to_value_with_options(&toon, &decode).unwrap(),
+        input
+    );
+}
+
+#[rstest]
+#[case(json!([{"user": {"id": 1}}, {"admin": {"id": 2}}]))]
+#[case(json!([{"user": {"id": 1}}, {"user": {"name": "b"}}]))]
+#[case(json!([{"user": {"id": 1}, "x": 1}, {"user": {"id": 2}, "x": 2}]))]
+#[case(json!([{"user": {"id": 1, "tags": [1, 2]}}]))]
+#[case(json!([{"user": {"a.b": 1}}]))]
+#[case(json!([{"user.x": {"id": 1}}]))]
+fn spec09_unwrap_single_key_tabular_skips_other_shapes(#[case] input: Value) {
+    let plain = serde_toon::to_string(&input).unwrap();
+    let options = EncodeOptions::new().with_unwrap_single_key_tabular(true);
+    assert_eq!(
+        serde_toon::to_string_with_options(&input, &options).unwrap(),
+        plain
+    );
+}
+
+#[rstest]
+#[case(json!({"matrix": [[1, 2, 3], [4, 5, 6]]}), "matrix[2x3]:\n  1,2,3\n  4,5,6")]
+#[case(json!({"m": [[1, 0], [0, 1]]}), "m[2x2]:\n  1,0\n  0,1")]
+#[case(json!([[1.5, "a b"], [true, null]]), "[2x2]:\n  1.5,a b\n  true,null")]
+#[case(
+    json!({"rows": [{"m": [[1], [2]], "k": 1}]}),
+    "rows[1]:\n  - m[2x1]:\n      1\n      2\n    k: 1"
+)]
+fn spec09_matrix_tabular_round_trips(#[case] input: Value, #[case] expected: &str) {
+    let options = EncodeOptions::new().with_matrix_tabular(true);
+    let toon = serde_toon::to_string_with_options(&input, &options).unwrap();
+    assert_eq!(toon, expected);
+    for strict in [true, false] {
+        let decode = DecodeOptions::new()
+            .with_strict(strict)
+            .with_matrix_tabular(true);
+        let decoded: Value = serde_toon::from_str_with_options(&toon, &decode).unwrap();
+        assert_eq!(decoded, input);
+        assert_eq!(
+            serde_toon::decode_to_value_with_options(&toon, &decode).unwrap(),
+            input
+        );
+        assert!(serde_toon::validate_str_with_options(&toon, &decode).is_ok());
+        assert!(serde_toon::validate_reader_with_options(toon.as_bytes(), &decode).is_ok());
+    }
+}
+
+#[test]
+fn spec09_matrix_tabular_uses_active_delimiter() {
+    let options = EncodeOptions::new()
+        .with_matrix_tabular(true)
+        .with_delimiter(Delimiter::Pipe);
+    let input = json!({"m": [["a,b", "c"], ["d", "e"]]});
+    let toon = serde_toon::to_string_with_options(&input, &options).unwrap();
+    assert_eq!(toon, "m[2x2|]:\n  a,b|c\n  d|e");
+    let decode = DecodeOptions::new().with_matrix_tabular(true);
+    assert_eq!(
+        serde_toon::from_str_with_options::<Value>(&toon, &decode).unwrap(),
+        input
+    );
+}
+
+#[rstest]
+#[case(json!({"r": [[1, 2], [3]]}))]
+#[case(json!({"r": [[1, [2]], [3, 4]]}))]
+#[case(json!({"r": [[], []]}))]
+#[case(json!({"r": [[1, 2], 3]}))]
+fn spec09_matrix_tabular_skips_ragged_arrays(#[case] input: Value) {
+    let options = EncodeOptions::new().with_matrix_tabular(true);
+    assert_eq!(
+        serde_toon::to_string_with_options(&input, &options).unwrap(),
+        serde_toon::to_string(&input).unwrap()
+    );
+}
+
+#[rstest]
+#[case("m[2x3]:\n  1,2,3\n  4,5", "matrix row 2 has 2 values, expected 3")]
+#[case("m[2x2]:\n  1,2,3\n  4,5", "matrix row 1 has 3 values, expected 2")]
+#[case("m[2x]:\n  1\n  2", "matrix column count missing")]
+#[case("m[1x2]: 1,2", "matrix header cannot have")]
+#[case("m[1x1]{a}:\n  1", "matrix header cannot have")]
+#[case("m[2x0]:", "matrix column count must be at least 1")]
+#[case("[1x0]:\n  1", "matrix column count must be at least 1")]
+#[case("m[0x5]:\n  1,2,3,4,5", "array length mismatch")]
+fn spec09_matrix_decode_rejects_malformed(#[case] input: &str, #[case] message: &str) {
+    let options = DecodeOptions::new().with_matrix_tabular(true);
+    for err in [
+        serde_toon::from_str_with_options::<Value>(input, &options).unwrap_err(),
+        serde_toon::decode_to_value_with_options(input, &options).unwrap_err(),
+        serde_toon::validate_str_with_options(input, &options).unwrap_err(),
+        serde_toon::validate_reader_with_options(input.as_bytes(), &options).unwrap_err(),
+    ] {
+        assert!(err.to_string().contains(message), "{err}");
+    }
+}
+
+#[rstest]
+#[case("m[0x5]:", json!({"m": []}))]
+#[case("[0x2]:", json!([]))]
+#[case("rows[1]:\n  - m[0x3]:\n    k: 1", json!({"rows": [{"m": [], "k": 1}]}))]
+fn spec09_matrix_decode_reads_zero_rows_as_empty(#[case] input: &str, #[case] expected: Value) {
+    let options = DecodeOptions::new().with_matrix_tabular(true);
+    assert_eq!(
+        serde_toon::from_str_with_options::<Value>(input, &options).unwrap(),
+        expected
+    );
+    assert_eq!(
+        serde_toon::decode_to_value_with_options(input, &options).unwrap(),
+        expected
+    );
+    assert!(serde_toon::validate_str_with_options(input, &options).is_ok());
+}
+
+#[rstest]
+#[case("m[2x3]:\n  1,2,3\n  4,5,6")]
+#[case("[1x2|]:\n  a|b")]
+#[case("m[0x5]:")]
+fn spec09_matrix_headers_need_the_option(#[case] input: &str) {
+    for strict in [true, false] {
+        let options = DecodeOptions::new().with_strict(strict);
+        for err in [
+            serde_toon::from_str_with_options::<Value>(input, &options).unwrap_err(),
+            serde_toon::decode_to_value_with_options(input, &options).unwrap_err(),
+            serde_toon::validate_str_with_options(input, &options).unwrap_err(),
+        ] {
+            assert!(err.to_string().contains("invalid array delimiter"), "{err}");
+        }
+    }
+}
+
+fn heterogeneous_item(i: usize) -> Value {
+    match i % 6 {
+        0 => json!({"id": i, "tags": ["a", "b c"], "meta": {"ok": true, "note": null}}),
+        1 => json!({"rows": [{"x": i, "y": "v"}, {"x": i + 1, "y": "w"}], "name": "item"}),
+        2 => json!([i, [1, 2], {"k": "- dash"}]),
+        3 => json!(format!("s{i}, with comma")),
+        4 => json!({"matrix": [[1, 2], [3, 4]], "empty": [], "obj": {}}),
+        _ => json!({}),
+    }
+}
+
+// A diagnostics hook keeps list items on the serial path under the
+// `parallel` feature, so both encodings must match byte for byte.
+#[rstest]
+#[case(EncodeOptions::new())]
+#[case(EncodeOptions::new().with_indent(Indent::spaces(4)).with_delimiter(Delimiter::Pipe))]
+#[case(EncodeOptions::new().with_key_folding(ToonKeyFolding::Safe).with_indent_first_level(2))]
+fn spec09_large_list_arrays_encode_identically_in_parallel(#[case] options: EncodeOptions) {
+    let items: Vec<Value> = (0..600).map(heterogeneous_item).collect();
+    let inputs = [
+        Value::Array(items.clone()),
+        json!({"data": {"items": items.clone()}}),
+        json!({"groups": [{"items": items.clone(), "id": 1}]}),
+    ];
+    let serial_options = options.clone().with_diagnostics_hook(Arc::new(|_| {}));
+    for input in inputs {
+        let parallel = serde_toon::to_string_with_options(&input, &options).unwrap();
+        let serial = serde_toon::to_string_with_options(&input, &serial_options).unwrap();
+        assert_eq!(parallel, serial);
+
+        let mut streamed = Vec::new();
+        serde_toon::to_writer_streaming(&mut streamed, &input, &options, 512).unwrap();
+        assert_eq!(streamed, serial.as_bytes());
+    }
+}
+
+#[rstest]
+#[case(5, None, true)]
+#[case(5, Some(100), true)]
+#[case(100, Some(100), true)]
+#[case(101, Some(100), false)]
+#[case(5000, Some(100), false)]
+#[case(5000, None, true)]
+fn spec09_inline_array_threshold(
+    #[case] len: usize,
+    #[case] threshold: Option<usize>,
+    #[case] inline: bool,
+) {
+    let input = json!({"values": (0..len).collect::<Vec<_>>(), "tags": ["a", "b"]});
+    let options = EncodeOptions::new().with_inline_array_threshold(threshold);
+    let toon = serde_toon::to_string_with_options(&input, &options).unwrap();
+    let mut lines = toon.lines();
+    if inline {
+        let expected = (0..len)
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        assert_eq!(
+            lines.next(),
+            Some(format!("values[{len}]: {expected}").as_str())
+        );
+    } else {
+        assert_eq!(lines.next(), Some(format!("values[{len}]:").as_str()));
+        assert_eq!(lines.next(), Some("  - 0"));
+        assert_eq!(toon.lines().count(), len + 2);
+    }
+    assert!(toon.ends_with("\ntags[2]: a,b"), "{toon}");
+
+    let decoded: Value = serde_toon::from_str(&toon).unwrap();
+    assert_eq!(decoded, input);
+}
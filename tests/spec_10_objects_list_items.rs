@@ -193,3 +193,26 @@ fn spec10_objects_list_items_validate(#[case] input: &str, #[case] valid: bool)
         assert!(result.is_err());
     }
 }
+
+#[rstest]
+#[case(json!([1, {"a": 2}, [3]]), "[3]:\n  - 1\n  - a: 2\n  - [1]: 3")]
+#[case(
+    json!([{"a": {"b": 1}, "c": 2}, "s", null]),
+    "[3]:\n  - a:\n      b: 1\n    c: 2\n  - s\n  - null"
+)]
+#[case(
+    json!({"x": [{"k": 1, "v": [1, 2]}, "s", [], {}, [[1], [2, 3]], true, [{"k": 1}]]}),
+    "x[7]:\n  - k: 1\n    v[2]: 1,2\n  - s\n  - [0]:\n  -\n  - [2]:\n    - [1]: 1\n    - [2]: 2,3\n  - true\n  - [1]{k}:\n    1"
+)]
+#[case(
+    json!([[{"a": 1}], {"a": {"b": {"c": 1}}}, "-", "- x", -1]),
+    "[5]:\n  - [1]{a}:\n    1\n  - a:\n      b:\n        c: 1\n  - \"-\"\n  - \"- x\"\n  - -1"
+)]
+fn spec10_heterogeneous_arrays_round_trip(#[case] input: Value, #[case] expected: &str) {
+    let toon = serde_toon::to_string(&input).unwrap();
+    assert_eq!(toon, expected);
+    let arena: Value = serde_toon::from_str(&toon).unwrap();
+    assert_eq!(arena, input);
+    assert_eq!(serde_toon::decode_to_value(&toon).unwrap(), input);
+    assert!(serde_toon::validate_str(&toon).is_ok());
+}
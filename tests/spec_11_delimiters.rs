@@ -89,6 +89,7 @@ fn map_encode_options(options: &SpecOptions) -> EncodeOptions {
             ',' => Delimiter::Comma,
             '\t' => Delimiter::Tab,
             '|' => Delimiter::Pipe,
+            ';' => Delimiter::Semicolon,
             _ => Delimiter::Comma,
         };
     }
@@ -113,6 +114,7 @@ fn map_decode_options(options: &SpecOptions) -> DecodeOptions {
     if let Some(ExpandPaths::Safe) = options.expand_paths {
         decoded.expand_paths = ToonExpandPaths::Safe;
     }
+    decoded.semicolon_delimiter = options.delimiter == Some(';');
     decoded
 }
 
@@ -137,6 +139,16 @@ fn map_decode_options(options: &SpecOptions) -> DecodeOptions {
     Some("items[1|]{v}:\n  \"a|b\""),
     SpecOptions::default().with_delimiter('|')
 )]
+#[case(
+    json!({"items": ["a, b", "c;d"]}),
+    Some("items[2;]: a, b;\"c;d\""),
+    SpecOptions::default().with_delimiter(';')
+)]
+#[case(
+    json!({"items": [{"v": "x, y", "n": 1}, {"v": "z;w", "n": 2}]}),
+    Some("items[2;]{v;n}:\n  x, y;1\n  \"z;w\";2"),
+    SpecOptions::default().with_delimiter(';')
+)]
 fn spec11_delimiters_encode(
     #[case] input: Value,
     #[case] expected: Option<&'static str>,
@@ -185,6 +197,18 @@ fn spec11_delimiters_encode(
     Some(json!({"a": "b,c"})),
     SpecOptions::default()
 )]
+#[case(
+    "items[2;]{a;b}:\n  1;x, y\n  3;\"p;q\"",
+    Some(json!({"items": [{"a": 1, "b": "x, y"}, {"a": 3, "b": "p;q"}]})),
+    SpecOptions::default().with_delimiter(';')
+)]
+#[case(
+    "items[2;]: a;b",
+    Some(json!({"items": ["a", "b"]})),
+    SpecOptions::default().with_delimiter(';')
+)]
+#[case("items[2;]: a;b", None, SpecOptions::default())]
+#[case("items[2;]: a;b", None, SpecOptions::default().with_strict(false))]
 fn spec11_delimiters_decode(
     #[case] input: &str,
     #[case] expected: Option<Value>,
@@ -206,6 +230,8 @@ fn spec11_delimiters_decode(
 #[case("items[1|]{a|b}:\n  1|2", true)]
 #[case("items[1|]{a,b}:\n  1,2", false)]
 #[case("items[1|]{a|b}:\n  1,2", false)]
+#[case("[0;]:", false)]
+#[case("items[1;]{a;b}:\n  1;2", false)]
 fn spec11_delimiters_validate(#[case] input: &str, #[case] valid: bool) {
     let result = Spec11Adapter::validate(input);
     if valid {
@@ -214,3 +240,68 @@ fn spec11_delimiters_validate(#[case] input: &str, #[case] valid: bool) {
         assert!(result.is_err());
     }
 }
+
+#[rstest]
+#[case("items[2]: a\\,b,c", json!({"items": ["a,b", "c"]}))]
+#[case("items[1]: a\\,b", json!({"items": ["a,b"]}))]
+#[case("items[2|]: a\\|b|c", json!({"items": ["a|b", "c"]}))]
+#[case("items[2]: \"a\\\\\",b", json!({"items": ["a\\", "b"]}))]
+#[case("items[2]: 1\\,5,2", json!({"items": ["1,5", 2]}))]
+#[case(
+    "rows[2]{a,b}:\n  x\\,y,1\n  z,\"q,r\"",
+    json!({"rows": [{"a": "x,y", "b": 1}, {"a": "z", "b": "q,r"}]})
+)]
+#[case(
+    "rows[1\t]{a\tb}:\n  x\\\ty\t1",
+    json!({"rows": [{"a": "x\ty", "b": 1}]})
+)]
+fn spec11_delimiter_escape(#[case] input: &str, #[case] expected: Value) {
+    let options = DecodeOptions::new().with_delimiter_escape(true);
+    let arena: Value = serde_toon::from_str_with_options(input, &options).unwrap();
+    assert_eq!(arena, expected);
+    assert_eq!(
+        serde_toon::decode_to_value_with_options(input, &options).unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn spec11_delimiter_escape_is_off_by_default() {
+    let decoded: Value = serde_toon::from_str_with_options(
+        "items[3]: a\\,b,c",
+        &DecodeOptions::new().with_strict(false),
+    )
+    .unwrap();
+    assert_eq!(decoded, json!({"items": ["a\\", "b", "c"]}));
+}
+
+#[rstest]
+#[case(Delimiter::Comma)]
+#[case(Delimiter::Tab)]
+#[case(Delimiter::Pipe)]
+#[case(Delimiter::Semicolon)]
+fn spec11_delimiter_variants_round_trip(#[case] delimiter: Delimiter) {
+    let input = json!({
+        "tags": ["a,b", "c|d", "e;f", "g\th", "plain"],
+        "rows": [{"id": 1, "note": "x; y, z"}, {"id": 2, "note": "w|v"}],
+        "nested": [[1, 2], ["a;b", "c"]]
+    });
+    let options = EncodeOptions::new().with_delimiter(delimiter);
+    let toon = serde_toon::to_string_with_options(&input, &options).unwrap();
+    let decode = DecodeOptions::new().with_semicolon_delimiter(delimiter == Delimiter::Semicolon);
+    assert_eq!(
+        serde_toon::from_str_with_options::<Value>(&toon, &decode).unwrap(),
+        input,
+        "{toon}"
+    );
+    assert_eq!(
+        serde_toon::decode_to_value_with_options(&toon, &decode).unwrap(),
+        input
+    );
+    assert!(serde_toon::validate_str_with_options(&toon, &decode).is_ok());
+    if delimiter == Delimiter::Semicolon {
+        assert!(toon.starts_with("tags[5;]: a,b;c|d;\"e;f\";"), "{toon}");
+        assert!(serde_toon::from_str::<Value>(&toon).is_err());
+        assert!(serde_toon::validate_str(&toon).is_err());
+    }
+}
@@ -1,8 +1,8 @@
 use rstest::rstest;
 use serde_json::{json, Value};
 use serde_toon::{
-    DecodeOptions, Delimiter, EncodeOptions, ExpandPaths as ToonExpandPaths, Indent,
-    KeyFolding as ToonKeyFolding,
+    DecodeDiagnostic, DecodeOptions, Delimiter, EncodeOptions, ExpandPaths as ToonExpandPaths,
+    Indent, KeyFolding as ToonKeyFolding,
 };
 
 #[allow(dead_code)]
@@ -183,3 +183,231 @@ fn spec12_indentation_whitespace_validate(#[case] input: &str, #[case] valid: bo
         assert!(result.is_err());
     }
 }
+
+#[test]
+fn spec12_indent_first_level_offsets_every_line() {
+    let input = json!({"name": "Ada", "tags": ["a", "b"], "rows": [{"id": 1}, {"id": 2}], "meta": {"x": 1}});
+    let options = EncodeOptions::new().with_indent_first_level(2);
+    let snippet = serde_toon::to_string_with_options(&input, &options).unwrap();
+    assert_eq!(
+        snippet,
+        "  name: Ada\n  tags[2]: a,b\n  rows[2]{id}:\n    1\n    2\n  meta:\n    x: 1"
+    );
+
+    let embedded = format!("config:\n{snippet}");
+    let decoded: Value = serde_toon::from_str(&embedded).unwrap();
+    assert_eq!(decoded, json!({"config": input}));
+}
+
+#[test]
+fn spec12_indent_warnings_infer_levels() {
+    let input = "a:\n   b: 1\n   c: 2\nd:\n  e:\n     f: 3\n   g: 4";
+    let strict = serde_toon::from_str::<Value>(input).unwrap_err();
+    assert!(strict.to_string().contains("invalid indentation"));
+
+    let options = DecodeOptions::new().with_indent_warnings(true);
+    let (value, diagnostics): (Value, _) =
+        serde_toon::from_str_with_diagnostics(input, &options).unwrap();
+    assert_eq!(
+        value,
+        json!({"a": {"b": 1, "c": 2}, "d": {"e": {"f": 3}, "g": 4}})
+    );
+    assert_eq!(
+        diagnostics,
+        vec![
+            DecodeDiagnostic::IndentMismatch {
+                line: 2,
+                indent: 3,
+                level: 1
+            },
+            DecodeDiagnostic::IndentMismatch {
+                line: 3,
+                indent: 3,
+                level: 1
+            },
+            DecodeDiagnostic::IndentMismatch {
+                line: 6,
+                indent: 5,
+                level: 2
+            },
+            DecodeDiagnostic::IndentMismatch {
+                line: 7,
+                indent: 3,
+                level: 1
+            },
+        ]
+    );
+    assert_eq!(
+        serde_toon::from_str_with_options::<Value>(input, &options).unwrap(),
+        value
+    );
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn spec12_indent_warnings_apply_to_parallel_decode() {
+    let input = "[2]:\n  - a: 1\n     b: 2\n  - a: 3";
+    assert!(serde_toon::from_str_parallel::<Value>(input).is_err());
+    let options = DecodeOptions::new().with_indent_warnings(true);
+    assert_eq!(
+        serde_toon::from_str_parallel_with_options::<Value>(input, &options).unwrap(),
+        vec![json!({"a": 1, "b": 2}), json!({"a": 3})]
+    );
+}
+
+#[test]
+fn spec12_indent_warnings_silent_for_conforming_input() {
+    let options = DecodeOptions::new().with_indent_warnings(true);
+    let (value, diagnostics): (Value, _) =
+        serde_toon::from_str_with_diagnostics("a:\n  b: 1", &options).unwrap();
+    assert_eq!(value, json!({"a": {"b": 1}}));
+    assert!(diagnostics.is_empty());
+}
+
+#[rstest]
+fn spec12_section_spacing_separates_top_level_containers(#[values(true, false)] strict: bool) {
+    let value = json!({
+        "name": "app",
+        "version": 1,
+        "server": {"host": "localhost", "port": 8080},
+        "tags": ["a", "b"],
+        "debug": true,
+        "users": [{"id": 1}, {"id": 2}]
+    });
+    let toon = serde_toon::to_string_pretty(&value).unwrap();
+    assert_eq!(
+        toon,
+        "name: app\nversion: 1\n\nserver:\n  host: localhost\n  port: 8080\n\ntags[2]: a,b\ndebug: true\n\nusers[2]{id}:\n  1\n  2"
+    );
+    let options = DecodeOptions::new().with_strict(strict);
+    assert_eq!(
+        serde_toon::from_str_with_options::<Value>(&toon, &options).unwrap(),
+        value
+    );
+    assert!(serde_toon::validate_str_with_options(&toon, &options).is_ok());
+}
+
+#[test]
+fn spec12_section_spacing_skips_leading_blank_and_nested_keys() {
+    let options = EncodeOptions::new().with_section_spacing(true);
+    let toon =
+        serde_toon::to_string_with_options(&json!({"a": {"b": {"c": 1}}}), &options).unwrap();
+    assert_eq!(toon, "a:\n  b:\n    c: 1");
+}
+
+#[rstest]
+#[case(false, false)]
+#[case(true, false)]
+#[case(false, true)]
+#[case(true, true)]
+fn spec12_trailing_newline_only_when_enabled(#[case] enabled: bool, #[case] checksum: bool) {
+    let value = json!({"name": "app", "users": [{"id": 1}, {"id": 2}], "tags": ["a", "b"]});
+    let options = EncodeOptions::new()
+        .with_trailing_newline(enabled)
+        .with_checksum(checksum);
+    let toon = serde_toon::to_string_with_options(&value, &options).unwrap();
+    let bytes = serde_toon::to_vec_with_options(&value, &options).unwrap();
+    let mut written = Vec::new();
+    serde_toon::to_writer_with_options(&mut written, &value, &options).unwrap();
+    for output in [toon.as_bytes(), &bytes, &written] {
+        assert_eq!(output.last() == Some(&b'\n'), enabled);
+        assert!(!output.ends_with(b"\n\n"));
+        assert_eq!(output, toon.as_bytes());
+    }
+
+    for strict in [true, false] {
+        let options = DecodeOptions::new()
+            .with_strict(strict)
+            .with_verify_checksum(checksum);
+        assert_eq!(
+            serde_toon::from_str_with_options::<Value>(&toon, &options).unwrap(),
+            value
+        );
+        assert_eq!(
+            serde_toon::decode_to_value_with_options(&toon, &options).unwrap(),
+            value
+        );
+        // Validation stays canonical and only passes once the newline is gone.
+        let validated = serde_toon::validate_str_with_options(&toon, &options);
+        assert_eq!(validated.is_err(), enabled, "{validated:?}");
+        let streamed = serde_toon::validate_reader_with_options(toon.as_bytes(), &options);
+        assert_eq!(streamed.is_err(), enabled, "{streamed:?}");
+        if enabled {
+            let err = validated.unwrap_err();
+            assert!(
+                err.to_string().contains("trailing newline not allowed"),
+                "{err}"
+            );
+            let trimmed = toon.strip_suffix('\n').unwrap();
+            assert!(serde_toon::validate_str_with_options(trimmed, &options).is_ok());
+        }
+    }
+}
+
+#[rstest]
+fn spec12_decode_errors_report_line_and_column(#[values(true, false)] strict: bool) {
+    let options = DecodeOptions::new().with_strict(strict);
+    let err = serde_toon::decode_to_value_with_options("a: 1\nbad indent here\n   x: 2", &options)
+        .unwrap_err();
+    let location = err.location.expect("decode error without a location");
+    assert_eq!(location.line, 3);
+    assert!(
+        err.to_string()
+            .ends_with(&format!(" at line 3, column {}", location.column)),
+        "{err}"
+    );
+}
+
+#[test]
+fn spec12_array_length_errors_point_at_the_header() {
+    let err = serde_toon::decode_to_value("a:\n  b: 1\nitems[3]:\n  - x\n  - y").unwrap_err();
+    assert_eq!(err.message, "array length mismatch");
+    assert_eq!(err.location.map(|at| (at.line, at.column)), Some((3, 1)));
+}
+
+#[test]
+fn spec12_tab_indentation_round_trips_nested_objects() {
+    let input = json!({
+        "server": {"host": "localhost", "tls": {"enabled": true, "ports": [443, 8443]}},
+        "users": [{"id": 1, "name": "Ada"}, {"id": 2, "name": "Bob"}],
+        "jobs": [{"name": "build", "steps": ["fetch", "compile"]}, "done"],
+    });
+    let toon =
+        serde_toon::to_string_with_options(&input, &EncodeOptions::new().with_indent(Indent::Tabs))
+            .unwrap();
+    assert_eq!(
+        toon,
+        "server:\n\thost: localhost\n\ttls:\n\t\tenabled: true\n\t\tports[2]: 443,8443\n\
+         users[2]{id,name}:\n\t1,Ada\n\t2,Bob\n\
+         jobs[2]:\n\t- name: build\n\t\tsteps[2]: fetch,compile\n\t- done"
+    );
+
+    let options = DecodeOptions::new().with_indent(Indent::Tabs);
+    let decoded: Value = serde_toon::from_str_with_options(&toon, &options).unwrap();
+    assert_eq!(decoded, input);
+    let decoded = serde_toon::decode_to_value_with_options(&toon, &options).unwrap();
+    assert_eq!(decoded, input);
+    serde_toon::validate_str_with_options(&toon, &options).unwrap();
+}
+
+#[rstest]
+fn spec12_strict_rejects_the_other_indent_character(#[values(true, false)] tabs: bool) {
+    let (input, indent, message) = if tabs {
+        (
+            "a:\n  b: 1",
+            Indent::Tabs,
+            "spaces not allowed in tab indentation",
+        )
+    } else {
+        (
+            "a:\n\tb: 1",
+            Indent::Spaces(2),
+            "tabs not allowed in indentation",
+        )
+    };
+    let strict = DecodeOptions::new().with_indent(indent);
+    let err = serde_toon::from_str_with_options::<Value>(input, &strict).unwrap_err();
+    assert!(err.to_string().contains(message), "{err}");
+    let err = serde_toon::decode_to_value_with_options(input, &strict).unwrap_err();
+    assert!(err.to_string().contains(message), "{err}");
+}
@@ -259,3 +259,395 @@ fn spec13_validator_conformance(#[case] input: &str, #[case] valid: bool) {
         assert!(result.is_err());
     }
 }
+
+#[rstest]
+#[case("/", "a/b/c: 1\nx:\n  y: true\n  z: 2")]
+#[case("__", "a__b__c: 1\nx:\n  y: true\n  z: 2")]
+#[case(".", "a.b.c: 1\nx:\n  y: true\n  z: 2")]
+fn spec13_flatten_separator_round_trip(#[case] separator: &str, #[case] expected: &str) {
+    let input = json!({"a": {"b": {"c": 1}}, "x": {"y": true, "z": 2}});
+    let encode = EncodeOptions::new()
+        .with_key_folding(ToonKeyFolding::Safe)
+        .with_flatten_separator(separator);
+    let toon = serde_toon::to_string_with_options(&input, &encode).unwrap();
+    assert_eq!(toon, expected);
+
+    let decode = DecodeOptions::new()
+        .with_expand_paths(ToonExpandPaths::Safe)
+        .with_expand_separator(separator);
+    let decoded: Value = serde_toon::from_str_with_options(&toon, &decode).unwrap();
+    assert_eq!(decoded, input);
+}
+
+#[test]
+fn spec13_flatten_separator_only_leaves_folded_keys_unquoted() {
+    let input = json!({"a": {"b": [1, 2]}, "p/q": [{"r/s": 1}], "t": {"u/v": 2}});
+    let encode = EncodeOptions::new()
+        .with_key_folding(ToonKeyFolding::Safe)
+        .with_flatten_separator("/");
+    let toon = serde_toon::to_string_with_options(&input, &encode).unwrap();
+    assert_eq!(
+        toon,
+        "a/b[2]: 1,2\n\"p/q\"[1]{\"r/s\"}:\n  1\nt:\n  \"u/v\": 2"
+    );
+}
+
+#[rstest]
+#[case("")]
+#[case(": ")]
+#[case(",")]
+fn spec13_flatten_separator_rejects_structural(
+    #[case] separator: &str,
+    #[values(ToonKeyFolding::Safe, ToonKeyFolding::Aggressive)] folding: ToonKeyFolding,
+) {
+    let encode = EncodeOptions::new()
+        .with_key_folding(folding)
+        .with_flatten_separator(separator);
+    let err = serde_toon::to_string_with_options(&json!({"a": {"b": 1}}), &encode).unwrap_err();
+    assert_eq!(err.message, "invalid flatten separator");
+
+    let decode = DecodeOptions::new()
+        .with_expand_paths(ToonExpandPaths::Safe)
+        .with_expand_separator(separator);
+    assert!(serde_toon::from_str_with_options::<Value>("a: 1", &decode).is_err());
+}
+
+#[rstest]
+#[case(
+    json!({"env": [{"key": "a", "value": 1}, {"key": "b", "value": {"x": true}}]}),
+    "env:\n  a: 1\n  b:\n    x: true"
+)]
+#[case(json!([{"key": "a", "value": [1, 2]}]), "a[2]: 1,2")]
+#[case(
+    json!({"env": [{"key": "a", "value": 1}, {"key": "a", "value": 2}]}),
+    "env[2]{key,value}:\n  a,1\n  a,2"
+)]
+#[case(
+    json!({"env": [{"key": "a", "value": 1}, {"key": 2, "value": 2}]}),
+    "env[2]{key,value}:\n  a,1\n  2,2"
+)]
+#[case(
+    json!({"env": [{"key": "a", "value": 1, "extra": 0}]}),
+    "env[1]{key,value,extra}:\n  a,1,0"
+)]
+#[case(json!({"env": []}), "env[0]:")]
+fn spec13_kv_array_collapse(#[case] input: Value, #[case] expected: &str) {
+    let options =
+        EncodeOptions::new().with_kv_array_collapse(Some(("key".to_string(), "value".to_string())));
+    let toon = serde_toon::to_string_with_options(&input, &options).unwrap();
+    assert_eq!(toon, expected);
+}
+
+#[test]
+fn spec13_kv_array_collapse_custom_fields() {
+    let input = json!({"tags": [{"name": "env", "val": "prod"}, {"name": "tier", "val": 2}]});
+    let options =
+        EncodeOptions::new().with_kv_array_collapse(Some(("name".to_string(), "val".to_string())));
+    let toon = serde_toon::to_string_with_options(&input, &options).unwrap();
+    assert_eq!(toon, "tags:\n  env: prod\n  tier: 2");
+}
+
+fn redact_passwords(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, item) in map.iter_mut() {
+                if key == "password" {
+                    *item = Value::String("***".to_string());
+                } else {
+                    redact_passwords(item);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_passwords),
+        _ => {}
+    }
+}
+
+#[test]
+fn spec13_transform_redacts_before_encoding() {
+    let input = json!({
+        "user": {"name": "ada", "password": "hunter2"},
+        "accounts": [{"id": 1, "password": "a"}, {"id": 2, "password": "b"}]
+    });
+    let options = EncodeOptions::new().with_transform(redact_passwords);
+    let toon = serde_toon::to_string_with_options(&input, &options).unwrap();
+    assert_eq!(
+        toon,
+        "user:\n  name: ada\n  password: ***\naccounts[2]{id,password}:\n  1,***\n  2,***"
+    );
+    assert_eq!(input["user"]["password"], "hunter2");
+
+    let from_json =
+        serde_toon::to_string_from_json_str_with_options(r#"{"password":"x","n":1}"#, &options)
+            .unwrap();
+    assert_eq!(from_json, "password: ***\nn: 1");
+}
+
+#[rstest]
+#[case(json!({"a": {"b": {"c": {"d": {"e": 1}}}}}), "a.b.c.d.e: 1")]
+#[case(json!({"a": {"b": {"c": 1, "d": 2}}}), "a:\n  b:\n    c: 1\n    d: 2")]
+#[case(json!({"a": {"x": {"y": 1}, "z": 2}}), "a:\n  x.y: 1\n  z: 2")]
+#[case(json!({"a": {"b": [1, 2]}, "c": {"d": {}}}), "a.b[2]: 1,2\nc.d:")]
+#[case(json!({"a": {"b-c": 1}}), "a:\n  \"b-c\": 1")]
+#[case(json!({"a.b": 1, "a": {"b": 2}}), "a.b: 1\na:\n  b: 2")]
+fn spec13_collapse_single_chains(#[case] input: Value, #[case] expected: &str) {
+    let options = EncodeOptions::new().with_collapse_single_chains(true);
+    let toon = serde_toon::to_string_with_options(&input, &options).unwrap();
+    assert_eq!(toon, expected);
+    let expand = DecodeOptions::new().with_expand_paths(ToonExpandPaths::Safe);
+    if !input.as_object().unwrap().contains_key("a.b") {
+        let decoded: Value = serde_toon::from_str_with_options(&toon, &expand).unwrap();
+        assert_eq!(decoded, input);
+    }
+}
+
+#[test]
+fn spec13_collapse_single_chains_differs_from_key_folding() {
+    let input = json!({"a": {"b": {"c": 1, "d": 2}}});
+    let folding = EncodeOptions::new().with_key_folding(ToonKeyFolding::Safe);
+    assert_eq!(
+        serde_toon::to_string_with_options(&input, &folding).unwrap(),
+        "a.b:\n  c: 1\n  d: 2"
+    );
+
+    let deep = json!({"a": {"b": {"c": {"d": {"e": 1}}}}});
+    let shallow = folding.clone().with_flatten_depth(Some(2));
+    assert_eq!(
+        serde_toon::to_string_with_options(&deep, &shallow).unwrap(),
+        "a.b:\n  c:\n    d:\n      e: 1"
+    );
+    let both = shallow.with_collapse_single_chains(true);
+    assert_eq!(
+        serde_toon::to_string_with_options(&deep, &both).unwrap(),
+        "a.b:\n  c.d.e: 1"
+    );
+}
+
+#[rstest]
+#[case(json!({"tags": ["b", "a", "c"]}), "tags[3]: a,b,c")]
+#[case(json!([10, 9, 100, -1.5]), "[4]: -1.5,9,10,100")]
+#[case(json!(["x", 1, null, true, false]), "[5]: null,false,true,1,x")]
+#[case(
+    json!({"rows": [{"ids": [3, 1]}, {"ids": [2]}]}),
+    "rows[2]:\n  - ids[2]: 1,3\n  - ids[1]: 2"
+)]
+#[case(json!({"mixed": [{"a": 2}, {"a": 1}]}), "mixed[2]{a}:\n  2\n  1")]
+fn spec13_sort_arrays(#[case] input: Value, #[case] expected: &str) {
+    let options = EncodeOptions::new().with_sort_arrays(true);
+    assert_eq!(
+        serde_toon::to_string_with_options(&input, &options).unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn spec13_sort_arrays_makes_hash_sets_deterministic() {
+    let options = EncodeOptions::new().with_sort_arrays(true);
+    let words = [
+        "delta", "alpha", "echo", "charlie", "bravo", "golf", "foxtrot",
+    ];
+    let encoded: Vec<String> = (0..8)
+        .map(|_| {
+            let set: std::collections::HashSet<&str> = words.iter().copied().collect();
+            serde_toon::to_string_with_options(&json!({ "set": set }), &options).unwrap()
+        })
+        .collect();
+    assert!(encoded.iter().all(|toon| toon == &encoded[0]));
+    assert_eq!(
+        encoded[0],
+        "set[7]: alpha,bravo,charlie,delta,echo,foxtrot,golf"
+    );
+}
+
+#[rstest]
+#[case(json!({"a": 1, "b": null, "c": {"d": null, "e": 2}}), "a: 1\nc:\n  e: 2")]
+#[case(json!({"only": null}), "")]
+#[case(json!({"list": [{"a": 1, "b": null}, {"a": null, "b": [2]}]}), "list[2]:\n  - a: 1\n  - b[1]: 2")]
+#[case(json!({"rows": [{"a": 1, "b": null}, {"a": null, "b": 2}]}), "rows[2]{a,b}:\n  1,null\n  null,2")]
+#[case(json!({"items": [null, 1]}), "items[2]: null,1")]
+fn spec13_sparse_objects_omit_null_fields(#[case] input: Value, #[case] expected: &str) {
+    let options = EncodeOptions::new().with_sparse_objects(true);
+    assert_eq!(
+        serde_toon::to_string_with_options(&input, &options).unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn spec13_sparse_objects_shrink_mostly_null_records() {
+    let record: serde_json::Map<String, Value> = (0..50)
+        .map(|idx| {
+            let value = if idx % 10 == 0 {
+                json!(idx)
+            } else {
+                Value::Null
+            };
+            (format!("field_{idx:02}"), value)
+        })
+        .collect();
+    let record = Value::Object(record);
+    let dense = serde_toon::to_string(&record).unwrap();
+    let sparse = serde_toon::to_string_with_options(
+        &record,
+        &EncodeOptions::new().with_sparse_objects(true),
+    )
+    .unwrap();
+    assert_eq!(sparse.lines().count(), 5);
+    let (dense, sparse) = (
+        serde_toon::estimate_tokens(&dense),
+        serde_toon::estimate_tokens(&sparse),
+    );
+    assert!(sparse * 5 < dense, "{sparse} vs {dense}");
+}
+
+#[test]
+fn spec13_options_round_trip_through_serde_json() {
+    let encode = EncodeOptions::new()
+        .with_delimiter(Delimiter::Pipe)
+        .with_indent(Indent::Spaces(4))
+        .with_key_folding(ToonKeyFolding::Safe);
+    let json = serde_json::to_value(&encode).unwrap();
+    assert_eq!(json["delimiter"], json!("|"));
+    assert_eq!(json["indent"], json!(4));
+    assert_eq!(json["key_folding"], json!("safe"));
+    let restored: EncodeOptions = serde_json::from_value(json).unwrap();
+    assert_eq!(format!("{restored:?}"), format!("{encode:?}"));
+
+    let decode = DecodeOptions::new()
+        .with_strict(false)
+        .with_expand_paths(ToonExpandPaths::Safe);
+    let text = serde_json::to_string(&decode).unwrap();
+    let restored: DecodeOptions = serde_json::from_str(&text).unwrap();
+    assert_eq!(format!("{restored:?}"), format!("{decode:?}"));
+}
+
+#[test]
+fn spec13_partial_options_fill_defaults() {
+    let options: EncodeOptions =
+        serde_json::from_value(json!({"delimiter": "\t", "indent": 3})).unwrap();
+    assert_eq!(options.delimiter, Delimiter::Tab);
+    assert_eq!(options.indent, Indent::Spaces(3));
+    assert_eq!(options.key_folding, ToonKeyFolding::Off);
+
+    let err = serde_json::from_value::<EncodeOptions>(json!({"delimiter": "/"})).unwrap_err();
+    assert!(err.to_string().contains("invalid value"), "{err}");
+
+    let options: DecodeOptions = serde_json::from_value(json!({"indent": "tabs"})).unwrap();
+    assert_eq!(options.indent, Indent::Tabs);
+    let json = serde_json::to_value(EncodeOptions::new().with_indent(Indent::Tabs)).unwrap();
+    assert_eq!(json["indent"], json!("tabs"));
+    let err = serde_json::from_value::<EncodeOptions>(json!({"indent": "tab"})).unwrap_err();
+    assert!(err.to_string().contains("invalid value"), "{err}");
+}
+
+#[test]
+fn spec13_cache_limits_do_not_change_output() {
+    let rows: Vec<Value> = (0..300)
+        .map(|idx| json!({"id": idx % 7, "name": format!("n{}", idx % 11), "tag": "x y"}))
+        .collect();
+    let notes: serde_json::Map<String, Value> = (0..50)
+        .map(|idx| (format!("k{idx}"), json!(format!("v{}", idx % 5))))
+        .collect();
+    let input = json!({"rows": rows, "notes": notes, "pipe": [{"a": "p|q"}, {"a": "r"}]});
+    let expected = serde_toon::to_string(&input).unwrap();
+    for limits in [
+        serde_toon::CacheLimits::uniform(0),
+        serde_toon::CacheLimits::uniform(1),
+        serde_toon::CacheLimits::uniform(100_000),
+        serde_toon::CacheLimits::default(),
+    ] {
+        let options = EncodeOptions::new().with_cache_limits(limits);
+        assert_eq!(
+            serde_toon::to_string_with_options(&input, &options).unwrap(),
+            expected,
+            "{limits:?}"
+        );
+    }
+}
+
+#[rstest]
+#[case(json!({"a": {"b": {"c": 1}}}))]
+#[case(json!({"a": {"b": {"c": {"d": [1, 2]}}}, "e": {"f": {"g": 1, "h": 2}}}))]
+#[case(json!({"a": {"b": 1}, "c": {"x": {"y": {}}}}))]
+#[case(json!({"rows": [{"a": 1, "c": {"d": {"e": 2}}}]}))]
+#[case(json!({"list": [{"x": 1}, {"k": {"m": {"n": 1}}}, 3]}))]
+fn spec13_key_folding_is_inverted_by_path_expansion(
+    #[case] input: Value,
+    #[values(None, Some(2))] depth: Option<usize>,
+    #[values(".", "/")] separator: &str,
+) {
+    let options = EncodeOptions::new()
+        .with_key_folding(ToonKeyFolding::Safe)
+        .with_flatten_depth(depth)
+        .with_flatten_separator(separator);
+    let toon = serde_toon::to_string_with_options(&input, &options).unwrap();
+    let expand = DecodeOptions::new()
+        .with_expand_paths(ToonExpandPaths::Safe)
+        .with_expand_separator(separator);
+    let decoded: Value = serde_toon::from_str_with_options(&toon, &expand).unwrap();
+    assert_eq!(decoded, input, "{toon}");
+    assert_eq!(
+        serde_toon::decode_to_value_with_options(&toon, &expand).unwrap(),
+        input
+    );
+    assert!(serde_toon::validate_str_with_options(&toon, &expand).is_ok());
+}
+
+#[rstest]
+#[case(json!({"a b": {"c": 1}}), "\"a b\".c: 1", json!({"a b.c": 1}))]
+#[case(
+    json!({"x": {"a-b": {"c.d": [1, 2]}}, "y": 2}),
+    "x.\"a-b\".\"c.d\"[2]: 1,2\ny: 2",
+    json!({"x.a-b.c.d": [1, 2], "y": 2})
+)]
+#[case(
+    json!({"id": {"say \"hi\"": {"k": {"v": true, "w": false}}}}),
+    "id.\"say \\\"hi\\\"\".k:\n  v: true\n  w: false",
+    json!({"id.say \"hi\".k": {"v": true, "w": false}})
+)]
+#[case(json!({"a": {"b": 1}}), "a.b: 1", json!({"a.b": 1}))]
+fn spec13_aggressive_key_folding_quotes_segments(
+    #[case] input: Value,
+    #[case] expected: &str,
+    #[case] literal: Value,
+) {
+    let options = EncodeOptions::new().with_key_folding(ToonKeyFolding::Aggressive);
+    let toon = serde_toon::to_string_with_options(&input, &options).unwrap();
+    assert_eq!(toon, expected);
+
+    for strict in [true, false] {
+        let expand = DecodeOptions::new()
+            .with_strict(strict)
+            .with_expand_paths(ToonExpandPaths::Safe);
+        let decoded: Value = serde_toon::from_str_with_options(&toon, &expand).unwrap();
+        assert_eq!(decoded, input);
+        serde_toon::validate_str_with_options(&toon, &expand).unwrap();
+
+        let plain = DecodeOptions::new().with_strict(strict);
+        let decoded: Value = serde_toon::from_str_with_options(&toon, &plain).unwrap();
+        assert_eq!(decoded, literal);
+        let decoded = serde_toon::decode_to_value_with_options(&toon, &plain).unwrap();
+        assert_eq!(decoded, literal);
+    }
+
+    // Safe folding writes literal dotted keys as they are, so path expansion
+    // only restores inputs without one.
+    if input.to_string().contains('.') {
+        return;
+    }
+    let safe = EncodeOptions::new().with_key_folding(ToonKeyFolding::Safe);
+    let toon = serde_toon::to_string_with_options(&input, &safe).unwrap();
+    let expand = DecodeOptions::new().with_expand_paths(ToonExpandPaths::Safe);
+    let decoded: Value = serde_toon::from_str_with_options(&toon, &expand).unwrap();
+    assert_eq!(decoded, input);
+}
+
+#[rstest]
+#[case("\"a\" .b: 1")]
+#[case("\"a\".: 1")]
+#[case("\"a\".b c: 1")]
+fn spec13_malformed_quoted_path_keys_are_rejected(#[case] input: &str) {
+    let expand = DecodeOptions::new().with_expand_paths(ToonExpandPaths::Safe);
+    assert!(serde_toon::from_str_with_options::<Value>(input, &expand).is_err());
+    assert!(serde_toon::from_str::<Value>(input).is_err());
+}
@@ -150,6 +150,15 @@ fn spec14_strict_mode_encode(
     None,
     SpecOptions::default().with_expand_paths_safe().with_strict(true)
 )]
+#[case("a: 1\na: 2", None, SpecOptions::default().with_strict(true))]
+#[case("a:\n  b: 1\n  b: 2", None, SpecOptions::default().with_strict(true))]
+#[case("items[1]{id,id}:\n  1,2", None, SpecOptions::default().with_strict(true))]
+#[case("a: 1\na: 2", Some(json!({"a": 2})), SpecOptions::default().with_strict(false))]
+#[case(
+    "items[1]{id,id}:\n  1,2",
+    Some(json!({"items": [{"id": 2}]})),
+    SpecOptions::default().with_strict(false)
+)]
 fn spec14_strict_mode_decode(
     #[case] input: &str,
     #[case] expected: Option<Value>,
@@ -175,6 +184,9 @@ fn spec14_strict_mode_decode(
 #[case("value: \"\\q\"", false)]
 #[case("a:\n\tb: 1", false)]
 #[case("items[2]:\n  - 1\n\n  - 2", false)]
+#[case("a: 1\na: 2", false)]
+#[case("items[1]{id,id}:\n  1,2", false)]
+#[case("a:\n  b: 1\nb: 2", true)]
 fn spec14_strict_mode_validate(#[case] input: &str, #[case] valid: bool) {
     let result = Spec14Adapter::validate(input);
     if valid {
@@ -183,3 +195,73 @@ fn spec14_strict_mode_validate(#[case] input: &str, #[case] valid: bool) {
         assert!(result.is_err());
     }
 }
+
+#[rstest]
+#[case("name: \"Ad", true)]
+#[case("items[3]: a,b", true)]
+#[case("items[3]:\n  - a\n  - b", true)]
+#[case("rows[3]{id,name}:\n  1,a\n  2,b", true)]
+#[case("rows[1]{id,name}:\n  1,\"a", true)]
+#[case("items[1]: a,b", false)]
+#[case("items[2]:\n  - a\n  - b\n  - c", false)]
+#[case("a: 1\n   b: 2", false)]
+#[case("items[x]: a", false)]
+fn spec14_decode_errors_are_classified(#[case] input: &str, #[case] recoverable: bool) {
+    let tree = serde_toon::from_str::<Value>(input).unwrap_err();
+    assert_eq!(tree.is_recoverable(), recoverable, "{input}: {tree}");
+    let arena = serde_toon::decode_to_value(input).unwrap_err();
+    assert_eq!(arena.is_recoverable(), recoverable, "{input}: {arena}");
+}
+
+#[rstest]
+#[case("a: 1\na: 2")]
+#[case("items[1]{id,id}:\n  1,2")]
+#[case("items[1]:\n  - a: 1\n    a: 2")]
+#[case("a: 1\nb: 1\nc: 1\nd: 1\ne: 1\nf: 1\ng: 1\nh: 1\ni: 1\nh: 2")]
+fn spec14_duplicate_keys_error_in_both_decoders(#[case] input: &str) {
+    let tree = serde_toon::from_str::<Value>(input).unwrap_err();
+    assert!(
+        tree.to_string().contains("duplicate key"),
+        "{input}: {tree}"
+    );
+    let arena = serde_toon::decode_to_value(input).unwrap_err();
+    assert!(
+        arena.to_string().contains("duplicate key"),
+        "{input}: {arena}"
+    );
+}
+
+#[test]
+fn spec14_non_strict_duplicate_keys_keep_the_last_value() {
+    let options = DecodeOptions::new().with_strict(false);
+    let input = "a: 1\nb: 1\nc: 1\nd: 1\ne: 1\nf: 1\ng: 1\nh: 1\ni: 1\na: 2\ni: 2";
+    let value: Value = serde_toon::from_str_with_options(input, &options).unwrap();
+    assert_eq!(
+        value,
+        json!({"a": 2, "b": 1, "c": 1, "d": 1, "e": 1, "f": 1, "g": 1, "h": 1, "i": 2})
+    );
+}
+
+#[rstest]
+fn spec14_intern_threshold_keeps_values_and_duplicate_checks(
+    #[values(None, Some(0), Some(1), Some(100))] threshold: Option<usize>,
+) {
+    let options = DecodeOptions::new().with_intern_threshold(threshold);
+    let input = "items[3]:\n  - id: 1\n    name: a\n  - id: 2\n    name: b\n  - id: 3\n    name: a\nrows[2]{id,name}:\n  4,c\n  5,d\nid: 9";
+    let value: Value = serde_toon::from_str_with_options(input, &options).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "items": [{"id": 1, "name": "a"}, {"id": 2, "name": "b"}, {"id": 3, "name": "a"}],
+            "rows": [{"id": 4, "name": "c"}, {"id": 5, "name": "d"}],
+            "id": 9,
+        })
+    );
+
+    let input = "items[2]:\n  - id: 1\n    name: a\n  - id: 2\n    id: 3";
+    let err = serde_toon::from_str_with_options::<Value>(input, &options).unwrap_err();
+    assert!(err.to_string().contains("duplicate key"), "{err}");
+    let value: Value =
+        serde_toon::from_str_with_options(input, &options.clone().with_strict(false)).unwrap();
+    assert_eq!(value, json!({"items": [{"id": 1, "name": "a"}, {"id": 3}]}));
+}
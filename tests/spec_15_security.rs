@@ -1,8 +1,11 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
 use rstest::rstest;
 use serde_json::{json, Value};
 use serde_toon::{
     DecodeOptions, Delimiter, EncodeOptions, ExpandPaths as ToonExpandPaths, Indent,
-    KeyFolding as ToonKeyFolding,
+    KeyFolding as ToonKeyFolding, LineLimitPolicy,
 };
 
 #[allow(dead_code)]
@@ -171,3 +174,290 @@ fn spec15_security_validate(#[case] input: &str, #[case] valid: bool) {
         assert!(result.is_err());
     }
 }
+
+#[rstest]
+#[case(
+    "items[999999999]:\n  - a",
+    "array \"items\" has 999999999 items, exceeding max_array_len 1000"
+)]
+#[case(
+    "[5000]{id}:\n  1",
+    "root array has 5000 items, exceeding max_array_len 1000"
+)]
+#[case(
+    "outer:\n  inner[1001]: a",
+    "array \"inner\" has 1001 items, exceeding max_array_len 1000"
+)]
+fn spec15_max_array_len_rejects_declared_huge_arrays(#[case] input: &str, #[case] message: &str) {
+    let options = DecodeOptions::new().with_max_array_len(1000);
+    let tree = serde_toon::from_str_with_options::<Value>(input, &options).unwrap_err();
+    assert_eq!(tree.to_string(), message);
+    let arena = serde_toon::decode_to_value_with_options(input, &options).unwrap_err();
+    assert_eq!(arena.message, message);
+    assert!(serde_toon::validate_str_with_options(input, &options).is_err());
+}
+
+#[test]
+fn spec15_max_array_len_counts_items_in_non_strict_mode() {
+    let options = DecodeOptions::new()
+        .with_strict(false)
+        .with_max_array_len(2);
+    let err = serde_toon::decode_to_value_with_options("items[1]: a,b,c", &options).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "array \"items\" has 3 items, exceeding max_array_len 2"
+    );
+    assert_eq!(
+        serde_toon::decode_to_value_with_options("items[2]: a,b", &options).unwrap(),
+        json!({"items": ["a", "b"]})
+    );
+}
+
+// Long enough to blow any small limit, followed by a field whose non-tabular
+// array reports a diagnostic only if the encoder ever gets that far.
+fn oversized_document() -> Value {
+    let items: Vec<Value> = (0..10_000).map(|idx| json!([idx, "a"])).collect();
+    let rows: Vec<Value> = (0..10_000).map(|idx| json!({"id": idx})).collect();
+    json!({"items": items, "rows": rows, "late": [{"id": 1}, 2]})
+}
+
+#[rstest]
+#[case(LineLimitPolicy::Error)]
+#[case(LineLimitPolicy::Truncate)]
+fn spec15_line_limit_stops_encoding_early(#[case] policy: LineLimitPolicy) {
+    let input = oversized_document();
+    let full = serde_toon::to_string(&input).unwrap();
+    assert!(full.len() > 100_000);
+
+    let late_diagnostics = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&late_diagnostics);
+    let options = EncodeOptions::new()
+        .with_line_limit(1024, policy)
+        .with_diagnostics_hook(Arc::new(move |_| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }));
+    let result = serde_toon::to_string_with_options(&input, &options);
+    assert_eq!(late_diagnostics.load(Ordering::Relaxed), 0);
+    match policy {
+        LineLimitPolicy::Error => {
+            let err = result.unwrap_err();
+            assert!(err.to_string().contains("line limit of 1024 bytes"));
+        }
+        LineLimitPolicy::Truncate => {
+            let toon = result.unwrap();
+            assert!(toon.len() <= 1024 && toon.len() > 900, "{}", toon.len());
+            assert!(full.starts_with(toon.strip_suffix("\n# truncated").unwrap()));
+        }
+    }
+}
+
+#[rstest]
+#[case(json!({"rows": (0..500).map(|idx| json!({"id": idx})).collect::<Vec<_>>()}))]
+#[case(json!({"a": "x".repeat(400), "b": 1}))]
+#[case(json!("x".repeat(400)))]
+fn spec15_line_limit_applies_to_tabular_and_long_lines(#[case] input: Value) {
+    let options = EncodeOptions::new().with_line_limit(200, LineLimitPolicy::Error);
+    assert!(serde_toon::to_string_with_options(&input, &options).is_err());
+
+    let options = EncodeOptions::new().with_line_limit(200, LineLimitPolicy::Truncate);
+    let toon = serde_toon::to_string_with_options(&input, &options).unwrap();
+    assert!(toon.len() <= 200);
+    assert!(toon.ends_with("# truncated"));
+}
+
+#[test]
+fn spec15_line_limit_leaves_small_output_alone() {
+    let input = json!({"a": 1, "b": [1, 2]});
+    let toon = serde_toon::to_string(&input).unwrap();
+    for policy in [LineLimitPolicy::Error, LineLimitPolicy::Truncate] {
+        let options = EncodeOptions::new().with_line_limit(toon.len(), policy);
+        assert_eq!(
+            serde_toon::to_string_with_options(&input, &options).unwrap(),
+            toon
+        );
+    }
+}
+
+#[test]
+fn spec15_line_limit_keeps_a_line_ending_at_the_budget() {
+    let input = json!({"alpha": 1, "beta": 2, "gamma": 3});
+    let options = EncodeOptions::new().with_line_limit(20, LineLimitPolicy::Truncate);
+    let toon = serde_toon::to_string_with_options(&input, &options).unwrap();
+    assert_eq!(toon, "alpha: 1\n# truncated");
+    assert_eq!(toon.len(), 20);
+}
+
+#[test]
+fn spec15_line_limit_too_small_for_the_marker_fails() {
+    let input = json!({"alpha": 1, "beta": 2});
+    let options = EncodeOptions::new().with_line_limit(10, LineLimitPolicy::Truncate);
+    let err = serde_toon::to_string_with_options(&input, &options).unwrap_err();
+    assert!(err.to_string().contains("line limit of 10 bytes"), "{err}");
+    let options = EncodeOptions::new().with_line_limit(11, LineLimitPolicy::Truncate);
+    assert_eq!(
+        serde_toon::to_string_with_options(&input, &options).unwrap(),
+        "# truncated"
+    );
+}
+
+#[rstest]
+#[case(EncodeOptions::new().with_checksum(true))]
+#[case(EncodeOptions::new().with_trailing_newline(true))]
+#[case(EncodeOptions::new().with_checksum(true).with_trailing_newline(true))]
+fn spec15_line_limit_counts_checksum_and_trailing_newline(#[case] options: EncodeOptions) {
+    let input = json!({"items": (0..200).collect::<Vec<_>>(), "rows": [{"a": 1}, {"a": 2}]});
+    let full = serde_toon::to_string_with_options(&input, &options).unwrap();
+    let exact = options
+        .clone()
+        .with_line_limit(full.len(), LineLimitPolicy::Error);
+    assert_eq!(
+        serde_toon::to_string_with_options(&input, &exact).unwrap(),
+        full
+    );
+    let short = options
+        .clone()
+        .with_line_limit(full.len() - 1, LineLimitPolicy::Error);
+    assert!(serde_toon::to_string_with_options(&input, &short).is_err());
+
+    let options = options.with_line_limit(60, LineLimitPolicy::Truncate);
+    let toon = serde_toon::to_string_with_options(&input, &options).unwrap();
+    assert!(toon.len() <= 60, "{}", toon.len());
+}
+
+#[test]
+fn spec15_line_limit_drops_keys_whose_children_were_cut() {
+    let input = json!({"name": "app", "server": {"tls": {"cert": "x".repeat(64)}}});
+    let options = EncodeOptions::new().with_line_limit(40, LineLimitPolicy::Truncate);
+    let toon = serde_toon::to_string_with_options(&input, &options).unwrap();
+    assert_eq!(toon, "name: app\n# truncated");
+
+    assert!(serde_toon::from_str::<Value>(&toon).is_err());
+    let decoded: Value =
+        serde_toon::from_str_with_options(&toon, &DecodeOptions::new().with_allow_comments(true))
+            .unwrap();
+    assert_eq!(decoded, json!({"name": "app"}));
+}
+
+fn nested_objects(depth: usize) -> String {
+    (0..depth)
+        .map(|level| format!("{}a:", "  ".repeat(level)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn nested_lists(depth: usize) -> String {
+    let mut input = String::from("[1]:");
+    for level in 1..depth {
+        input.push_str(&format!("\n{}- [1]:", "  ".repeat(level)));
+    }
+    input.push_str(&format!("\n{}- 1", "  ".repeat(depth)));
+    input
+}
+
+fn assert_depth_error(input: &str, options: &DecodeOptions) {
+    for err in [
+        serde_toon::from_str_with_options::<Value>(input, options).unwrap_err(),
+        serde_toon::decode_to_value_with_options(input, options).unwrap_err(),
+        serde_toon::validate_str_with_options(input, options).unwrap_err(),
+        serde_toon::validate_reader_with_options(input.as_bytes(), options).unwrap_err(),
+    ] {
+        assert!(
+            err.to_string().contains("maximum nesting depth exceeded"),
+            "{err}"
+        );
+    }
+}
+
+#[rstest]
+#[case(nested_objects(10_000))]
+#[case(nested_lists(10_000))]
+fn spec15_deep_nesting_errors_instead_of_overflowing(#[case] input: String) {
+    assert_depth_error(&input, &DecodeOptions::default());
+}
+
+#[test]
+fn spec15_max_depth_is_configurable() {
+    let input = nested_objects(10);
+    let value: Value = serde_toon::from_str(&input).unwrap();
+    assert_eq!(value.pointer("/a/a/a/a/a/a/a/a/a/a"), Some(&json!({})));
+    assert!(serde_toon::validate_str(&input).is_ok());
+
+    assert_depth_error(&input, &DecodeOptions::new().with_max_depth(10));
+    let options = DecodeOptions::new().with_max_depth(11);
+    assert!(serde_toon::from_str_with_options::<Value>(&input, &options).is_ok());
+    assert!(serde_toon::validate_reader_with_options(input.as_bytes(), &options).is_ok());
+}
+
+#[test]
+fn spec15_max_depth_covers_expanded_paths() {
+    let key = vec!["a"; 200].join(".");
+    let input = format!("{key}: 1");
+    let options = DecodeOptions::new().with_expand_paths(ToonExpandPaths::Safe);
+    let err = serde_toon::from_str_with_options::<Value>(&input, &options).unwrap_err();
+    assert!(
+        err.to_string().contains("maximum nesting depth exceeded"),
+        "{err}"
+    );
+    let options = options.with_max_depth(256);
+    assert!(serde_toon::from_str_with_options::<Value>(&input, &options).is_ok());
+}
+
+#[derive(Default)]
+struct CountingWriter {
+    bytes: Vec<u8>,
+    max_write: usize,
+}
+
+impl std::io::Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.max_write = self.max_write.max(buf.len());
+        self.bytes.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[rstest]
+#[case(EncodeOptions::default())]
+#[case(EncodeOptions::default().with_checksum(true))]
+#[case(EncodeOptions::default().with_section_spacing(true))]
+fn spec15_to_writer_streaming_bounds_buffered_bytes(#[case] options: EncodeOptions) {
+    let rows: Vec<Value> = (0..100_000)
+        .map(|id| json!({"id": id, "name": format!("user-{id}"), "active": id % 2 == 0}))
+        .collect();
+    let value = json!({"meta": {"count": rows.len()}, "users": rows});
+    let expected = serde_toon::to_string_with_options(&value, &options).unwrap();
+
+    let mut writer = CountingWriter::default();
+    serde_toon::to_writer_streaming(&mut writer, &value, &options, 4096).unwrap();
+    assert!(
+        writer.max_write < 8192,
+        "buffered {} bytes",
+        writer.max_write
+    );
+    assert_eq!(String::from_utf8(writer.bytes).unwrap(), expected);
+
+    let mut writer = CountingWriter::default();
+    serde_toon::to_writer_with_options(&mut writer, &value, &options).unwrap();
+    assert!(writer.max_write < expected.len() / 4);
+    assert_eq!(String::from_utf8(writer.bytes).unwrap(), expected);
+}
+
+#[test]
+fn spec15_to_writer_streaming_enforces_line_limit() {
+    let value = json!({"items": (0..1000).collect::<Vec<_>>(), "rows": [{"a": 1}, {"a": 2}]});
+    let options = EncodeOptions::default().with_line_limit(64, LineLimitPolicy::Error);
+    let mut writer = CountingWriter::default();
+    assert!(serde_toon::to_writer_streaming(&mut writer, &value, &options, 16).is_err());
+    assert!(writer.bytes.is_empty());
+    let options = options.with_line_limit(64, LineLimitPolicy::Truncate);
+    let mut writer = CountingWriter::default();
+    serde_toon::to_writer_streaming(&mut writer, &value, &options, 16).unwrap();
+    assert_eq!(
+        String::from_utf8(writer.bytes).unwrap(),
+        serde_toon::to_string_with_options(&value, &options).unwrap()
+    );
+}
@@ -188,3 +188,22 @@ fn spec16_internationalization_validate(#[case] input: &str, #[case] valid: bool
         assert!(result.is_err());
     }
 }
+
+#[rstest]
+#[case::ascii("users[2]{id,name}:\n  1,Ada\n  2,Bob\nnote: \"a, b\"")]
+#[case::non_ascii("users[2]{id,name}:\n  1,Zoë\n  2,李\nnote: \"😀, b\"")]
+#[case::escapes("note: \"tab\\there\"\nlist[2]: x,y")]
+fn spec16_from_slice_matches_from_str(#[case] input: &str) {
+    let from_str: Value = serde_toon::from_str(input).unwrap();
+    let from_slice: Value = serde_toon::from_slice(input.as_bytes()).unwrap();
+    assert_eq!(from_slice, from_str);
+}
+
+#[rstest]
+#[case::lone_continuation(b"a: \x80".as_slice())]
+#[case::truncated_sequence(b"a: caf\xc3".as_slice())]
+#[case::after_valid_non_ascii("a: é\nb: ".bytes().chain([0xff]).collect::<Vec<u8>>())]
+fn spec16_from_slice_rejects_invalid_utf8(#[case] input: impl AsRef<[u8]>) {
+    let err = serde_toon::from_slice::<Value>(input.as_ref()).unwrap_err();
+    assert!(err.to_string().contains("invalid utf-8"), "{err}");
+}
@@ -0,0 +1,59 @@
+#![cfg(feature = "stats")]
+
+use rstest::rstest;
+use serde_json::{json, Value};
+use serde_toon::{encode_stats, encode_with_stats, estimate_tokens, EncodeOptions};
+
+const GITHUB_REPOS_JSON: &str = include_str!("../benchmarks/data/github-repos.json");
+
+#[test]
+fn github_repos_take_fewer_tokens_than_json() {
+    let value: Value = serde_json::from_str(GITHUB_REPOS_JSON).unwrap();
+    let (toon, stats) = encode_with_stats(&value, &EncodeOptions::default()).unwrap();
+    assert_eq!(toon, serde_toon::to_string(&value).unwrap());
+    assert_eq!(stats.bytes, toon.len());
+    assert!(stats.toon_tokens > 0);
+    assert!(
+        stats.toon_tokens <= stats.json_tokens,
+        "toon {} > json {}",
+        stats.toon_tokens,
+        stats.json_tokens
+    );
+    assert_eq!(
+        stats.saved_tokens(),
+        stats.json_tokens as isize - stats.toon_tokens as isize
+    );
+}
+
+#[test]
+fn encode_stats_counts_the_given_output() {
+    let value = json!({"a": 1});
+    let stats = encode_stats(&value, "a: 1").unwrap();
+    assert_eq!(stats.bytes, 4);
+    assert_eq!(stats, encode_stats(&value, "a: 1").unwrap());
+    assert!(stats.json_tokens > stats.toon_tokens, "{stats:?}");
+}
+
+// The tolerances are the accuracy documented on `estimate_tokens`.
+#[rstest]
+#[case("github-repos", 0.10)]
+#[case("peanuts_characters", 0.10)]
+#[case("peanuts_specials", 0.10)]
+#[case("peanuts_universe", 0.10)]
+#[case("peanuts_jsonld", 0.25)]
+fn estimate_tokens_tracks_cl100k_base(#[case] name: &str, #[case] tolerance: f64) {
+    let path = format!("{}/benchmarks/data/{name}.json", env!("CARGO_MANIFEST_DIR"));
+    let value: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    let (toon, stats) = encode_with_stats(&value, &EncodeOptions::default()).unwrap();
+    for (text, actual) in [
+        (toon, stats.toon_tokens),
+        (value.to_string(), stats.json_tokens),
+    ] {
+        let estimate = estimate_tokens(&text);
+        let error = (estimate as f64 - actual as f64).abs() / actual as f64;
+        assert!(
+            error <= tolerance,
+            "{name}: estimated {estimate}, cl100k_base counted {actual}"
+        );
+    }
+}
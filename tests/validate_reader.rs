@@ -0,0 +1,220 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+use serde_json::{json, Value};
+use serde_toon::{DecodeOptions, EncodeOptions, Indent, Location, Severity, ToonDiagnostic};
+
+/// Hands out one byte per `read` call to exercise line assembly.
+struct Trickle<'a>(&'a [u8]);
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let Some((first, rest)) = self.0.split_first() else {
+            return Ok(0);
+        };
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        buf[0] = *first;
+        self.0 = rest;
+        Ok(1)
+    }
+}
+
+fn location(input: &str, options: &DecodeOptions) -> Location {
+    let err = serde_toon::validate_reader_with_options(input.as_bytes(), options).unwrap_err();
+    err.location.expect("validation error without a location")
+}
+
+#[test]
+fn validate_reader_agrees_with_validate_str_on_fixtures() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/decode");
+    let mut checked = 0;
+    for entry in fs::read_dir(root).unwrap() {
+        let fixture: Value =
+            serde_json::from_str(&fs::read_to_string(entry.unwrap().path()).unwrap()).unwrap();
+        for case in fixture["tests"].as_array().unwrap() {
+            let Some(input) = case["input"].as_str() else {
+                continue;
+            };
+            let indent = case["options"]["indent"].as_u64().unwrap_or(2) as usize;
+            for strict in [true, false] {
+                let options = DecodeOptions::new()
+                    .with_strict(strict)
+                    .with_indent(Indent::Spaces(indent));
+                let expected = serde_toon::validate_str_with_options(input, &options);
+                let streamed =
+                    serde_toon::validate_reader_with_options(Trickle(input.as_bytes()), &options);
+                assert_eq!(
+                    streamed.is_ok(),
+                    expected.is_ok(),
+                    "{} (strict: {strict}): {input:?}",
+                    case["name"]
+                );
+                checked += 1;
+            }
+        }
+    }
+    assert!(checked > 100);
+}
+
+#[test]
+fn validate_reader_accepts_encoder_output() {
+    let value = json!({
+        "name": "Ada",
+        "tags": ["a", "b"],
+        "rows": [{"id": 1, "ok": true}, {"id": 2, "ok": false}],
+        "items": [{"kind": "x", "parts": [1, 2]}, [1, 2], "plain"],
+        "meta": {"nested": {"deep": null}},
+    });
+    let encoded = serde_toon::to_string(&value).unwrap();
+    serde_toon::validate_reader(encoded.as_bytes()).unwrap();
+
+    let options = EncodeOptions::new().with_checksum(true);
+    let encoded = serde_toon::to_string_with_options(&value, &options).unwrap();
+    let verify = DecodeOptions::new().with_verify_checksum(true);
+    serde_toon::validate_reader_with_options(encoded.as_bytes(), &verify).unwrap();
+    let tampered = encoded.replacen("Ada", "Bob", 1);
+    let err = serde_toon::validate_reader_with_options(tampered.as_bytes(), &verify).unwrap_err();
+    assert!(err.message.starts_with("checksum mismatch"), "{err}");
+}
+
+#[test]
+fn validate_reader_reports_first_violation_location() {
+    let options = DecodeOptions::new();
+
+    let input = "a:\n  b: 1\n   c: 2\nd: 3";
+    assert_eq!(
+        location(input, &options),
+        Location {
+            offset: 13,
+            line: 3,
+            column: 4
+        }
+    );
+
+    let input = "a: 1\nitems[3]:\n  - x\n  - y";
+    let err = serde_toon::validate_reader(input.as_bytes()).unwrap_err();
+    assert_eq!(err.message, "array length mismatch");
+    assert!(err.is_recoverable());
+    assert_eq!(err.location.unwrap().line, 2);
+
+    let input = "rows[2]{a,b}:\n  1,2\n  3";
+    let err = serde_toon::validate_reader(input.as_bytes()).unwrap_err();
+    assert_eq!(err.message, "tabular row field count mismatch");
+    assert_eq!(
+        err.location,
+        Some(Location {
+            offset: 22,
+            line: 3,
+            column: 3
+        })
+    );
+
+    assert_eq!(location("a: 1 \nb: 2", &options).column, 5);
+    assert_eq!(location("a: 1\nb: 2\n", &options).line, 3);
+}
+
+#[test]
+fn validate_str_reports_the_same_locations_as_validate_reader() {
+    let options = DecodeOptions::new();
+    for input in [
+        "a:\n  b: 1\n   c: 2\nd: 3",
+        "a: 1\nitems[3]:\n  - x\n  - y",
+        "rows[2]{a,b}:\n  1,2\n  3",
+        "a: 1 \nb: 2",
+    ] {
+        let err = serde_toon::validate_str_with_options(input, &options).unwrap_err();
+        assert_eq!(
+            err.location.map(|at| at.line),
+            Some(location(input, &options).line)
+        );
+    }
+}
+
+fn diagnostic(line: usize, column: usize, message: &str, severity: Severity) -> ToonDiagnostic {
+    ToonDiagnostic {
+        line,
+        column,
+        message: message.to_string(),
+        severity,
+    }
+}
+
+#[test]
+fn validate_collect_reports_every_problem() {
+    let input =
+        "items[3]: a,b\nuser:\n   name: Ada\n  age: 37\ntags[2]:\n  - x\n  - y\n  - z\nok: true";
+    let diagnostics = serde_toon::validate_collect(input, &DecodeOptions::default());
+    assert_eq!(
+        diagnostics,
+        vec![
+            diagnostic(1, 1, "array length mismatch", Severity::Error),
+            diagnostic(3, 4, "invalid indentation", Severity::Error),
+            diagnostic(5, 1, "array length mismatch", Severity::Error),
+        ]
+    );
+    assert!(serde_toon::validate_str(input).is_err());
+}
+
+#[test]
+fn misindented_lines_point_past_the_indentation_everywhere() {
+    let input = "a:\n  b: 1\n   c: 2\nd: 3";
+    let options = DecodeOptions::default();
+    let expected = Location {
+        offset: 13,
+        line: 3,
+        column: 4,
+    };
+    let decoded = serde_toon::decode_to_value_with_options(input, &options).unwrap_err();
+    assert_eq!(decoded.message, "invalid indentation");
+    assert_eq!(decoded.location, Some(expected));
+    let validated = serde_toon::validate_str_with_options(input, &options).unwrap_err();
+    assert_eq!(validated.location, Some(expected));
+    assert_eq!(location(input, &options), expected);
+    assert_eq!(
+        serde_toon::validate_collect(input, &options)[0],
+        diagnostic(3, 4, "invalid indentation", Severity::Error)
+    );
+}
+
+#[test]
+fn validate_collect_reads_the_block_after_a_duplicate_key() {
+    let input =
+        "rows[2]{a}:\n  1\n  2\nrows[2]{a}:\n  3\n  4\nuser:\n  a: 1\nuser:\n  b: 2\nok: true";
+    let diagnostics = serde_toon::validate_collect(input, &DecodeOptions::default());
+    assert_eq!(
+        diagnostics,
+        vec![
+            diagnostic(4, 1, "duplicate key 'rows'", Severity::Error),
+            diagnostic(9, 1, "duplicate key 'user'", Severity::Error),
+        ]
+    );
+}
+
+#[test]
+fn validate_collect_downgrades_tolerated_indentation_to_warnings() {
+    let input = "user:\n   name: Ada\n  age: 37";
+    let options = DecodeOptions::default().with_indent_warnings(true);
+    let diagnostics = serde_toon::validate_collect(input, &options);
+    assert_eq!(
+        diagnostics,
+        vec![diagnostic(
+            2,
+            4,
+            "indent of 3 spaces is not a multiple of the indent size; read as level 1",
+            Severity::Warning
+        )]
+    );
+    assert!(serde_toon::validate_str_with_options(input, &options).is_ok());
+}
+
+#[test]
+fn validate_collect_is_empty_for_valid_documents() {
+    let input = serde_toon::to_string(&json!({"a": [1, 2], "b": {"c": "d"}})).unwrap();
+    assert!(serde_toon::validate_collect(&input, &DecodeOptions::default()).is_empty());
+    let json = serde_toon::validate_collect("{\"a\": 1}", &DecodeOptions::default());
+    assert_eq!(json.len(), 1);
+    assert_eq!(json[0].severity, Severity::Error);
+}